@@ -1,9 +1,9 @@
-/// Example: WebSocket-based Lichess game integration
-/// 
-/// This example demonstrates how to use the WebSocket infrastructure
-/// for real-time Lichess game communication.
+//! Example: WebSocket-based Lichess game integration
+//! 
+//! This example demonstrates how to use the WebSocket infrastructure
+//! for real-time Lichess game communication.
 
-use chess_tui::lichess_ws::LichessWebSocket;
+use chess_tui::lichess_ws::{GameEvent, LichessWebSocket, KEEPALIVE_INTERVAL};
 use chess_tui::auto_move::AutoMoveController;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::channel;
@@ -33,19 +33,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _message_thread = thread::spawn(move || {
         loop {
             let ws = ws_clone.lock().unwrap();
-            match ws.process_messages() {
-                Ok(messages) => {
-                    for msg in messages {
-                        if msg.starts_with("MOVE:") {
-                            let uci = &msg[5..];
-                            println!("Received move: {}", uci);
-                            let _ = move_tx.send(uci.to_string());
-                        } else if msg == "GAME_END" {
-                            println!("Game ended!");
-                            break;
-                        } else if msg.starts_with("FEN:") {
-                            let fen = &msg[4..];
-                            println!("Position update: {}", fen);
+            // Ping every few seconds, or Lichess drops the socket while we think
+            if let Err(e) = ws.tick_keepalive(KEEPALIVE_INTERVAL) {
+                eprintln!("Failed to ping: {}", e);
+            }
+            match ws.process_events() {
+                Ok(events) => {
+                    for event in events {
+                        match event {
+                            GameEvent::Move(uci) => {
+                                println!("Received move: {}", uci);
+                                let _ = move_tx.send(uci);
+                            }
+                            GameEvent::GameEnd(_) => {
+                                println!("Game ended!");
+                                break;
+                            }
+                            GameEvent::Fen(fen) => println!("Position update: {}", fen),
+                            _ => {}
                         }
                     }
                 }
@@ -95,6 +100,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 // 3. In the main event loop (tick):
 //    if let Some(ws) = &app.lichess_ws {
 //        let ws = ws.lock().unwrap();
+//        ws.tick_keepalive(KEEPALIVE_INTERVAL)?;
 //        let messages = ws.process_messages()?;
 //        for msg in messages {
 //            // Handle moves, game end, etc.
@@ -1,6 +1,10 @@
 use crate::config::Config;
-use crate::constants::config_dir;
-use crate::constants::{DisplayMode, Pages, Popups, NETWORK_PORT, SLEEP_DURATION_LONG_MS};
+use crate::constants::data_dir;
+use crate::constants::{
+    DisplayMode, InputMethod, MoveNotation, Pages, Popups, SpectatorOrientation,
+    ACK_FLASH_DURATION_MS, NETWORK_PORT, SLEEP_DURATION_LONG_MS,
+};
+use crate::engine::DEEP_ANALYSIS_DEPTH;
 use crate::game_logic::bot::Bot;
 use crate::game_logic::coord::Coord;
 use crate::game_logic::game::Game;
@@ -8,17 +12,21 @@ use crate::game_logic::game::GameState;
 use crate::game_logic::opponent::wait_for_game_start;
 use crate::game_logic::opponent::{Opponent, OpponentKind};
 use crate::game_logic::puzzle::PuzzleGame;
-use crate::lichess::LichessClient;
+use crate::lichess::{
+    validate_with_retry, LichessClient, UserProfile, ValidationError, VALIDATE_ATTEMPTS,
+    VALIDATE_BACKOFF,
+};
+use crate::lichess_ws::{ClockData, GameEvent};
 use crate::server::game_server::GameServer;
 use crate::skin::Skin;
 use crate::utils::flip_square_if_needed;
 use log::LevelFilter;
-use shakmaty::{Color, Move, Position};
+use shakmaty::{Chess, Color, EnPassantMode, Move, Position};
 use std::error;
 use std::fs::{self, File};
 use std::io::Write;
 use std::net::{IpAddr, UdpSocket};
-use std::sync::mpsc::{channel, Receiver};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -86,6 +94,94 @@ pub struct App {
     pub lichess_ws: Option<std::sync::Arc<std::sync::Mutex<crate::lichess_ws::LichessWebSocket>>>,
     /// Auto-move controller
     pub auto_move_controller: Option<crate::auto_move::AutoMoveController>,
+    /// Fair-play lock: disables engine assistance and auto-move while engaged
+    pub fair_play_lock: bool,
+    /// Whether the current Lichess game is rated (forces the fair-play lock)
+    pub current_game_rated: bool,
+    /// Whether the rated game engaged the lock, rather than the user, so it's
+    /// released again once the game is over
+    pub fair_play_lock_forced: bool,
+    /// Delay range (min, max) in ms before reacting to the opponent's move, off if None
+    pub reaction_delay_ms: Option<(u64, u64)>,
+    /// Ordered sources auto-move tries to get a move from
+    pub auto_move_sources: Vec<crate::auto_move::MoveSource>,
+    /// Auto-move only plays the opening from the book, then hands back control
+    pub auto_move_book_only: bool,
+    /// Last full move book-only auto-move may play (no limit if None)
+    pub auto_move_book_depth: Option<u32>,
+    /// Resign clearly lost positions during auto-play instead of flagging
+    pub courtesy_resign: bool,
+    /// Centipawn deficit that counts as clearly lost (default if unset)
+    pub courtesy_resign_cp: Option<i32>,
+    /// Log unknown socket messages at warn level (for reporting protocol additions)
+    pub ws_strict_mode: bool,
+    /// What the ack field of moves sent over the WebSocket carries
+    pub ws_ack_scheme: crate::lichess_ws::AckScheme,
+    /// Game socket host (DEFAULT_WS_HOST if None)
+    pub ws_host: Option<String>,
+    /// How pieces are moved on the board (two-click, drag or typed)
+    pub input_method: InputMethod,
+    /// Board orientation while spectating Lichess TV
+    pub spectator_orientation: SpectatorOrientation,
+    /// Notation of the moves in the move list
+    pub move_notation: MoveNotation,
+    /// Confirm keys this soon after a played move are ignored (0 disables)
+    pub move_debounce_ms: u64,
+    /// When the last move confirmed from the keyboard was played
+    pub last_move_confirm: Option<std::time::Instant>,
+    /// When the opponent left, the moment victory becomes claimable
+    pub opponent_gone_deadline: Option<std::time::Instant>,
+    /// Seed for reproducible SRI and auto-move randomness (random if None)
+    pub rng_seed: Option<u64>,
+    /// Lichess TV feed receiver (spectating the featured game)
+    pub tv_feed_receiver: Option<Receiver<crate::lichess::TvFeedFrame>>,
+    /// Whether the Lichess game's threefold repetition was claimed with a draw offer
+    pub threefold_draw_claimed: bool,
+    /// Receiver for the game's PGN, refetched when endData contradicts the board
+    pub end_sync_receiver: Option<Receiver<Result<String, String>>>,
+    /// Feeds opponent moves received over the WebSocket to the LichessWs opponent
+    pub lichess_ws_move_tx: Option<Sender<String>>,
+    /// Final status and winner reported by the server's endData
+    pub lichess_end_data: Option<(String, String)>,
+    /// Remaining time (white, black) in ms from the last socket snapshot or simulated move
+    pub lichess_clocks_ms: Option<(u64, u64)>,
+    /// When those clocks were synced, for counting down between updates; None once
+    /// they're stopped
+    pub lichess_clocks_synced_at: Option<std::time::Instant>,
+    /// Chat of the connected Lichess game, oldest first (at most lichess_ws::CHAT_LINES)
+    pub lichess_chat: Vec<crate::lichess_ws::ChatData>,
+    /// Who is connected to the Lichess game, from its last crowd update
+    pub lichess_presence: Option<crate::lichess_ws::CrowdData>,
+    /// Players of the featured game currently shown in TV mode
+    pub tv_featured_players: Option<String>,
+    /// PGN being played back as a live game (--simulate)
+    pub simulation: Option<crate::pgn::Simulation>,
+    /// Opponent, time control and other details of the connected Lichess game
+    pub game_meta: Option<crate::lichess::GameMeta>,
+    /// Write every finished game's PGN to `pgn_dir`
+    pub auto_save_pgn: bool,
+    /// Where finished games are saved (data_dir/games/pgn if None)
+    pub pgn_dir: Option<std::path::PathBuf>,
+    /// Rejoin the last session's still running game at startup without asking
+    pub auto_resume_game: bool,
+    /// Whether the current game's record was already written
+    pub game_record_saved: bool,
+    /// Board history longer than this is trimmed from the front
+    pub max_history_plies: usize,
+    /// Flash the board when the server acknowledges our move
+    pub ack_flash: bool,
+    /// Draw the file letters and rank numbers around the board
+    pub show_coordinates: bool,
+    /// Draw the evaluation bar while a deep analysis has a score
+    pub show_eval_bar: bool,
+    /// Where the Lichess session's credentials are saved
+    pub auth_backend: crate::lichess_auth::AuthBackend,
+    /// When the current ack flash ends
+    pub ack_flash_until: Option<std::time::Instant>,
+    /// On-demand deep analysis still searching
+    pub deep_analysis: Option<crate::engine::AnalysisJob>,
+    /// Last deep analysis result, with the FEN it's for
+    pub deep_analysis_result: Option<(String, crate::engine::Analysis)>,
 }
 
 impl Default for App {
@@ -120,6 +216,49 @@ impl Default for App {
             lichess_session: None,
             lichess_ws: None,
             auto_move_controller: None,
+            fair_play_lock: false,
+            current_game_rated: false,
+            fair_play_lock_forced: false,
+            reaction_delay_ms: None,
+            auto_move_sources: crate::auto_move::MoveSource::default_chain(),
+            auto_move_book_only: false,
+            auto_move_book_depth: None,
+            courtesy_resign: false,
+            courtesy_resign_cp: None,
+            ws_strict_mode: false,
+            ws_ack_scheme: crate::lichess_ws::AckScheme::default(),
+            ws_host: None,
+            input_method: InputMethod::default(),
+            spectator_orientation: SpectatorOrientation::default(),
+            move_notation: MoveNotation::default(),
+            move_debounce_ms: crate::constants::DEFAULT_MOVE_DEBOUNCE_MS,
+            last_move_confirm: None,
+            opponent_gone_deadline: None,
+            rng_seed: None,
+            tv_feed_receiver: None,
+            end_sync_receiver: None,
+            lichess_ws_move_tx: None,
+            lichess_end_data: None,
+            lichess_clocks_ms: None,
+            lichess_clocks_synced_at: None,
+            threefold_draw_claimed: false,
+            lichess_chat: Vec::new(),
+            lichess_presence: None,
+            tv_featured_players: None,
+            simulation: None,
+            game_meta: None,
+            auto_save_pgn: false,
+            auto_resume_game: false,
+            pgn_dir: None,
+            game_record_saved: false,
+            max_history_plies: crate::constants::DEFAULT_MAX_HISTORY_PLIES,
+            ack_flash: false,
+            show_coordinates: true,
+            show_eval_bar: true,
+            auth_backend: crate::lichess_auth::AuthBackend::default(),
+            ack_flash_until: None,
+            deep_analysis: None,
+            deep_analysis_result: None,
         }
     }
 }
@@ -139,6 +278,138 @@ impl App {
             self.current_popup = Some(Popups::PuzzleEndScreen);
         } else {
             self.current_popup = Some(Popups::EndScreen);
+            self.auto_save_game_record();
+        }
+    }
+
+    /// Result of the finished game in PGN form: the server's verdict for Lichess
+    /// games, else mate or draw on the board. None while the game goes on, or if aborted.
+    pub fn game_result(&self) -> Option<&'static str> {
+        if let Some((status, winner)) = &self.lichess_end_data {
+            return match (status.as_str(), winner.as_str()) {
+                ("aborted", _) => None,
+                (_, "white") => Some("1-0"),
+                (_, "black") => Some("0-1"),
+                _ => Some("1/2-1/2"),
+            };
+        }
+        let board = &self.game.logic.game_board;
+        if board.is_checkmate() {
+            Some(match board.position_ref().turn() {
+                Color::White => "0-1",
+                Color::Black => "1-0",
+            })
+        } else if board.is_draw() {
+            Some("1/2-1/2")
+        } else {
+            None
+        }
+    }
+
+    /// Write the current game's PGN to the record directory and return its path.
+    /// The file is named after the date, the opponent and the game id.
+    pub fn save_game_record(&self) -> Result<std::path::PathBuf, Box<dyn error::Error>> {
+        let now = chrono::Local::now();
+        let result = self.game_result().unwrap_or("*");
+        let is_lichess = self.game_meta.is_some();
+        let game_id = self
+            .game_meta
+            .as_ref()
+            .and_then(|meta| meta.game_id.clone());
+
+        // Our name against the opponent's, on the sides we played
+        let (opponent, me) = if let Some(meta) = &self.game_meta {
+            let me = self
+                .lichess_user_profile
+                .as_ref()
+                .map_or("?".to_string(), |profile| profile.username.clone());
+            let opponent = meta.opponent_name.as_deref().unwrap_or("Anonymous");
+            (opponent.to_string(), me)
+        } else if self.game.logic.bot.is_some() {
+            ("Bot".to_string(), "Player".to_string())
+        } else {
+            ("Local".to_string(), "Local".to_string())
+        };
+        let (white, black) = match self.selected_color {
+            Some(Color::Black) => (opponent.clone(), me),
+            _ => (me, opponent.clone()),
+        };
+
+        let event = if is_lichess {
+            "Lichess game"
+        } else {
+            "Casual game"
+        };
+        let site = match &game_id {
+            Some(id) => format!("https://lichess.org/{}", id),
+            None => "chess-tui".to_string(),
+        };
+        let mut headers = vec![
+            ("Event".to_string(), event.to_string()),
+            ("Site".to_string(), site),
+            ("Date".to_string(), now.format("%Y.%m.%d").to_string()),
+            ("Round".to_string(), "-".to_string()),
+            ("White".to_string(), white),
+            ("Black".to_string(), black),
+            ("Result".to_string(), result.to_string()),
+        ];
+        if let Some((initial, increment)) = self.game_meta.as_ref().and_then(|meta| meta.clock) {
+            let time_control = format!("{}+{}", initial, increment);
+            headers.push(("TimeControl".to_string(), time_control));
+        }
+        let positions = &self.game.logic.game_board.position_history;
+        // Games that didn't start from the initial position carry it as a FEN tag
+        if let Some(start) = positions
+            .first()
+            .filter(|start| **start != Chess::default())
+        {
+            let fen = shakmaty::fen::Fen::from_position(start.clone(), EnPassantMode::Legal);
+            headers.push(("SetUp".to_string(), "1".to_string()));
+            headers.push(("FEN".to_string(), fen.to_string()));
+        }
+
+        let pgn = crate::pgn::export_pgn(&headers, positions, result);
+        let dir = match &self.pgn_dir {
+            Some(dir) => dir.clone(),
+            None => data_dir()?.join("games").join("pgn"),
+        };
+        let stem = crate::pgn::game_file_stem(
+            &now.format("%Y-%m-%d").to_string(),
+            &opponent,
+            game_id.as_deref().unwrap_or("offline"),
+        );
+        Ok(crate::pgn::save_pgn(&dir, &stem, &pgn)?)
+    }
+
+    /// Save the game's record when asked from the end screen, reporting failures in a popup
+    pub fn save_game_on_request(&mut self) {
+        match self.save_game_record() {
+            Ok(path) => {
+                log::info!("Saved game record to {}", path.display());
+                self.game_record_saved = true;
+            }
+            Err(e) => {
+                log::error!("Failed to save game record: {}", e);
+                self.error_message = Some(format!("Failed to save the game: {}", e));
+                self.current_popup = Some(Popups::Error);
+            }
+        }
+    }
+
+    /// Save the finished game's record once, if enabled. Simulations replay a game we
+    /// already have, and aborted games have no result, so neither is saved.
+    fn auto_save_game_record(&mut self) {
+        if !self.auto_save_pgn
+            || self.game_record_saved
+            || self.simulation.is_some()
+            || self.game_result().is_none()
+        {
+            return;
+        }
+        self.game_record_saved = true;
+        match self.save_game_record() {
+            Ok(path) => log::info!("Saved game record to {}", path.display()),
+            Err(e) => log::error!("Failed to save game record: {}", e),
         }
     }
     pub fn toggle_credit_popup(&mut self) {
@@ -488,6 +759,9 @@ impl App {
         initial_move_count: usize,
         immediate_last_move: Option<String>,
     ) {
+        // Never leave the previous game's connection running in the background
+        self.end_current_game();
+
         if let Some(token) = &self.lichess_token {
             let client = LichessClient::new(token.clone());
             let (lichess_to_app_tx, lichess_to_app_rx) = channel::<String>();
@@ -530,6 +804,27 @@ impl App {
                 }
             });
 
+            let is_rated = self
+                .ongoing_games
+                .iter()
+                .find(|g| g.game_id == game_id)
+                .map(|g| g.rated)
+                .unwrap_or(false);
+            self.game_meta = Some(self.game_meta_for(&game_id));
+
+            let color_name = if color == Color::White {
+                "white"
+            } else {
+                "black"
+            };
+            let saved = crate::lichess::SavedGame {
+                game_id: game_id.clone(),
+                color: color_name.to_string(),
+            };
+            if let Err(e) = saved.save() {
+                log::warn!("Failed to save the current game: {}", e);
+            }
+
             let opponent = Opponent::new_lichess(
                 game_id,
                 color,
@@ -542,6 +837,8 @@ impl App {
             self.selected_color = Some(color);
             self.game.logic.opponent = Some(opponent);
 
+            self.engage_rated_game_lock(is_rated);
+
             if color == Color::Black {
                 self.game.logic.game_board.flip_the_board();
             }
@@ -582,7 +879,30 @@ impl App {
         }
     }
 
+    /// Ask before resigning the Lichess game being played
+    pub fn request_resign(&mut self) {
+        if self.lichess_ws.is_some() && self.has_live_game() {
+            self.current_popup = Some(Popups::ResignConfirmation);
+        }
+    }
+
     pub fn confirm_resign_game(&mut self) {
+        // The game on the board goes through its socket, menu games through the API
+        if self.lichess_ws.is_some() && self.has_live_game() {
+            self.current_popup = None;
+            if let Some(ws_arc) = self.lichess_ws.as_ref() {
+                let result = ws_arc
+                    .lock()
+                    .map_err(|_| "WebSocket unavailable".to_string())
+                    .and_then(|ws| ws.resign());
+                if let Err(e) = result {
+                    log::error!("Failed to resign: {}", e);
+                    self.error_message = Some(format!("Failed to resign.\n\n{}", e));
+                    self.current_popup = Some(Popups::Error);
+                }
+            }
+            return;
+        }
         if let Some(game) = self.ongoing_games.get(self.menu_cursor as usize) {
             let game_id = game.game_id.clone();
             let opponent_name = game.opponent.username.clone();
@@ -659,6 +979,103 @@ impl App {
         }
     }
 
+    /// Check the configured token once at startup, retrying network blips.
+    /// Only an expired token or closed account drops it; otherwise we carry on offline.
+    pub fn validate_lichess_token_at_startup(&mut self) {
+        let Some(token) = self.lichess_token.clone().filter(|t| !t.is_empty()) else {
+            return;
+        };
+        let client = LichessClient::new(token);
+        let result = validate_with_retry(VALIDATE_ATTEMPTS, VALIDATE_BACKOFF, || client.validate());
+        self.apply_token_validation(result);
+    }
+
+    /// Act on the outcome of a startup token check
+    pub fn apply_token_validation(&mut self, result: Result<UserProfile, ValidationError>) {
+        match result {
+            Ok(profile) => {
+                log::info!("Lichess token valid for {}", profile.username);
+                self.lichess_user_profile = Some(profile);
+            }
+            Err(ValidationError::Unauthorized) => {
+                log::warn!("Lichess token rejected at startup");
+                // Dropped for this session so the Lichess menu asks for a new one
+                self.lichess_token = None;
+                self.lichess_user_profile = None;
+                self.error_message = Some(format!(
+                    "{}.\n\n Open the Lichess menu to enter a new token.",
+                    ValidationError::Unauthorized
+                ));
+                self.current_popup = Some(Popups::Error);
+            }
+            Err(ValidationError::AccountClosed) => {
+                log::warn!("Lichess account closed or disabled");
+                self.lichess_token = None;
+                self.lichess_user_profile = None;
+                self.error_message = Some(format!(
+                    "{}.\n\n Lichess features are disabled for this session.",
+                    ValidationError::AccountClosed
+                ));
+                self.current_popup = Some(Popups::Error);
+            }
+            Err(e) => {
+                log::warn!("{}, continuing offline", e);
+                self.error_message = Some(format!(
+                    "{}.\n\n Continuing offline: your token is kept and Lichess features will work once the connection is back.",
+                    e
+                ));
+                self.current_popup = Some(Popups::Error);
+            }
+        }
+    }
+
+    /// Offer to rejoin the game the last session was playing, if it's still going on
+    pub fn check_resumable_game_at_startup(&mut self) {
+        if self.current_popup.is_some() {
+            return;
+        }
+        let (Some(token), Some(saved)) = (&self.lichess_token, crate::lichess::SavedGame::load())
+        else {
+            return;
+        };
+        match LichessClient::new(token.clone()).get_ongoing_games() {
+            Ok(ongoing) => {
+                if !self.offer_resume(&saved, ongoing) {
+                    crate::lichess::SavedGame::clear();
+                }
+            }
+            Err(e) => log::warn!("Cannot check for a game to resume: {}", e),
+        }
+    }
+
+    /// Prompt to resume `saved` (or rejoin it straight away with auto_resume_game).
+    /// Returns false when it's no longer in `ongoing`, i.e. it has ended.
+    pub fn offer_resume(
+        &mut self,
+        saved: &crate::lichess::SavedGame,
+        ongoing: Vec<crate::lichess::OngoingGame>,
+    ) -> bool {
+        let Some(index) = saved.find_in(&ongoing) else {
+            log::info!("Saved game {} has ended, not resuming", saved.game_id);
+            return false;
+        };
+        self.ongoing_games = ongoing;
+        self.menu_cursor = index as u8;
+        if self.auto_resume_game {
+            log::info!("Resuming saved game {}", saved.game_id);
+            self.select_ongoing_game();
+        } else {
+            self.current_popup = Some(Popups::ResumeGame);
+        }
+        true
+    }
+
+    /// Rejoin the game offered by the resume prompt
+    pub fn resume_saved_game(&mut self) {
+        self.current_popup = None;
+        self.select_ongoing_game();
+    }
+
     pub fn select_ongoing_game(&mut self) {
         log::debug!(
             "select_ongoing_game called, menu_cursor: {}",
@@ -689,6 +1106,185 @@ impl App {
         }
     }
 
+    /// Start spectating the Lichess TV featured game (no token needed)
+    pub fn start_lichess_tv(&mut self) {
+        self.current_popup = None;
+        self.error_message = None;
+        self.game.logic.opponent = None;
+        self.game.logic.bot = None;
+        self.game.logic.game_board.reset();
+        self.tv_featured_players = None;
+
+        let (tx, rx) = channel();
+        match LichessClient::stream_tv_feed(tx) {
+            Ok(()) => {
+                self.tv_feed_receiver = Some(rx);
+                self.current_page = Pages::LichessTv;
+                if let Some(skin) = &self.loaded_skin {
+                    self.game.ui.skin = skin.clone();
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to start Lichess TV: {}", e);
+                self.error_message = Some(format!("Failed to start Lichess TV: {}", e));
+                self.current_popup = Some(Popups::Error);
+            }
+        }
+    }
+
+    /// Apply any pending Lichess TV frames to the board
+    pub fn check_lichess_tv(&mut self) {
+        let frames: Vec<crate::lichess::TvFeedFrame> = match &self.tv_feed_receiver {
+            Some(rx) => rx.try_iter().collect(),
+            None => return,
+        };
+
+        let mut featured_flipped = None;
+        for frame in frames {
+            match frame {
+                crate::lichess::TvFeedFrame::Featured(featured) => {
+                    log::info!("Lichess TV featured game: {}", featured.id);
+                    self.tv_featured_players = Some(featured.players_label());
+                    let board = &mut self.game.logic.game_board;
+                    board.reset();
+                    if let Some(position) = crate::lichess::tv_feed_position(&featured.fen, None) {
+                        board.position_history = vec![position];
+                    }
+                    featured_flipped = Some(featured.orientation == "black");
+                }
+                crate::lichess::TvFeedFrame::Fen(fen) => {
+                    let Some(position) =
+                        crate::lichess::tv_feed_position(&fen.fen, fen.lm.as_deref())
+                    else {
+                        log::warn!("Invalid TV feed FEN: {}", fen.fen);
+                        continue;
+                    };
+                    let board = &mut self.game.logic.game_board;
+                    board.position_history = vec![position];
+                    if let Some(lm) = &fen.lm {
+                        board.set_websocket_last_move(lm);
+                    }
+                }
+            }
+        }
+        self.game.logic.sync_player_turn_with_position();
+
+        // Orient once for the whole batch so the board never flickers between frames
+        let want_flipped = match self.spectator_orientation {
+            SpectatorOrientation::Featured => featured_flipped,
+            SpectatorOrientation::FixedWhite => Some(false),
+            SpectatorOrientation::FixedBlack => Some(true),
+            SpectatorOrientation::SideToMove => {
+                Some(self.game.logic.player_turn == Color::Black)
+            }
+        };
+        let board = &mut self.game.logic.game_board;
+        if want_flipped.is_some_and(|flipped| flipped != board.is_flipped) {
+            board.flip_the_board();
+        }
+    }
+
+    /// Play a PGN back as if it were live, on the spectator page. Nothing is changed
+    /// if the PGN can't be parsed or has an illegal move.
+    pub fn start_simulation(&mut self, pgn: &str, now: std::time::Instant) -> Result<(), String> {
+        let game = crate::pgn::parse_pgn(pgn)?;
+        let simulation = crate::pgn::Simulation::new(&game, now)?;
+        log::info!(
+            "Simulating {} ({} moves)",
+            simulation.players,
+            game.moves.len()
+        );
+
+        self.current_popup = None;
+        self.error_message = None;
+        self.tv_feed_receiver = None;
+        self.tv_featured_players = None;
+        self.game.logic.opponent = None;
+        self.game.logic.bot = None;
+        self.game.logic.game_board.reset();
+        self.game.logic.game_board.position_history = vec![simulation.start.clone()];
+        self.game.logic.sync_player_turn_with_position();
+        self.game.logic.update_game_state();
+        self.lichess_clocks_ms = None;
+        self.lichess_clocks_synced_at = None;
+        self.simulation = Some(simulation);
+        self.current_page = Pages::LichessTv;
+        if let Some(skin) = &self.loaded_skin {
+            self.game.ui.skin = skin.clone();
+        }
+        Ok(())
+    }
+
+    /// Play every simulated move that is due by `now`, then show the result once the
+    /// last one is on the board
+    pub fn advance_simulation(&mut self, now: std::time::Instant) {
+        let Some(simulation) = self.simulation.as_mut() else {
+            return;
+        };
+        let mut played = false;
+        while let Some((chess_move, clocks_ms)) = simulation.next_due(now) {
+            let board = &mut self.game.logic.game_board;
+            let Some(from) = chess_move.from() else {
+                continue;
+            };
+            // Castling is played by moving the king to its destination square
+            let to = match chess_move.castling_side() {
+                Some(side) => side.king_to(board.position_ref().turn()),
+                None => chess_move.to(),
+            };
+            if let Some(executed_move) = board.execute_move(from, to, chess_move.promotion()) {
+                board.increment_consecutive_non_pawn_or_capture(
+                    executed_move.role(),
+                    executed_move.capture(),
+                );
+                board.move_history.push(executed_move);
+            }
+            crate::sound::play_move_sound();
+            if clocks_ms.is_some() {
+                self.lichess_clocks_ms = clocks_ms;
+                self.lichess_clocks_synced_at = Some(now);
+            }
+            played = true;
+        }
+        if !played {
+            return;
+        }
+        self.game.logic.sync_player_turn_with_position();
+
+        if simulation.is_finished() {
+            log::info!(
+                "Simulation finished: {}",
+                simulation.result.as_deref().unwrap_or("*")
+            );
+            // Mate and draws on the board get the usual end screen, other results the header
+            self.check_game_end_status();
+        }
+    }
+
+    /// Advance the simulation, if one is running
+    pub fn check_simulation(&mut self) {
+        if self.simulation.is_some() {
+            self.advance_simulation(std::time::Instant::now());
+        }
+    }
+
+    /// Leave Lichess TV mode and go back to the Lichess menu
+    pub fn stop_lichess_tv(&mut self) {
+        // Dropping the receiver makes the feed thread exit on its next frame
+        self.tv_feed_receiver = None;
+        self.tv_featured_players = None;
+        self.simulation = None;
+        self.lichess_clocks_ms = None;
+        self.lichess_clocks_synced_at = None;
+        self.game.logic.game_board.reset();
+        self.menu_cursor = 0;
+        self.current_page = if self.lichess_token.is_some() {
+            Pages::LichessMenu
+        } else {
+            Pages::Home
+        };
+    }
+
     pub fn start_puzzle_mode(&mut self) {
         // Clear any existing popups and error messages when starting a new puzzle
         self.current_popup = None;
@@ -977,88 +1573,613 @@ impl App {
             }
         }
 
+        // Finish a pending end-of-game resync
+        self.check_end_sync();
+
         // Process WebSocket messages if connected
         if let Some(ws_arc) = &self.lichess_ws {
-            let messages = if let Ok(ws) = ws_arc.lock() {
-                ws.process_messages().ok()
+            let mut resend_error = None;
+            let events = if let Ok(ws) = ws_arc.lock() {
+                if let Err(e) = ws.tick_keepalive(crate::lichess_ws::KEEPALIVE_INTERVAL) {
+                    log::debug!("Keepalive ping failed: {}", e);
+                }
+                let events = ws.process_events().ok();
+                // A lost move would otherwise block every later one until we flag
+                if ws.check_pending_timeout(crate::lichess_ws::ACK_TIMEOUT).is_some() {
+                    resend_error = ws.resend_pending().err();
+                }
+                events
             } else {
                 None
             };
+            if let Some(e) = resend_error {
+                log::error!("{}", e);
+                self.error_message = Some(format!("{}.\n\nPlay your move again.", e));
+                self.current_popup = Some(Popups::Error);
+            }
 
-            if let Some(messages) = messages {
-                for msg in messages {
-                    if msg.starts_with("MOVE:") {
-                        let uci = &msg[5..];
-                        // Set WebSocket move highlight
-                        self.game.logic.game_board.set_websocket_last_move(uci);
-                        log::info!("WebSocket move received: {}", uci);
-                    } else if msg == "GAME_END" {
-                        log::info!("Game ended via WebSocket");
-                        self.check_game_end_status();
-                    } else if msg.starts_with("FEN:") {
-                        let fen = &msg[4..];
-                        log::debug!("Position sync: {}", fen);
-                    }
-                }
+            for event in events.unwrap_or_default() {
+                self.handle_game_event(event);
             }
+            self.claim_threefold_draw();
         }
     }
 
-    /// Start bot thinking in a separate thread
-    pub fn start_bot_thinking(&mut self) {
-        // Don't start if already thinking
-        if self.bot_move_receiver.is_some() {
+    /// The position occurred a third time: claim the draw on Lichess, which sends the
+    /// same `draw-yes` as an offer. Retried while a move waits for its ack.
+    fn claim_threefold_draw(&mut self) {
+        if self.threefold_draw_claimed
+            || self.lichess_end_data.is_some()
+            || !self.game.logic.game_board.is_threefold_repetition()
+        {
             return;
         }
-
-        let bot = match &self.game.logic.bot {
-            Some(b) => b,
-            None => return,
+        let Some(ws_arc) = self.lichess_ws.as_ref() else {
+            return;
         };
+        let Ok(ws) = ws_arc.lock() else {
+            return;
+        };
+        match ws.offer_draw() {
+            Ok(()) => {
+                log::info!("Threefold repetition, draw claimed");
+                self.threefold_draw_claimed = true;
+            }
+            Err(e) => log::debug!("Threefold repetition draw not claimed yet: {}", e),
+        }
+    }
 
-        // Get current game state
-        let fen = self.game.logic.game_board.fen_position();
-        let engine_path = self.chess_engine_path.clone().unwrap_or_default();
-        let depth = bot.depth;
-
-        // Create channel for communication
-        let (tx, rx) = channel();
-        self.bot_move_receiver = Some(rx);
-
-        // Spawn thread to compute bot move
-        std::thread::spawn(move || {
-            // Create bot instance in thread
-            let bot = Bot::new(&engine_path, false, depth);
-            let uci_move = bot.get_move(&fen);
-
-            // Convert UCI move to shakmaty Move
-            let position: Option<shakmaty::Chess> = shakmaty::fen::Fen::from_ascii(fen.as_bytes())
-                .ok()
-                .and_then(|fen| fen.into_position(shakmaty::CastlingMode::Standard).ok());
-
-            if let Some(pos) = position {
-                if let Ok(chess_move) = uci_move.to_move(&pos) {
-                    let _ = tx.send(chess_move);
+    /// React to one event from the Lichess game socket
+    fn handle_game_event(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::Move(uci) => {
+                // Set WebSocket move highlight
+                self.game.logic.game_board.set_websocket_last_move(&uci);
+                log::info!("WebSocket move received: {}", uci);
+                // The server echoes our own moves too: only the opponent's
+                // moves, legal on their turn, go to the board
+                let is_opponent_move = self
+                    .game
+                    .logic
+                    .opponent
+                    .as_ref()
+                    .is_some_and(|opponent| opponent.color == self.game.logic.player_turn)
+                    && self.game.logic.game_board.parse_typed_move(&uci).is_some();
+                if is_opponent_move {
+                    if let Some(tx) = &self.lichess_ws_move_tx {
+                        let _ = tx.send(uci);
+                    }
+                    if let Some(auto_move) = self.auto_move_controller.as_mut() {
+                        auto_move.on_opponent_move();
+                    }
                 }
             }
-        });
+            GameEvent::Disconnected => log::warn!("Lichess socket dropped, reconnecting"),
+            GameEvent::Reconnected => log::info!("Lichess socket reconnected"),
+            GameEvent::ConnectionLost(reason) => {
+                log::error!("Lichess socket lost: {}", reason);
+                self.error_message = Some(format!(
+                    "Lost the connection to Lichess.\n\n{}\n\nCheck your network connection.",
+                    reason
+                ));
+                self.current_popup = Some(Popups::Error);
+            }
+            GameEvent::Clock { white, black } => {
+                self.lichess_clocks_ms = Some(ClockData { white, black }.to_millis());
+                self.lichess_clocks_synced_at = Some(std::time::Instant::now());
+                self.update_auto_move_clock();
+            }
+            GameEvent::Ack(uci) => {
+                log::debug!("Move {} acknowledged", uci);
+                self.on_move_acked(std::time::Instant::now());
+            }
+            GameEvent::GameEnd(Some(end)) => {
+                self.lichess_clocks_synced_at = None;
+                self.release_rated_game_lock();
+                let status = end.status.as_deref().unwrap_or("unknown");
+                let winner = end.winner.as_deref().unwrap_or("none");
+                log::info!("Game ended via WebSocket ({}, winner: {})", status, winner);
+                self.handle_end_data(status, winner);
+            }
+            GameEvent::GameEnd(None) => {
+                self.lichess_clocks_synced_at = None;
+                self.release_rated_game_lock();
+                log::info!("Game ended via WebSocket");
+                self.check_game_end_status();
+            }
+            GameEvent::Snapshot => {
+                let snapshot = self
+                    .lichess_ws
+                    .as_ref()
+                    .and_then(|ws_arc| ws_arc.lock().ok().and_then(|ws| ws.take_snapshot()));
+                if let Some(snapshot) = snapshot {
+                    self.apply_snapshot(&snapshot);
+                }
+            }
+            GameEvent::Fen(fen) => log::debug!("Position sync: {}", fen),
+            GameEvent::Reload | GameEvent::Resync => {
+                log::debug!("Lichess asked for a reload, pending move dropped")
+            }
+            GameEvent::OpponentGone(secs) => self.handle_opponent_gone(secs),
+            GameEvent::OpponentBack => self.handle_opponent_back(),
+            GameEvent::DrawOffer => {
+                // Don't cover an end screen or another question
+                if self.current_popup.is_none() {
+                    self.current_popup = Some(Popups::DrawOffer);
+                }
+            }
+            GameEvent::DrawOfferCleared => {
+                if self.current_popup == Some(Popups::DrawOffer) {
+                    self.current_popup = None;
+                }
+            }
+            GameEvent::TakebackOffer => {
+                if self.current_popup.is_none() {
+                    self.current_popup = Some(Popups::TakebackOffer);
+                }
+            }
+            GameEvent::TakebackCleared => {
+                if self.current_popup == Some(Popups::TakebackOffer) {
+                    self.current_popup = None;
+                }
+            }
+            GameEvent::TakenBack(plies) => self.apply_takeback(plies),
+            GameEvent::Chat(chat) => {
+                log::info!("Chat from {}: {}", chat.u, chat.t);
+                self.lichess_chat.push(chat);
+                let excess = self
+                    .lichess_chat
+                    .len()
+                    .saturating_sub(crate::lichess_ws::CHAT_LINES);
+                self.lichess_chat.drain(..excess);
+            }
+            GameEvent::Presence(crowd) => {
+                self.lichess_presence = Some(crowd);
+                // A reconnect shows up as the opponent being present again
+                let opponent_present = match self.selected_color {
+                    Some(Color::White) => crowd.black_present,
+                    Some(Color::Black) => crowd.white_present,
+                    None => false,
+                };
+                if opponent_present && self.opponent_gone_deadline.is_some() {
+                    self.handle_opponent_back();
+                }
+            }
+            // Whoever queued the premove plays it on the board
+            GameEvent::Lag(ms) => log::debug!("Lichess measured our lag at {}ms", ms),
+            GameEvent::PremoveSent(uci) => log::info!("Premove {} sent", uci),
+            GameEvent::Custom(out) => log::debug!("Custom socket handler: {}", out),
+        }
     }
 
-    /// Check if bot move is ready and apply it
-    pub fn check_bot_move(&mut self) -> bool {
-        if let Some(rx) = &self.bot_move_receiver {
-            if let Ok(bot_move) = rx.try_recv() {
-                // Apply the bot move
-                self.apply_bot_move(bot_move);
-                self.bot_move_receiver = None;
-                return true;
+    /// Rebuild the board from a full game snapshot: replay the moves (checked against
+    /// the FEN), or start from the FEN alone, then take over its turn and clocks
+    pub fn apply_snapshot(&mut self, snapshot: &crate::lichess_ws::GameSnapshot) {
+        let board = &mut self.game.logic.game_board;
+        if !snapshot.moves.is_empty() {
+            board.reconstruct_history(&snapshot.moves.join(" "), snapshot.fen.as_deref());
+        } else if let Some(fen) = &snapshot.fen {
+            match shakmaty::fen::Fen::from_ascii(fen.as_bytes()).map_err(|e| e.to_string()).and_then(
+                |fen| {
+                    fen.into_position::<shakmaty::Chess>(shakmaty::CastlingMode::Standard)
+                        .map_err(|e| e.to_string())
+                },
+            ) {
+                Ok(position) => {
+                    board.reset();
+                    board.position_history = vec![position];
+                }
+                Err(e) => log::error!("Invalid snapshot FEN {}: {}", fen, e),
             }
         }
-        false
+        self.game.logic.sync_player_turn_with_position();
+        self.game.logic.update_game_state();
+        self.lichess_clocks_ms = snapshot.clocks_ms;
+        self.lichess_clocks_synced_at = snapshot.clocks_ms.map(|_| std::time::Instant::now());
+        if let Some(meta) = self.game_meta.as_mut() {
+            meta.merge(snapshot.meta.clone());
+        }
+        self.update_auto_move_clock();
+        log::info!(
+            "Applied snapshot: {} moves, {:?} to move",
+            snapshot.moves.len(),
+            self.game.logic.player_turn
+        );
     }
 
-    /// Apply a bot move to the game
-    fn apply_bot_move(&mut self, bot_move: Move) {
+    /// Remaining time (white, black) as of `now`: the last synced clocks, with the
+    /// side to move's running down since. They stand still until both sides have
+    /// moved, and once the game is over.
+    pub fn running_clocks_ms(&self, now: std::time::Instant) -> Option<(u64, u64)> {
+        let (white, black) = self.lichess_clocks_ms?;
+        let position = self.game.logic.game_board.position_history.last()?;
+        let ply = (position.fullmoves().get() - 1) * 2 + u32::from(position.turn() == Color::Black);
+        let is_running = matches!(
+            self.game.logic.game_state,
+            GameState::Playing | GameState::Promotion
+        ) && ply >= 2;
+        let Some(synced_at) = self.lichess_clocks_synced_at.filter(|_| is_running) else {
+            return Some((white, black));
+        };
+        let elapsed = now.saturating_duration_since(synced_at).as_millis() as u64;
+        Some(match position.turn() {
+            Color::White => (white.saturating_sub(elapsed), black),
+            Color::Black => (white, black.saturating_sub(elapsed)),
+        })
+    }
+
+    /// Feed our side's Lichess clock to auto-move, for its low-clock panic mode, and
+    /// to its engine with the game's increment, so searches shrink as the clock runs down
+    fn update_auto_move_clock(&mut self) {
+        let increment = self
+            .game_meta
+            .as_ref()
+            .and_then(|meta| meta.clock)
+            .map_or(Duration::ZERO, |(_, increment)| {
+                Duration::from_secs(increment as u64)
+            });
+        if let (Some((white_ms, black_ms)), Some(color), Some(controller)) = (
+            self.lichess_clocks_ms,
+            self.selected_color,
+            self.auto_move_controller.as_mut(),
+        ) {
+            let ours = if color == Color::White { white_ms } else { black_ms };
+            controller.update_clock(ours as f32 / 1000.0);
+            controller.set_clock(Duration::from_millis(ours), increment);
+        }
+    }
+
+    /// Whether the server's end status should match the board but the local position is still in play.
+    /// Results that don't show on the board (resign, timeout, abort, ...) are trusted as is.
+    pub fn end_data_contradicts_board(&self, status: &str) -> bool {
+        let board = &self.game.logic.game_board;
+        let board_over = board.is_checkmate() || board.is_draw();
+        !board_over && matches!(status, "mate" | "stalemate" | "unknown")
+    }
+
+    /// Play the opponent moves the socket queued for the next tick right away
+    fn apply_queued_opponent_moves(&mut self) {
+        while self
+            .game
+            .logic
+            .opponent
+            .as_ref()
+            .is_some_and(|opponent| opponent.color == self.game.logic.player_turn)
+            && self.game.logic.execute_opponent_move()
+        {
+            self.game.logic.switch_player_turn();
+        }
+    }
+
+    /// Handle the server's endData: reconcile with the authoritative position first if
+    /// the result contradicts the local board, then show the summary
+    fn handle_end_data(&mut self, status: &str, winner: &str) {
+        self.lichess_end_data = Some((status.to_string(), winner.to_string()));
+        // An aborted game has no result and doesn't count, so it gets its own screen
+        if status == "aborted" {
+            log::info!("Game aborted, no result");
+            self.current_popup = Some(Popups::GameAborted);
+            return;
+        }
+        // A mating move comes in the same batch as the endData, queued for the next tick
+        self.apply_queued_opponent_moves();
+        if !self.end_data_contradicts_board(status) {
+            self.check_game_end_status();
+            // Resignations, flags, ... end the game without a mate or draw on the board
+            self.auto_save_game_record();
+            return;
+        }
+
+        log::warn!(
+            "SYNC_WARNING: server reports {} (winner: {}) but the local board is still in play, resyncing",
+            status, winner
+        );
+
+        let game_id = self
+            .lichess_ws
+            .as_ref()
+            .and_then(|ws_arc| ws_arc.lock().ok().map(|ws| ws.game_id().to_string()));
+        let (Some(game_id), Some(token)) = (game_id, self.lichess_token.clone()) else {
+            // Nothing to refetch from, fall back to the server's verdict
+            self.show_end_screen();
+            return;
+        };
+
+        let (tx, rx) = channel();
+        self.end_sync_receiver = Some(rx);
+        std::thread::spawn(move || {
+            let session = crate::lichess_auth::LichessSession::from_token(token);
+            let options = crate::lichess::PgnExportOptions {
+                clocks: false,
+                evals: false,
+            };
+            let pgn = crate::lichess::export_pgn(&session, &game_id, options);
+            let _ = tx.send(pgn.map_err(|e| e.to_string()));
+        });
+    }
+
+    /// Rebuild the board from the server's moves once the resync requested by
+    /// `handle_end_data` finishes
+    fn check_end_sync(&mut self) {
+        let result = match &self.end_sync_receiver {
+            Some(rx) => match rx.try_recv() {
+                Ok(result) => result,
+                Err(std::sync::mpsc::TryRecvError::Empty) => return,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    Err("resync thread stopped".to_string())
+                }
+            },
+            None => return,
+        };
+        self.end_sync_receiver = None;
+
+        let replayed = result.and_then(|pgn| {
+            let game = crate::pgn::parse_pgn(&pgn)?;
+            let moves = game.mainline()?;
+            self.game
+                .logic
+                .game_board
+                .replay_from(game.start_position()?, &moves)
+        });
+        match replayed {
+            Ok(()) => {
+                log::info!("Resynced board with the server before ending the game");
+                // The server's moves include anything still queued for the board
+                if let Some(OpponentKind::LichessWs { move_rx, .. }) = self
+                    .game
+                    .logic
+                    .opponent
+                    .as_ref()
+                    .and_then(|opponent| opponent.kind.as_ref())
+                {
+                    while move_rx.try_recv().is_ok() {}
+                }
+                self.game.logic.sync_player_turn_with_position();
+                self.game.logic.update_game_state();
+            }
+            Err(e) => log::error!("Failed to resync board before ending the game: {}", e),
+        }
+
+        // The game is over on the server either way
+        self.show_end_screen();
+    }
+
+    /// The opponent left; victory can be claimed after `secs` seconds
+    pub fn handle_opponent_gone(&mut self, secs: u64) {
+        log::info!("Opponent gone, victory claimable in {}s", secs);
+        self.opponent_gone_deadline = Some(std::time::Instant::now() + Duration::from_secs(secs));
+    }
+
+    /// The opponent reconnected, cancel the disconnect countdown
+    pub fn handle_opponent_back(&mut self) {
+        log::info!("Opponent reconnected");
+        self.opponent_gone_deadline = None;
+    }
+
+    /// Time left before victory can be claimed, if the opponent is gone
+    pub fn opponent_gone_countdown(&self) -> Option<Duration> {
+        self.opponent_gone_deadline
+            .map(|deadline| deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+
+    /// Whether the opponent has been gone long enough to claim victory
+    pub fn can_claim_victory(&self) -> bool {
+        self.opponent_gone_countdown()
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// Claim victory after the opponent's disconnect countdown expired
+    pub fn claim_victory(&mut self) {
+        if !self.can_claim_victory() {
+            return;
+        }
+        if let Some(ws_arc) = &self.lichess_ws {
+            if let Ok(ws) = ws_arc.lock() {
+                match ws.claim_win() {
+                    Ok(()) => self.opponent_gone_deadline = None,
+                    Err(e) => log::error!("Failed to claim victory: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Offer a draw in the current WebSocket game
+    pub fn offer_draw(&mut self) -> Result<(), String> {
+        if self.game.logic.game_state != GameState::Playing {
+            return Err("No game in progress".to_string());
+        }
+        let ws_arc = self
+            .lichess_ws
+            .as_ref()
+            .ok_or_else(|| "Draw offers need a Lichess game".to_string())?;
+        let ws = ws_arc.lock().map_err(|_| "WebSocket unavailable".to_string())?;
+        ws.offer_draw()
+    }
+
+    /// Answer the opponent's draw offer, closing its popup either way
+    pub fn answer_draw_offer(&mut self, accept: bool) {
+        if self.current_popup == Some(Popups::DrawOffer) {
+            self.current_popup = None;
+        }
+        let Some(ws_arc) = self.lichess_ws.as_ref() else {
+            return;
+        };
+        let Ok(ws) = ws_arc.lock() else {
+            return;
+        };
+        let result = if accept {
+            ws.offer_draw()
+        } else {
+            ws.decline_draw()
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to answer the draw offer: {}", e);
+        }
+    }
+
+    /// Send a chat line in the current WebSocket game
+    pub fn send_chat(&mut self, text: &str) -> Result<(), String> {
+        let ws_arc = self
+            .lichess_ws
+            .as_ref()
+            .ok_or_else(|| "Chat needs a Lichess game".to_string())?;
+        let ws = ws_arc
+            .lock()
+            .map_err(|_| "WebSocket unavailable".to_string())?;
+        ws.send_chat(text)
+    }
+
+    /// Ask the opponent to take back our last move in the current WebSocket game
+    pub fn propose_takeback(&mut self) -> Result<(), String> {
+        if self.game.logic.game_state != GameState::Playing {
+            return Err("No game in progress".to_string());
+        }
+        let ws_arc = self
+            .lichess_ws
+            .as_ref()
+            .ok_or_else(|| "Takebacks need a Lichess game".to_string())?;
+        let ws = ws_arc
+            .lock()
+            .map_err(|_| "WebSocket unavailable".to_string())?;
+        ws.propose_takeback()
+    }
+
+    /// Answer the opponent's takeback proposal, closing its popup either way
+    pub fn answer_takeback_offer(&mut self, accept: bool) {
+        if self.current_popup == Some(Popups::TakebackOffer) {
+            self.current_popup = None;
+        }
+        let result = match self.lichess_ws.as_ref() {
+            Some(ws_arc) => ws_arc
+                .lock()
+                .map_err(|_| "WebSocket unavailable".to_string())
+                .and_then(|ws| ws.respond_takeback(accept)),
+            None => return,
+        };
+        match result {
+            Ok(plies) => self.apply_takeback(plies),
+            Err(e) => log::warn!("Failed to answer the takeback: {}", e),
+        }
+    }
+
+    /// Undo the last `plies` plies on the board after a takeback went through
+    pub fn apply_takeback(&mut self, plies: u32) {
+        let board = &mut self.game.logic.game_board;
+        let latest = board.position_history.len().saturating_sub(1);
+        let Some(index) = latest.checked_sub(plies as usize) else {
+            log::warn!("Takeback of {} plies goes past the known history", plies);
+            return;
+        };
+        if plies == 0 {
+            return;
+        }
+        for _ in 0..plies {
+            if board.undo().is_none() {
+                // Moves and positions out of step: cut the history instead
+                board.truncate_history_at(index);
+                break;
+            }
+        }
+        // The server has the final say online, there's nothing to redo
+        board.redo_stack.clear();
+        self.game.logic.sync_player_turn_with_position();
+        self.game.logic.update_game_state();
+        log::info!(
+            "Took back {} plies, {:?} to move",
+            plies,
+            self.game.logic.player_turn
+        );
+    }
+
+    /// Whether the opponent's draw offer is waiting for an answer
+    pub fn has_opponent_draw_offer(&self) -> bool {
+        self.lichess_ws
+            .as_ref()
+            .and_then(|ws| ws.lock().ok().map(|ws| ws.has_opponent_draw_offer()))
+            .unwrap_or(false)
+    }
+
+    /// Opponent's presence and the spectator count, for the game panel
+    pub fn presence_line(&self) -> Option<String> {
+        let crowd = self.lichess_presence?;
+        let opponent_present = match self.selected_color? {
+            Color::White => crowd.black_present,
+            Color::Black => crowd.white_present,
+        };
+        let mut line = if opponent_present {
+            "Opponent at the board".to_string()
+        } else {
+            "Opponent away".to_string()
+        };
+        if let Some(watchers) = crowd.watchers.filter(|&n| n > 0) {
+            line.push_str(&format!(" • {} watching", watchers));
+        }
+        Some(line)
+    }
+
+    /// Whether our draw offer is still standing (a move withdraws it)
+    pub fn is_draw_offered(&self) -> bool {
+        self.lichess_ws
+            .as_ref()
+            .and_then(|ws| ws.lock().ok().map(|ws| ws.is_draw_offered()))
+            .unwrap_or(false)
+    }
+
+    /// Start bot thinking in a separate thread
+    pub fn start_bot_thinking(&mut self) {
+        // Don't start if already thinking
+        if self.bot_move_receiver.is_some() {
+            return;
+        }
+
+        let bot = match &self.game.logic.bot {
+            Some(b) => b,
+            None => return,
+        };
+
+        // Get current game state
+        let fen = self.game.logic.game_board.fen_position();
+        let engine_path = self.chess_engine_path.clone().unwrap_or_default();
+        let depth = bot.depth;
+
+        // Create channel for communication
+        let (tx, rx) = channel();
+        self.bot_move_receiver = Some(rx);
+
+        // Spawn thread to compute bot move
+        std::thread::spawn(move || {
+            // Create bot instance in thread
+            let bot = Bot::new(&engine_path, false, depth);
+            let uci_move = bot.get_move(&fen);
+
+            // Convert UCI move to shakmaty Move
+            let position: Option<shakmaty::Chess> = shakmaty::fen::Fen::from_ascii(fen.as_bytes())
+                .ok()
+                .and_then(|fen| fen.into_position(shakmaty::CastlingMode::Standard).ok());
+
+            if let Some(pos) = position {
+                if let Ok(chess_move) = uci_move.to_move(&pos) {
+                    let _ = tx.send(chess_move);
+                }
+            }
+        });
+    }
+
+    /// Check if bot move is ready and apply it
+    pub fn check_bot_move(&mut self) -> bool {
+        if let Some(rx) = &self.bot_move_receiver {
+            if let Ok(bot_move) = rx.try_recv() {
+                // Apply the bot move
+                self.apply_bot_move(bot_move);
+                self.bot_move_receiver = None;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Apply a bot move to the game
+    fn apply_bot_move(&mut self, bot_move: Move) {
         use shakmaty::Position;
 
         let current_position = match self.game.logic.game_board.current_position() {
@@ -1097,12 +2218,171 @@ impl App {
         self.bot_move_receiver.is_some()
     }
 
+    /// Search the current position deeper than the bot does, once. The result stays
+    /// up until the position changes.
+    pub fn start_deep_analysis(&mut self) {
+        if !self.is_engine_assistance_allowed() {
+            log::warn!("Fair-play lock engaged, analysis disabled");
+            return;
+        }
+        let Some(engine_path) = self.chess_engine_path.clone().filter(|p| !p.is_empty()) else {
+            self.current_popup = Some(Popups::EnginePathError);
+            return;
+        };
+
+        let fen = self.game.logic.game_board.fen_position();
+        let is_running = self
+            .deep_analysis
+            .as_ref()
+            .is_some_and(|job| job.fen == fen);
+        let is_done = self
+            .deep_analysis_result
+            .as_ref()
+            .is_some_and(|(analyzed, _)| *analyzed == fen);
+        if is_running || is_done {
+            return;
+        }
+
+        self.stop_deep_analysis();
+        let engine = crate::engine::UciEngine::new(&engine_path, DEEP_ANALYSIS_DEPTH);
+        match engine.spawn_analysis(&fen, DEEP_ANALYSIS_DEPTH) {
+            Ok(job) => {
+                log::info!("Deep analysis started at depth {}", DEEP_ANALYSIS_DEPTH);
+                self.deep_analysis = Some(job);
+            }
+            Err(e) => {
+                log::error!("Failed to start deep analysis: {}", e);
+                self.error_message = Some(format!("Failed to start analysis: {}", e));
+                self.current_popup = Some(Popups::Error);
+            }
+        }
+    }
+
+    /// Stop a running deep analysis (UCI `stop`) and drop it
+    pub fn stop_deep_analysis(&mut self) {
+        if let Some(job) = self.deep_analysis.take() {
+            job.stop();
+        }
+    }
+
+    /// Collect a finished deep analysis. Once the position changes, stop the search
+    /// or clear the result it left.
+    pub fn check_deep_analysis(&mut self) {
+        let fen = self.game.logic.game_board.fen_position();
+        if self
+            .deep_analysis_result
+            .as_ref()
+            .is_some_and(|(analyzed, _)| *analyzed != fen)
+        {
+            self.deep_analysis_result = None;
+        }
+
+        let Some(job) = &self.deep_analysis else {
+            return;
+        };
+        if job.fen != fen {
+            log::info!("Position changed, stopping deep analysis");
+            self.stop_deep_analysis();
+            return;
+        }
+        match job.try_result() {
+            None => {}
+            Some(Ok(analysis)) => {
+                self.deep_analysis_result = Some((job.fen.clone(), analysis));
+                self.deep_analysis = None;
+            }
+            Some(Err(e)) => {
+                log::warn!("Deep analysis failed: {}", e);
+                self.deep_analysis = None;
+            }
+        }
+    }
+
+    /// Status line for the deep analysis: "analyzing…" while it runs, then its
+    /// score (White's point of view) and best move. Nothing while fair-play locked.
+    pub fn deep_analysis_label(&self) -> Option<String> {
+        if !self.is_engine_assistance_allowed() {
+            return None;
+        }
+        if self.deep_analysis.is_some() {
+            return Some(format!("🔍 Analyzing… (depth {})", DEEP_ANALYSIS_DEPTH));
+        }
+        let (fen, analysis) = self.deep_analysis_result.as_ref()?;
+        let position: Chess = shakmaty::fen::Fen::from_ascii(fen.as_bytes())
+            .ok()?
+            .into_position(shakmaty::CastlingMode::Standard)
+            .ok()?;
+        let best_move = shakmaty::uci::UciMove::from_ascii(analysis.best_move.as_bytes())
+            .ok()
+            .and_then(|uci| uci.to_move(&position).ok())
+            .map(|m| shakmaty::san::SanPlus::from_move(position.clone(), &m).to_string())
+            .unwrap_or_else(|| analysis.best_move.clone());
+        let score = match analysis.eval {
+            Some(eval) => format!("{} ", eval.for_white(position.turn())),
+            None => String::new(),
+        };
+        Some(format!(
+            "🔍 Depth {}: {}{}",
+            DEEP_ANALYSIS_DEPTH, score, best_move
+        ))
+    }
+
     /// Set running to false to quit the application.
     pub fn quit(&mut self) {
         self.running = false;
     }
 
+    /// Our move landed: start the confirmation flash, unless it's off or panic mode
+    /// is saving every millisecond
+    pub fn on_move_acked(&mut self, now: std::time::Instant) {
+        let is_panic = self
+            .auto_move_controller
+            .as_ref()
+            .is_some_and(|auto_move| auto_move.is_panic_mode());
+        if self.ack_flash && !is_panic {
+            self.ack_flash_until = Some(now + Duration::from_millis(ACK_FLASH_DURATION_MS));
+        }
+    }
+
+    /// Whether the game socket dropped and is being reconnected
+    pub fn is_lichess_reconnecting(&self) -> bool {
+        self.lichess_ws
+            .as_ref()
+            .is_some_and(|ws| ws.lock().is_ok_and(|ws| ws.is_reconnecting()))
+    }
+
+    /// Whether the game socket dropped and every reconnect attempt failed
+    pub fn is_lichess_disconnected(&self) -> bool {
+        self.lichess_ws
+            .as_ref()
+            .is_some_and(|ws| ws.lock().is_ok_and(|ws| ws.has_reconnect_failed()))
+    }
+
+    /// Whose turn it is in the live game (not the one browsed in the history),
+    /// for the side-to-move marker; None once the game is over
+    pub fn side_to_move(&self) -> Option<Color> {
+        match self.game.logic.game_state {
+            GameState::Checkmate | GameState::Draw => None,
+            GameState::Playing | GameState::Promotion => self
+                .game
+                .logic
+                .game_board
+                .position_history
+                .last()
+                .map(|position| position.turn()),
+        }
+    }
+
+    /// Whether the ack confirmation flash is showing
+    pub fn is_ack_flash_active(&self, now: std::time::Instant) -> bool {
+        self.ack_flash_until.is_some_and(|until| now < until)
+    }
+
     pub fn check_game_end_status(&mut self) {
+        self.game
+            .logic
+            .game_board
+            .trim_history(self.max_history_plies);
         let previous_state = self.game.logic.game_state;
         self.game.logic.update_game_state();
         let new_state = self.game.logic.game_state;
@@ -1211,6 +2491,7 @@ impl App {
         // Clear puzzle state when restarting (for normal games)
         self.puzzle_game = None;
         self.end_screen_dismissed = false;
+        self.game_record_saved = false;
         let bot = self.game.logic.bot.clone();
         let opponent = self.game.logic.opponent.clone();
         // Preserve skin and display mode
@@ -1241,6 +2522,24 @@ impl App {
         }
     }
 
+    /// Replay the bot game from the start with colors swapped, to practice the same
+    /// opening from the other side. The engine opens if we're now Black.
+    pub fn swap_sides_and_restart(&mut self) {
+        let Some(color) = self.selected_color else {
+            return;
+        };
+        let Some(bot) = self.game.logic.bot.as_mut() else {
+            return;
+        };
+        let color = color.other();
+        bot.is_bot_starting = color == Color::Black;
+        self.selected_color = Some(color);
+        // Drop any move the engine was still computing for the old game
+        self.bot_move_receiver = None;
+        log::info!("Swapped sides, now playing {:?} against the bot", color);
+        self.restart();
+    }
+
     pub fn menu_select(&mut self) {
         match self.menu_cursor {
             0 => self.current_page = Pages::Solo,
@@ -1297,9 +2596,26 @@ impl App {
     }
 
     pub fn update_config(&self) {
-        let config_dir = config_dir().unwrap();
-        let config_path = config_dir.join("chess-tui/config.toml");
-        let mut config: Config = match fs::read_to_string(&config_path) {
+        let config_path = match data_dir() {
+            Ok(dir) => dir.join("config.toml"),
+            Err(e) => {
+                log::error!("Cannot save config: {}", e);
+                return;
+            }
+        };
+        let config = self.effective_config(&config_path);
+
+        if let Ok(mut file) = File::create(&config_path) {
+            let toml_string = toml::to_string(&config).unwrap_or_default();
+            if let Err(e) = file.write_all(toml_string.as_bytes()) {
+                log::error!("Failed to write config: {}", e);
+            }
+        }
+    }
+
+    /// The config file at `config_path` with the app's current settings applied
+    pub fn effective_config(&self, config_path: &std::path::Path) -> Config {
+        let mut config: Config = match fs::read_to_string(config_path) {
             Ok(content) => toml::from_str(&content).unwrap_or_default(),
             Err(_) => Config::default(),
         };
@@ -1310,19 +2626,43 @@ impl App {
         config.selected_skin_name = Some(self.selected_skin_name.clone());
         config.lichess_token = self.lichess_token.clone();
         config.sound_enabled = Some(self.sound_enabled);
-
-        if let Ok(mut file) = File::create(&config_path) {
-            let toml_string = toml::to_string(&config).unwrap_or_default();
-            if let Err(e) = file.write_all(toml_string.as_bytes()) {
-                log::error!("Failed to write config: {}", e);
-            }
-        }
+        config.reaction_delay_min_ms = self.reaction_delay_ms.map(|(min, _)| min);
+        config.reaction_delay_max_ms = self.reaction_delay_ms.map(|(_, max)| max);
+        config.ws_strict_mode = Some(self.ws_strict_mode);
+        config.ws_ack_scheme = Some(self.ws_ack_scheme.to_string());
+        config.ws_host = self.ws_host.clone();
+        config.input_method = Some(self.input_method.to_string());
+        config.spectator_orientation = Some(self.spectator_orientation.to_string());
+        config.move_notation = Some(self.move_notation.to_string());
+        config.move_debounce_ms = Some(self.move_debounce_ms);
+        config.auto_save_pgn = Some(self.auto_save_pgn);
+        config.auto_resume_game = Some(self.auto_resume_game);
+        config.max_history_plies = Some(self.max_history_plies);
+        config.ack_flash = Some(self.ack_flash);
+        config.show_coordinates = Some(self.show_coordinates);
+        config.show_eval_bar = Some(self.show_eval_bar);
+        config.auth_backend = Some(self.auth_backend.to_string());
+        config.pgn_dir = self
+            .pgn_dir
+            .as_ref()
+            .map(|dir| dir.to_string_lossy().to_string());
+        config.auto_move_book_only = Some(self.auto_move_book_only);
+        config.auto_move_book_depth = self.auto_move_book_depth;
+        config.courtesy_resign = Some(self.courtesy_resign);
+        config.courtesy_resign_cp = self.courtesy_resign_cp;
+        config.auto_move_sources = Some(
+            self.auto_move_sources
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        );
+        config
     }
 
     pub fn save_and_validate_lichess_token(&mut self, token: String) {
         // First, try to validate the token by fetching the user profile
         let client = crate::lichess::LichessClient::new(token.clone());
-        match client.get_user_profile() {
+        match client.validate() {
             Ok(profile) => {
                 // Token is valid, save it
                 self.lichess_token = Some(token);
@@ -1344,7 +2684,7 @@ impl App {
                 ));
                 self.current_popup = Some(Popups::Success);
             }
-            Err(e) => {
+            Err(e) if e.needs_relogin() => {
                 // Token is invalid, show error
                 self.error_message = Some(format!(
                     "Invalid Lichess token.\n\nError: {}\n\n Please check your token and try again.\n\n Follow the documentation: https://thomas-mauran.github.io/chess-tui/docs/Lichess/setup",
@@ -1352,6 +2692,11 @@ impl App {
                 ));
                 self.current_popup = Some(Popups::Error);
             }
+            Err(e) => {
+                // Closed account, rate limit or network: a different token wouldn't help
+                self.error_message = Some(format!("Could not verify the token.\n\n{}.", e));
+                self.current_popup = Some(Popups::Error);
+            }
         }
     }
 
@@ -1379,24 +2724,176 @@ impl App {
         self.current_popup = Some(Popups::Success);
     }
 
+    /// Write a redacted diagnostic bundle for bug reports and show where it went
+    pub fn dump_diagnostics(&mut self) {
+        self.error_message = Some(match crate::diagnostics::write_report(self) {
+            Ok(path) => {
+                log::info!("Diagnostics written to {}", path.display());
+                format!("Diagnostics written to\n\n {}", path.display())
+            }
+            Err(e) => {
+                log::error!("Failed to write diagnostics: {}", e);
+                format!("Failed to write diagnostics: {}", e)
+            }
+        });
+        self.current_popup = Some(Popups::Diagnostics);
+    }
+
+    /// Engage or release the fair-play lock and propagate it to auto-move
+    pub fn set_fair_play_lock(&mut self, locked: bool) {
+        self.fair_play_lock = locked;
+        if let Some(auto_move) = self.auto_move_controller.as_mut() {
+            auto_move.set_fair_play_lock(locked);
+        }
+        if locked {
+            self.game.ui.show_threats = false;
+            self.stop_deep_analysis();
+            self.deep_analysis_result = None;
+        }
+        log::info!(
+            "Fair-play lock {}",
+            if locked { "engaged" } else { "released" }
+        );
+    }
+
+    /// Rated games always run with the fair-play lock engaged
+    fn engage_rated_game_lock(&mut self, is_rated: bool) {
+        self.current_game_rated = is_rated;
+        if is_rated && !self.fair_play_lock {
+            self.fair_play_lock_forced = true;
+            self.set_fair_play_lock(true);
+        }
+    }
+
+    /// The rated game is over: release the lock it engaged, but not one the user did
+    fn release_rated_game_lock(&mut self) {
+        self.current_game_rated = false;
+        if std::mem::take(&mut self.fair_play_lock_forced) {
+            self.set_fair_play_lock(false);
+        }
+    }
+
+    /// Toggle the fair-play lock. It can't be released during a rated game.
+    pub fn toggle_fair_play_lock(&mut self) {
+        if self.fair_play_lock && self.current_game_rated {
+            log::warn!("Fair-play lock can't be released during a rated game");
+            return;
+        }
+        self.set_fair_play_lock(!self.fair_play_lock);
+    }
+
+    /// Whether engine assistance (engine, eval, hints, auto-move) may be used
+    pub fn is_engine_assistance_allowed(&self) -> bool {
+        !self.fair_play_lock
+    }
+
+    /// Toggle the overlay showing the squares attacked by the opponent.
+    /// Like other assistance, it can't be turned on while the fair-play lock is engaged.
+    pub fn toggle_threats_overlay(&mut self) {
+        if !self.game.ui.show_threats && !self.is_engine_assistance_allowed() {
+            log::warn!("Threats overlay is disabled by the fair-play lock");
+            return;
+        }
+        self.game.ui.show_threats = !self.game.ui.show_threats;
+    }
+
+    /// Turn the board around by hand. The cursor and the selected piece stay on the
+    /// same squares of the position, they're only drawn on the other side.
+    pub fn flip_board(&mut self) {
+        self.game.logic.game_board.flip_the_board();
+        let ui = &mut self.game.ui;
+        if ui.cursor_coordinates.is_valid() {
+            ui.cursor_coordinates = ui.cursor_coordinates.reverse();
+        }
+        ui.selected_square = ui
+            .selected_square
+            .map(|square| flip_square_if_needed(square, true));
+    }
+
+    /// Show or hide the coordinate labels around the board
+    pub fn toggle_coordinates(&mut self) {
+        self.show_coordinates = !self.show_coordinates;
+    }
+
+    /// Show or hide the evaluation bar, for games played without engine help
+    pub fn toggle_eval_bar(&mut self) {
+        self.show_eval_bar = !self.show_eval_bar;
+    }
+
+    /// Score for the evaluation bar, from White's point of view: the running deep
+    /// analysis' latest one, or the finished analysis' of the shown position
+    pub fn eval_bar_score(&self) -> Option<crate::auto_move::Eval> {
+        if !self.show_eval_bar || !self.is_engine_assistance_allowed() {
+            return None;
+        }
+        let (fen, eval) = match (&self.deep_analysis, &self.deep_analysis_result) {
+            (Some(job), _) => (&job.fen, job.latest_eval()?),
+            (None, Some((fen, analysis))) => (fen, analysis.eval?),
+            (None, None) => return None,
+        };
+        let side_to_move = match fen.split_whitespace().nth(1) {
+            Some("b") => Color::Black,
+            _ => Color::White,
+        };
+        Some(eval.for_white(side_to_move))
+    }
+
+    /// Best metadata we have for a game: the ongoing games list knows the opponent
+    fn game_meta_for(&self, game_id: &str) -> crate::lichess::GameMeta {
+        self.ongoing_games
+            .iter()
+            .find(|g| g.game_id == game_id)
+            .map(crate::lichess::GameMeta::from_ongoing)
+            .unwrap_or_else(|| crate::lichess::GameMeta::for_game(game_id))
+    }
+
     /// Start a Lichess game using WebSocket connection
     pub fn start_lichess_websocket_game(&mut self, game_id: &str, player_color: shakmaty::Color) -> Result<(), String> {
-        use std::sync::{Arc, Mutex};
-        use std::sync::mpsc::channel;
-        
         // Generate Socket Request ID
-        let sri = crate::lichess_ws::LichessWebSocket::generate_sri();
+        let sri = match self.rng_seed {
+            Some(seed) => {
+                use rand::SeedableRng;
+                let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+                crate::lichess_ws::LichessWebSocket::generate_sri_with(&mut rng)
+            }
+            None => crate::lichess_ws::LichessWebSocket::generate_sri(),
+        };
         log::info!("Generated SRI: {}", sri);
         
+        // Close the previous game's socket before opening a new one
+        self.end_current_game();
+        
         // Create WebSocket connection
-        let ws = crate::lichess_ws::LichessWebSocket::new(game_id, &sri)
+        let host = self
+            .ws_host
+            .as_deref()
+            .unwrap_or(crate::lichess_ws::DEFAULT_WS_HOST);
+        let ws = crate::lichess_ws::LichessWebSocket::new_with_host(game_id, &sri, host)
             .map_err(|e| format!("Failed to connect WebSocket: {}", e))?;
         
+        self.attach_lichess_websocket(ws, game_id, player_color);
+        Ok(())
+    }
+    
+    /// Play a game over an already connected WebSocket (the live socket or a test server)
+    pub fn attach_lichess_websocket(
+        &mut self,
+        ws: crate::lichess_ws::LichessWebSocket,
+        game_id: &str,
+        player_color: shakmaty::Color,
+    ) {
+        use std::sync::{Arc, Mutex};
+        use std::sync::mpsc::channel;
+        
+        ws.set_strict_mode(self.ws_strict_mode);
+        ws.set_ack_scheme(self.ws_ack_scheme);
+        
         // Store in app
         self.lichess_ws = Some(Arc::new(Mutex::new(ws)));
         
-        // Create channel for opponent moves
-        let (_move_tx, move_rx) = channel();
+        // Create channel for opponent moves, fed from the socket in tick()
+        let (move_tx, move_rx) = channel();
+        self.lichess_ws_move_tx = Some(move_tx);
         
         // Create LichessWs opponent
         let opponent = crate::game_logic::opponent::Opponent {
@@ -1414,13 +2911,44 @@ impl App {
         
         self.game.logic.opponent = Some(opponent);
         self.selected_color = Some(player_color);
+        self.game_meta = Some(self.game_meta_for(game_id));
+
+        let is_rated = self
+            .game_meta
+            .as_ref()
+            .and_then(|meta| meta.rated)
+            .unwrap_or(false);
+        self.engage_rated_game_lock(is_rated);
         
         // Initialize auto-move controller (disabled by default)
-        let auto_move = crate::auto_move::AutoMoveController::new();
+        let mut auto_move = match self.rng_seed {
+            Some(seed) => crate::auto_move::AutoMoveController::with_seed(seed),
+            None => crate::auto_move::AutoMoveController::new(),
+        };
+        auto_move.set_fair_play_lock(self.fair_play_lock);
+        if let Some((min_ms, max_ms)) = self.reaction_delay_ms {
+            auto_move.set_reaction_delay(min_ms, max_ms);
+        }
+        auto_move.set_move_sources(self.auto_move_sources.clone());
+        if self.auto_move_book_only {
+            auto_move.set_book_only(true, self.auto_move_book_depth);
+        }
+        auto_move.set_eval_log(Some(game_id.to_string()));
+        if let Some(engine_path) = &self.chess_engine_path {
+            auto_move.set_engine(Box::new(crate::engine::UciEngine::new(
+                engine_path,
+                self.bot_depth,
+            )));
+        }
+        if self.courtesy_resign {
+            auto_move.set_courtesy_resign(Some(
+                self.courtesy_resign_cp
+                    .unwrap_or(crate::auto_move::DEFAULT_COURTESY_RESIGN_CP),
+            ));
+        }
         self.auto_move_controller = Some(auto_move);
         
         log::info!("Started Lichess WebSocket game: {} as {:?}", game_id, player_color);
-        Ok(())
     }
 
     pub fn reset(&mut self) {
@@ -1634,6 +3162,147 @@ impl App {
         false
     }
 
+    /// Play a typed move (UCI or SAN) through the same path as a selection followed by a target.
+    /// Returns an error message when the move can't be played.
+    pub fn submit_typed_move(&mut self, input: &str) -> Result<(), String> {
+        if self.game.logic.game_state != GameState::Playing {
+            return Err("No move can be played right now".to_string());
+        }
+        // Online games only accept moves on our turn
+        if let (Some(my_color), Some(_)) = (self.selected_color, &self.game.logic.opponent) {
+            if self.game.logic.player_turn != my_color {
+                return Err("It's not your turn".to_string());
+            }
+        }
+
+        let Some(chess_move) = self.game.logic.game_board.parse_typed_move(input) else {
+            return Err(format!("Illegal or unknown move: {}", input.trim()));
+        };
+        let Some(from) = chess_move.from() else {
+            return Err(format!("Unsupported move: {}", input.trim()));
+        };
+        // Castling is played by moving the king to its destination square
+        let to = match chess_move.castling_side() {
+            Some(side) => side.king_to(self.game.logic.game_board.position_ref().turn()),
+            None => chess_move.to(),
+        };
+
+        let is_flipped = self.game.logic.game_board.is_flipped;
+        self.game.ui.selected_square = Some(flip_square_if_needed(from, is_flipped));
+        self.game.ui.cursor_coordinates =
+            Coord::from_square(flip_square_if_needed(to, is_flipped));
+        self.process_cell_click();
+
+        // Pick the typed promotion piece instead of asking for it
+        if let Some(role) = chess_move.promotion() {
+            if self.game.logic.game_state == GameState::Promotion {
+                self.game.ui.promotion_cursor = match role {
+                    shakmaty::Role::Rook => 1,
+                    shakmaty::Role::Bishop => 2,
+                    shakmaty::Role::Knight => 3,
+                    _ => 0,
+                };
+                self.process_cell_click();
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the server aborted the current game (it has no result)
+    pub fn is_game_aborted(&self) -> bool {
+        self.lichess_end_data
+            .as_ref()
+            .is_some_and(|(status, _)| status == "aborted")
+    }
+
+    /// Leave the finished game and look for a new Lichess opponent
+    pub fn seek_new_lichess_game(&mut self) {
+        self.reset_home();
+        self.menu_cursor = 0;
+        self.current_page = Pages::Lichess;
+        self.create_lichess_opponent();
+    }
+
+    /// Whether a confirm at `now` repeats the one that just played a move (a held key
+    /// or a double tap) and must be ignored, whatever the socket layer would do with it
+    pub fn is_move_confirm_debounced(&self, now: std::time::Instant) -> bool {
+        self.last_move_confirm.is_some_and(|last| {
+            now.saturating_duration_since(last) < Duration::from_millis(self.move_debounce_ms)
+        })
+    }
+
+    /// Take back the pawn move waiting in the promotion dialog; nothing was sent yet
+    pub fn cancel_promotion(&mut self) {
+        self.pending_promotion_move = None;
+        self.game.cancel_promotion();
+    }
+
+    /// Keyboard confirm on the cursor's square: select, move or pick the promotion.
+    /// Repeats right after a played move are dropped.
+    pub fn confirm_cell(&mut self, now: std::time::Instant) {
+        if self.is_move_confirm_debounced(now) {
+            log::debug!("Ignoring repeated move confirm");
+            return;
+        }
+        let moves_before = self.game.logic.game_board.move_history.len();
+        self.process_cell_click();
+        if self.game.logic.game_board.move_history.len() != moves_before {
+            self.last_move_confirm = Some(now);
+        }
+    }
+
+    /// Whether a game against a remote opponent (TCP or Lichess) is still being played
+    pub fn has_live_game(&self) -> bool {
+        !self.is_game_aborted()
+            && self.game.logic.opponent.is_some()
+            && matches!(
+                self.game.logic.game_state,
+                GameState::Playing | GameState::Promotion
+            )
+    }
+
+    /// Asks for confirmation before leaving a live game, otherwise goes straight home
+    pub fn request_leave_game(&mut self) {
+        if self.has_live_game() {
+            self.current_popup = Some(Popups::LeaveGameConfirmation);
+        } else {
+            self.reset_home();
+        }
+    }
+
+    /// Tears down the current remote game: closes the socket, drops the opponent
+    /// (which stops its background threads) and clears the per-game state.
+    pub fn end_current_game(&mut self) {
+        if let Some(ws_arc) = self.lichess_ws.take() {
+            if let Ok(ws) = ws_arc.lock() {
+                if let Err(e) = ws.close() {
+                    log::warn!("Failed to close Lichess WebSocket: {}", e);
+                }
+            }
+        }
+        self.auto_move_controller = None;
+        self.lichess_chat.clear();
+        self.lichess_presence = None;
+
+        if let Some(opponent) = self.game.logic.opponent.as_mut() {
+            opponent.send_end_game_to_server();
+            self.game.logic.opponent = None;
+            self.hosting = None;
+            self.host_ip = None;
+        }
+
+        self.release_rated_game_lock();
+        self.threefold_draw_claimed = false;
+        self.opponent_gone_deadline = None;
+        self.end_sync_receiver = None;
+        self.lichess_ws_move_tx = None;
+        self.lichess_end_data = None;
+        self.lichess_clocks_ms = None;
+        self.lichess_clocks_synced_at = None;
+        self.game_meta = None;
+        self.game_record_saved = false;
+    }
+
     /// Resets the application state and returns to the home page.
     /// Preserves display mode preference while cleaning up all game state,
     /// bot state, and multiplayer connections.
@@ -1642,6 +3311,8 @@ impl App {
         let display_mode = self.game.ui.display_mode;
         let current_skin = self.game.ui.skin.clone();
         self.end_screen_dismissed = false;
+        self.stop_deep_analysis();
+        self.deep_analysis_result = None;
 
         // Reset game-related state
         self.selected_color = None;
@@ -1649,12 +3320,7 @@ impl App {
         self.bot_move_receiver = None;
 
         // Clean up multiplayer connection if active
-        if let Some(opponent) = self.game.logic.opponent.as_mut() {
-            opponent.send_end_game_to_server();
-            self.game.logic.opponent = None;
-            self.hosting = None;
-            self.host_ip = None;
-        }
+        self.end_current_game();
 
         // Clear puzzle state
         self.puzzle_game = None;
@@ -1791,6 +3457,22 @@ impl App {
         }
     }
 
+    /// Show the position `index` plies into the game; the latest index or beyond
+    /// goes back to live play
+    pub fn navigate_history_to(&mut self, index: usize) {
+        // Check if we're in solo mode (no bot, no opponent)
+        let is_solo_mode = self.game.logic.bot.is_none() && self.game.logic.opponent.is_none();
+        if self
+            .game
+            .logic
+            .game_board
+            .navigate_history_to(index, is_solo_mode)
+        {
+            // Update player_turn to match the position's turn
+            self.game.logic.sync_player_turn_with_position();
+        }
+    }
+
     /// Navigate to the previous position in history (backward in time)
     pub fn navigate_history_previous(&mut self) {
         // Check if we're in solo mode (no bot, no opponent)
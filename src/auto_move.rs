@@ -1,15 +1,215 @@
 use log::{debug, info, warn};
-use std::sync::atomic::{AtomicBool, Ordering};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use shakmaty::fen::Fen;
+use shakmaty::uci::UciMove;
+use shakmaty::{CastlingMode, Chess, EnPassantMode, Position};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+/// Where an auto-move can come from, tried in the configured order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveSource {
+    /// Opening book lookup for the current position
+    Book,
+    /// Chess engine suggestion
+    Engine,
+    /// Random legal move, so a move always gets played
+    Random,
+}
+
+impl MoveSource {
+    /// Default chain: book, then engine, then a random legal move
+    pub fn default_chain() -> Vec<MoveSource> {
+        vec![MoveSource::Book, MoveSource::Engine, MoveSource::Random]
+    }
+}
+
+impl fmt::Display for MoveSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveSource::Book => write!(f, "book"),
+            MoveSource::Engine => write!(f, "engine"),
+            MoveSource::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl FromStr for MoveSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "book" => Ok(MoveSource::Book),
+            "engine" => Ok(MoveSource::Engine),
+            "random" => Ok(MoveSource::Random),
+            other => Err(format!("Unknown move source: {}", other)),
+        }
+    }
+}
+
+/// Engine evaluation from the side to move's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Eval {
+    /// Centipawns, positive when the side to move is better
+    Centipawns(i32),
+    /// Mate in this many moves, negative when the side to move gets mated
+    Mate(i32),
+}
+
+impl Eval {
+    /// Parse the score of a UCI `info` line (`score cp 35` or `score mate -3`)
+    pub fn from_uci_info(line: &str) -> Option<Eval> {
+        let mut tokens = line.split_whitespace().skip_while(|t| *t != "score").skip(1);
+        let kind = tokens.next()?;
+        let value = tokens.next()?.parse().ok()?;
+        match kind {
+            "cp" => Some(Eval::Centipawns(value)),
+            "mate" => Some(Eval::Mate(value)),
+            _ => None,
+        }
+    }
+
+    /// The same score seen from White's side, as eval bars show it
+    pub fn for_white(self, side_to_move: shakmaty::Color) -> Eval {
+        match (self, side_to_move) {
+            (eval, shakmaty::Color::White) => eval,
+            (Eval::Centipawns(cp), shakmaty::Color::Black) => Eval::Centipawns(-cp),
+            (Eval::Mate(moves), shakmaty::Color::Black) => Eval::Mate(-moves),
+        }
+    }
+}
+
+impl fmt::Display for Eval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Eval::Centipawns(cp) => write!(f, "{:+.2}", *cp as f64 / 100.0),
+            Eval::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+/// One engine evaluation kept for post-game review
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalRecord {
+    /// Ply of the position that was evaluated (0 = starting position)
+    pub ply: u32,
+    /// Move the engine chose, in UCI
+    pub best_move: String,
+    pub eval: Eval,
+}
+
+/// Per-game eval log: one JSON record per line in `<data dir>/games/<game id>.evals.jsonl`
+pub fn eval_log_path(game_id: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let data_dir = crate::constants::data_dir()
+        .map_err(|e| format!("Cannot store the eval log: {}", e))?;
+    Ok(data_dir.join("games").join(format!("{}.evals.jsonl", game_id)))
+}
+
+/// Append a record to the game's eval log
+pub fn append_eval_record(game_id: &str, record: &EvalRecord) -> Result<(), Box<dyn Error>> {
+    let path = eval_log_path(game_id)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Load a game's eval log, oldest first
+pub fn load_eval_log(game_id: &str) -> Result<Vec<EvalRecord>, Box<dyn Error>> {
+    let content = std::fs::read_to_string(eval_log_path(game_id)?)?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Lag reported in panic mode until the server has measured ours, unless configured
+pub const PANIC_LAG_MS: u32 = 50;
+
+/// Seconds our clock must climb back above the panic threshold before auto panic ends
+pub const PANIC_HYSTERESIS_SECS: f32 = 5.0;
+
+/// Centipawn deficit at which courtesy resignation kicks in unless configured
+pub const DEFAULT_COURTESY_RESIGN_CP: i32 = 800;
+
+/// Resign when the engine sees us mated within this many moves
+pub const COURTESY_RESIGN_MATE_DISTANCE: i32 = 5;
+
+/// Book key for a position: the FEN without move counters
+fn book_key(position: &Chess) -> String {
+    Fen::from_position(position.clone(), EnPassantMode::Legal)
+        .to_string()
+        .split(' ')
+        .take(4)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Check a UCI move is legal in the position and normalize it
+fn legal_uci(position: &Chess, uci: &str) -> Option<String> {
+    let uci_move = UciMove::from_ascii(uci.as_bytes()).ok()?;
+    let m = uci_move.to_move(position).ok()?;
+    Some(UciMove::from_move(&m, CastlingMode::Standard).to_string())
+}
+
+/// Called after every auto-move that was sent, with its UCI, the lag reported
+/// and whether it went out in panic mode
+pub type MoveCallback = Box<dyn FnMut(&str, u32, bool) + Send>;
+
+/// Counts of what auto-move did with the moves it was asked to send
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AutoMoveStats {
+    pub sent: u32,
+    pub blocked_duplicates: u32,
+    pub failed: u32,
+}
+
 /// Controller for automatic move execution with engine integration
 pub struct AutoMoveController {
     enabled: Arc<AtomicBool>,
     panic_mode: Arc<AtomicBool>,
+    panic_threshold: Option<f32>,
+    clock_panic: bool,
     engine_calculating: Arc<AtomicBool>,
+    engine_pondering: Arc<AtomicBool>,
+    fair_play_locked: Arc<AtomicBool>,
     last_move_sent: Option<String>,
     last_move_time: Option<Instant>,
+    min_interval: Duration,
+    reaction_delay: Option<(Duration, Duration)>,
+    reaction_ready_at: Option<Instant>,
+    move_sources: Vec<MoveSource>,
+    book: HashMap<String, Vec<String>>,
+    book_only: bool,
+    book_depth: Option<u32>,
+    notice: Option<String>,
+    courtesy_resign_cp: Option<i32>,
+    eval_log_game: Option<String>,
+    engine: Option<Box<dyn crate::engine::Engine>>,
+    lag_profile: (u32, u32),
+    lag_margin_ms: Option<u32>,
+    sent: AtomicU32,
+    blocked_duplicates: AtomicU32,
+    failed: AtomicU32,
+    on_move: Option<MoveCallback>,
+    rng: StdRng,
 }
 
 impl AutoMoveController {
@@ -18,14 +218,53 @@ impl AutoMoveController {
         AutoMoveController {
             enabled: Arc::new(AtomicBool::new(false)),
             panic_mode: Arc::new(AtomicBool::new(false)),
+            panic_threshold: None,
+            clock_panic: false,
             engine_calculating: Arc::new(AtomicBool::new(false)),
+            engine_pondering: Arc::new(AtomicBool::new(false)),
+            fair_play_locked: Arc::new(AtomicBool::new(false)),
             last_move_sent: None,
             last_move_time: None,
+            min_interval: Duration::ZERO,
+            reaction_delay: None,
+            reaction_ready_at: None,
+            move_sources: MoveSource::default_chain(),
+            book: HashMap::new(),
+            book_only: false,
+            book_depth: None,
+            notice: None,
+            courtesy_resign_cp: None,
+            eval_log_game: None,
+            engine: None,
+            lag_profile: (crate::lichess_ws::DEFAULT_LAG_MS, PANIC_LAG_MS),
+            lag_margin_ms: Some(0),
+            sent: AtomicU32::new(0),
+            blocked_duplicates: AtomicU32::new(0),
+            failed: AtomicU32::new(0),
+            on_move: None,
+            rng: StdRng::from_entropy(),
         }
     }
     
+    /// Create a controller whose random choices are reproducible from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        let mut controller = Self::new();
+        controller.set_seed(seed);
+        controller
+    }
+    
+    /// Reseed the RNG used for reaction delays and random moves
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        info!("[AutoMove] RNG seed: {}", seed);
+    }
+    
     /// Toggle auto-move on/off
     pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && self.is_fair_play_locked() {
+            warn!("[AutoMove] 🔒 Fair-play lock engaged, refusing to enable");
+            return;
+        }
         self.enabled.store(enabled, Ordering::Relaxed);
         if enabled {
             info!("[AutoMove] Enabled");
@@ -39,6 +278,306 @@ impl AutoMoveController {
         self.enabled.load(Ordering::Relaxed)
     }
     
+    /// Engage or release the fair-play lock (engaging also disables auto-move)
+    pub fn set_fair_play_lock(&mut self, locked: bool) {
+        self.fair_play_locked.store(locked, Ordering::Relaxed);
+        if locked {
+            self.enabled.store(false, Ordering::Relaxed);
+            info!("[AutoMove] 🔒 Fair-play lock engaged");
+        } else {
+            info!("[AutoMove] 🔓 Fair-play lock released");
+        }
+    }
+    
+    /// Check if the fair-play lock is engaged
+    pub fn is_fair_play_locked(&self) -> bool {
+        self.fair_play_locked.load(Ordering::Relaxed)
+    }
+    
+    /// Set the shortest time between consecutive auto-moves (zero turns it off), so a
+    /// second engine answer can't go out before the board has caught up
+    pub fn set_min_interval(&mut self, interval: Duration) {
+        self.min_interval = interval;
+        info!("[AutoMove] Minimum interval: {}ms", interval.as_millis());
+    }
+    
+    /// Get the minimum time between consecutive auto-moves
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+    
+    /// Check if the last auto-move was sent less than the minimum interval ago
+    fn within_min_interval(&self) -> bool {
+        self.last_move_time
+            .is_some_and(|sent_at| sent_at.elapsed() < self.min_interval)
+    }
+    
+    /// Set the delay range before reacting to an opponent's move (0, 0 turns it off)
+    pub fn set_reaction_delay(&mut self, min_ms: u64, max_ms: u64) {
+        if max_ms == 0 {
+            self.reaction_delay = None;
+            info!("[AutoMove] Reaction delay disabled");
+            return;
+        }
+        let (min_ms, max_ms) = (min_ms.min(max_ms), min_ms.max(max_ms));
+        self.reaction_delay = Some((Duration::from_millis(min_ms), Duration::from_millis(max_ms)));
+        info!("[AutoMove] Reaction delay: {}-{}ms", min_ms, max_ms);
+    }
+    
+    /// Get the configured reaction delay range
+    pub fn reaction_delay(&self) -> Option<(Duration, Duration)> {
+        self.reaction_delay
+    }
+    
+    /// Record that the opponent just moved, starting the reaction delay
+    pub fn on_opponent_move(&mut self) {
+        if let Some((min, max)) = self.reaction_delay {
+            let delay = if max > min {
+                self.rng.gen_range(min..=max)
+            } else {
+                min
+            };
+            debug!("[AutoMove] Reacting in {}ms", delay.as_millis());
+            self.reaction_ready_at = Some(Instant::now() + delay);
+        }
+    }
+    
+    /// Check if the reaction delay after the opponent's move is still running
+    pub fn is_reacting(&self) -> bool {
+        self.reaction_ready_at
+            .is_some_and(|ready_at| Instant::now() < ready_at)
+    }
+    
+    /// Set the ordered list of move sources to try
+    pub fn set_move_sources(&mut self, sources: Vec<MoveSource>) {
+        info!(
+            "[AutoMove] Move sources: {}",
+            sources.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" → ")
+        );
+        self.move_sources = sources;
+    }
+    
+    /// Get the ordered list of move sources
+    pub fn move_sources(&self) -> &[MoveSource] {
+        &self.move_sources
+    }
+    
+    /// Add a book move (UCI) for a position
+    pub fn add_book_move(&mut self, position: &Chess, uci: &str) {
+        self.book
+            .entry(book_key(position))
+            .or_default()
+            .push(uci.to_string());
+    }
+    
+    /// Only play the opening: auto-move fires while the book has a move (up to
+    /// `max_depth` full moves if set), then disables itself and hands back control
+    pub fn set_book_only(&mut self, book_only: bool, max_depth: Option<u32>) {
+        self.book_only = book_only;
+        self.book_depth = max_depth;
+        if book_only {
+            match max_depth {
+                Some(depth) => info!("[AutoMove] 📖 Book-only mode (up to move {})", depth),
+                None => info!("[AutoMove] 📖 Book-only mode"),
+            }
+        } else {
+            info!("[AutoMove] Book-only mode disabled");
+        }
+    }
+    
+    /// Check if book-only mode is on
+    pub fn is_book_only(&self) -> bool {
+        self.book_only
+    }
+    
+    /// Resign hopeless positions instead of letting the clock run out.
+    /// `threshold_cp` is the centipawn deficit that counts as hopeless; None turns it off.
+    pub fn set_courtesy_resign(&mut self, threshold_cp: Option<i32>) {
+        self.courtesy_resign_cp = threshold_cp.map(i32::abs);
+        match self.courtesy_resign_cp {
+            Some(cp) => info!("[AutoMove] 🏳️ Courtesy resign below -{}cp or mated soon", cp),
+            None => info!("[AutoMove] Courtesy resign disabled"),
+        }
+    }
+    
+    /// Check if courtesy resignation is on
+    pub fn is_courtesy_resign(&self) -> bool {
+        self.courtesy_resign_cp.is_some()
+    }
+    
+    /// Whether we should resign `position` (us to move) given the engine's `eval`.
+    /// Never fires when the opponent can't mate, so drawish endings are played out.
+    pub fn should_courtesy_resign(&self, position: &Chess, eval: Eval) -> bool {
+        let Some(threshold) = self.courtesy_resign_cp else {
+            return false;
+        };
+        if position.has_insufficient_material(position.turn().other()) {
+            return false;
+        }
+        match eval {
+            Eval::Mate(moves) => moves < 0 && -moves <= COURTESY_RESIGN_MATE_DISTANCE,
+            Eval::Centipawns(cp) => cp <= -threshold,
+        }
+    }
+    
+    /// Resign over the socket if the position is hopeless; returns true once resigned
+    pub fn courtesy_resign(
+        &mut self,
+        position: &Chess,
+        eval: Eval,
+        ws: &crate::lichess_ws::LichessWebSocket,
+    ) -> bool {
+        if !self.should_courtesy_resign(position, eval) {
+            return false;
+        }
+        match ws.resign() {
+            Ok(()) => {
+                info!("[AutoMove] 🏳️ Hopeless position ({:?}), resigned", eval);
+                self.enabled.store(false, Ordering::Relaxed);
+                true
+            }
+            Err(e) => {
+                warn!("[AutoMove] ❌ Failed to resign: {}", e);
+                false
+            }
+        }
+    }
+    
+    /// Persist engine evals to the eval log of `game_id` (None stops logging)
+    pub fn set_eval_log(&mut self, game_id: Option<String>) {
+        match &game_id {
+            Some(id) => info!("[AutoMove] Logging engine evals for game {}", id),
+            None => info!("[AutoMove] Eval log disabled"),
+        }
+        self.eval_log_game = game_id;
+    }
+    
+    /// Keep the engine's verdict on `position` for post-game review
+    pub fn record_eval(&self, position: &Chess, best_move: &str, eval: Eval) {
+        let Some(game_id) = &self.eval_log_game else {
+            return;
+        };
+        let ply = (position.fullmoves().get() - 1) * 2 + (position.turn() == shakmaty::Color::Black) as u32;
+        let record = EvalRecord {
+            ply,
+            best_move: best_move.to_string(),
+            eval,
+        };
+        if let Err(e) = append_eval_record(game_id, &record) {
+            warn!("[AutoMove] ❌ Failed to log eval: {}", e);
+        }
+    }
+    
+    /// Take the pending notice for the user (e.g. the book ran out), if any
+    pub fn take_notice(&mut self) -> Option<String> {
+        self.notice.take()
+    }
+    
+    /// Try each move source in order until one yields a legal move.
+    /// `engine` is only called when the engine source is reached.
+    /// In book-only mode the book is the only source, and leaving it disables auto-move.
+    pub fn pick_move<F>(&mut self, position: &Chess, mut engine: F) -> Option<(String, MoveSource)>
+    where
+        F: FnMut(&Chess) -> Option<String>,
+    {
+        if self.book_only {
+            let within_depth = self
+                .book_depth
+                .is_none_or(|depth| position.fullmoves().get() <= depth);
+            let book_move = self
+                .book
+                .get(&book_key(position))
+                .and_then(|moves| moves.choose(&mut self.rng))
+                .and_then(|uci| legal_uci(position, uci))
+                .filter(|_| within_depth);
+            
+            return match book_move {
+                Some(uci) => {
+                    info!("[AutoMove] 📖 Book move → {}", uci);
+                    Some((uci, MoveSource::Book))
+                }
+                None => {
+                    self.enabled.store(false, Ordering::Relaxed);
+                    info!("[AutoMove] 📖 Out of book, handing control back");
+                    self.notice = Some("Out of book: auto-move disabled, your move".to_string());
+                    None
+                }
+            };
+        }
+        
+        for source in &self.move_sources {
+            let candidate = match source {
+                MoveSource::Book => self
+                    .book
+                    .get(&book_key(position))
+                    .and_then(|moves| moves.choose(&mut self.rng))
+                    .and_then(|uci| legal_uci(position, uci)),
+                MoveSource::Engine => engine(position).and_then(|uci| legal_uci(position, &uci)),
+                MoveSource::Random => {
+                    let moves = position.legal_moves();
+                    moves
+                        .choose(&mut self.rng)
+                        .map(|m| UciMove::from_move(m, CastlingMode::Standard).to_string())
+                }
+            };
+            
+            match candidate {
+                Some(uci) => {
+                    info!("[AutoMove] 🎯 Source: {} → {}", source, uci);
+                    return Some((uci, *source));
+                }
+                None => debug!("[AutoMove] Source {} yielded no legal move", source),
+            }
+        }
+        
+        warn!("[AutoMove] ❌ No move source yielded a legal move");
+        None
+    }
+    
+    /// Engine consulted for the engine move source (a UCI subprocess by default)
+    pub fn set_engine(&mut self, engine: Box<dyn crate::engine::Engine>) {
+        info!("[AutoMove] Engine attached");
+        self.engine = Some(engine);
+    }
+    
+    /// Check if an engine is attached
+    pub fn has_engine(&self) -> bool {
+        self.engine.is_some()
+    }
+    
+    /// Pass our clock to the engine so its next search fits in the remaining time
+    pub fn set_clock(&mut self, remaining: Duration, increment: Duration) {
+        if let Some(engine) = self.engine.as_mut() {
+            engine.set_clock(remaining, increment);
+        }
+    }
+    
+    /// Like `pick_move`, asking the attached engine for the engine source.
+    /// The engine's eval for the chosen move goes to the eval log.
+    pub fn pick_engine_move(&mut self, position: &Chess) -> Option<(String, MoveSource)> {
+        let mut engine = self.engine.take();
+        let mut eval = None;
+        let picked = self.pick_move(position, |position| {
+            let fen = Fen::from_position(position.clone(), EnPassantMode::Legal).to_string();
+            match engine.as_mut()?.analyze(&fen) {
+                Ok(analysis) => {
+                    eval = analysis.eval;
+                    Some(analysis.best_move)
+                }
+                Err(e) => {
+                    warn!("[AutoMove] ❌ Engine failed: {}", e);
+                    None
+                }
+            }
+        });
+        self.engine = engine;
+        
+        if let (Some((uci, MoveSource::Engine)), Some(eval)) = (&picked, eval) {
+            self.record_eval(position, uci, eval);
+        }
+        picked
+    }
+    
     /// Toggle panic mode on/off
     pub fn set_panic_mode(&mut self, panic: bool) {
         self.panic_mode.store(panic, Ordering::Relaxed);
@@ -49,13 +588,81 @@ impl AutoMoveController {
         }
     }
     
-    /// Check if panic mode is enabled
+    /// Check if panic mode is enabled, by hand or by a low clock
     pub fn is_panic_mode(&self) -> bool {
-        self.panic_mode.load(Ordering::Relaxed)
+        self.panic_mode.load(Ordering::Relaxed) || self.clock_panic
+    }
+    
+    /// Enter panic mode on its own when our clock drops below `seconds`
+    /// (zero or less turns it off). Panic mode set by hand stays on regardless.
+    pub fn set_panic_threshold(&mut self, seconds: f32) {
+        if seconds > 0.0 {
+            self.panic_threshold = Some(seconds);
+            info!("[AutoMove] Panic threshold: {}s", seconds);
+        } else {
+            self.panic_threshold = None;
+            self.clock_panic = false;
+            info!("[AutoMove] Panic threshold disabled");
+        }
+    }
+    
+    /// Feed our remaining clock, entering or leaving panic mode against the threshold.
+    /// Leaving takes PANIC_HYSTERESIS_SECS more than the threshold, so increments
+    /// don't flip it on every move.
+    pub fn update_clock(&mut self, our_seconds: f32) {
+        let Some(threshold) = self.panic_threshold else {
+            return;
+        };
+        if !self.clock_panic && our_seconds < threshold {
+            self.clock_panic = true;
+            info!("[AutoMove] ⚡ Clock at {:.1}s, entering panic mode", our_seconds);
+        } else if self.clock_panic && our_seconds > threshold + PANIC_HYSTERESIS_SECS {
+            self.clock_panic = false;
+            info!("[AutoMove] Clock back to {:.1}s, leaving panic mode", our_seconds);
+        }
+    }
+    
+    /// Set the lag reported with moves in normal and panic mode, used whenever
+    /// adaptive lag is off or the server hasn't measured ours yet.
+    ///
+    /// Lichess credits the reported lag back to our clock, so a higher value keeps
+    /// fast premoves from flagging on a slow connection, but overstating it costs
+    /// time on every move the server doesn't believe.
+    pub fn set_lag_profile(&mut self, normal_ms: u32, panic_ms: u32) {
+        self.lag_profile = (normal_ms, panic_ms);
+        info!("[AutoMove] Lag profile: {}ms normal, {}ms panic", normal_ms, panic_ms);
+    }
+    
+    /// Report the lag the server measured plus `margin_ms` (None always reports the
+    /// fixed profile). On by default with no margin.
+    pub fn set_adaptive_lag(&mut self, margin_ms: Option<u32>) {
+        self.lag_margin_ms = margin_ms;
+        match margin_ms {
+            Some(margin) => info!("[AutoMove] Adaptive lag: measured + {}ms", margin),
+            None => info!("[AutoMove] Adaptive lag disabled"),
+        }
+    }
+    
+    /// Lag to report with the next move, given the server's last measurement
+    pub fn lag_ms(&self, measured_ms: Option<u32>) -> u32 {
+        let (normal, panic) = self.lag_profile;
+        let fixed = if self.is_panic_mode() {
+            panic
+        } else {
+            normal
+        };
+        match (self.lag_margin_ms, measured_ms) {
+            (Some(margin), Some(measured)) => measured.saturating_add(margin),
+            _ => fixed,
+        }
     }
     
     /// Check if we should execute an auto-move
     pub fn should_auto_move(&self, is_our_turn: bool) -> bool {
+        if self.fair_play_locked.load(Ordering::Relaxed) {
+            return false;
+        }
+        
         if !self.enabled.load(Ordering::Relaxed) {
             return false;
         }
@@ -69,20 +676,52 @@ impl AutoMoveController {
             return false;
         }
         
+        if self.is_reacting() {
+            return false;
+        }
+        
+        if self.within_min_interval() {
+            debug!("[AutoMove] Minimum interval not elapsed");
+            return false;
+        }
+        
         true
     }
     
+    /// Like `execute_auto_move`, first checking the move is legal in `position`
+    /// (the board's `position_ref()`), so a misparsed engine answer can't desync the game
+    pub fn execute_checked_auto_move(
+        &mut self,
+        uci: &str,
+        position: &Chess,
+        ws: &crate::lichess_ws::LichessWebSocket,
+    ) -> bool {
+        match legal_uci(position, uci) {
+            Some(legal) => self.execute_auto_move(&legal, ws),
+            None => {
+                warn!("[AutoMove] ❌ Illegal move blocked: {}", uci);
+                false
+            }
+        }
+    }
+    
     /// Execute an auto-move with duplicate prevention and lag compensation
     pub fn execute_auto_move(
         &mut self,
         uci: &str,
         ws: &crate::lichess_ws::LichessWebSocket,
     ) -> bool {
+        if self.fair_play_locked.load(Ordering::Relaxed) {
+            warn!("[AutoMove] 🔒 Fair-play lock engaged, blocking: {}", uci);
+            return false;
+        }
+        
         // Duplicate check with 500ms window
         let now = Instant::now();
         if let (Some(last_uci), Some(last_time)) = (&self.last_move_sent, &self.last_move_time) {
             if last_uci == uci && now.duration_since(*last_time) < Duration::from_millis(500) {
                 warn!("[AutoMove] ❌ Duplicate blocked: {}", uci);
+                self.blocked_duplicates.fetch_add(1, Ordering::Relaxed);
                 return false;
             }
         }
@@ -91,29 +730,51 @@ impl AutoMoveController {
         self.last_move_sent = Some(uci.to_string());
         self.last_move_time = Some(now);
         
-        // Calculate lag compensation
-        let lag_ms = if self.panic_mode.load(Ordering::Relaxed) {
-            50 // Panic mode: 50ms lag
-        } else {
-            20 // Normal mode: 20ms lag
-        };
+        // Lag compensation: what the server measured, else the profile
+        let lag_ms = self.lag_ms(ws.last_lag_ms());
         
-        let berserked = self.panic_mode.load(Ordering::Relaxed);
+        let berserked = self.is_panic_mode();
         
         // Send the move
         match ws.send_move(uci, lag_ms, berserked) {
             Ok(_) => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
                 info!("[AutoMove] ✅ Executed: {} | Lag: {}ms{}", 
                     uci, lag_ms, if berserked { " [PANIC]" } else { "" });
+                if let Some(on_move) = self.on_move.as_mut() {
+                    on_move(uci, lag_ms, berserked);
+                }
                 true
             }
             Err(e) => {
                 warn!("[AutoMove] ❌ Failed to send move: {}", e);
+                self.failed.fetch_add(1, Ordering::Relaxed);
                 false
             }
         }
     }
     
+    /// Call `callback` after each auto-move is sent (none by default)
+    pub fn set_on_move(&mut self, callback: MoveCallback) {
+        self.on_move = Some(callback);
+    }
+    
+    /// Moves sent, duplicates blocked and failed sends since the last reset
+    pub fn stats(&self) -> AutoMoveStats {
+        AutoMoveStats {
+            sent: self.sent.load(Ordering::Relaxed),
+            blocked_duplicates: self.blocked_duplicates.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+    
+    /// Zero the auto-move statistics
+    pub fn reset_stats(&self) {
+        self.sent.store(0, Ordering::Relaxed);
+        self.blocked_duplicates.store(0, Ordering::Relaxed);
+        self.failed.store(0, Ordering::Relaxed);
+    }
+    
     /// Mark engine as calculating
     pub fn set_engine_calculating(&mut self, calculating: bool) {
         self.engine_calculating.store(calculating, Ordering::Relaxed);
@@ -123,6 +784,29 @@ impl AutoMoveController {
     pub fn is_engine_calculating(&self) -> bool {
         self.engine_calculating.load(Ordering::Relaxed)
     }
+    
+    /// Mark engine as pondering on the opponent's time
+    pub fn set_engine_pondering(&mut self, pondering: bool) {
+        self.engine_pondering.store(pondering, Ordering::Relaxed);
+    }
+    
+    /// Check if engine is pondering on the opponent's time
+    pub fn is_engine_pondering(&self) -> bool {
+        self.engine_pondering.load(Ordering::Relaxed)
+    }
+    
+    /// The opponent played the pondered move: the search carries on as our move
+    pub fn ponder_hit(&mut self) {
+        debug!("[AutoMove] Ponderhit");
+        self.engine_pondering.store(false, Ordering::Relaxed);
+        self.engine_calculating.store(true, Ordering::Relaxed);
+    }
+    
+    /// The opponent played something else: the ponder search is dropped
+    pub fn ponder_miss(&mut self) {
+        debug!("[AutoMove] Ponder miss");
+        self.engine_pondering.store(false, Ordering::Relaxed);
+    }
 }
 
 impl Default for AutoMoveController {
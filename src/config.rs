@@ -9,6 +9,50 @@ pub struct Config {
     pub selected_skin_name: Option<String>,
     pub lichess_token: Option<String>,
     pub sound_enabled: Option<bool>,
+    /// Minimum delay before reacting to the opponent's move (auto-move)
+    pub reaction_delay_min_ms: Option<u64>,
+    /// Maximum delay before reacting to the opponent's move (auto-move)
+    pub reaction_delay_max_ms: Option<u64>,
+    /// Ordered auto-move sources ("book", "engine", "random")
+    pub auto_move_sources: Option<Vec<String>>,
+    /// Let auto-move play only book moves, then hand control back
+    pub auto_move_book_only: Option<bool>,
+    /// Last full move book-only auto-move may play (no limit if unset)
+    pub auto_move_book_depth: Option<u32>,
+    /// Let auto-move resign clearly lost positions instead of running out the clock
+    pub courtesy_resign: Option<bool>,
+    /// Centipawn deficit that counts as clearly lost for courtesy resignation
+    pub courtesy_resign_cp: Option<i32>,
+    /// Log unknown Lichess socket messages at warn level with their payload
+    pub ws_strict_mode: Option<bool>,
+    /// What the ack field of sent moves carries: "sequence" (default) or "ply" (legacy)
+    pub ws_ack_scheme: Option<String>,
+    /// Game socket host, bare ("socket1.lichess.org") or with a scheme ("ws://localhost:9000")
+    pub ws_host: Option<String>,
+    /// How pieces are moved: "two_click" (select then target), "drag" or "type" (ignores clicks)
+    pub input_method: Option<String>,
+    /// Board orientation on Lichess TV: "featured", "fixed_white", "fixed_black" or "side_to_move"
+    pub spectator_orientation: Option<String>,
+    /// Notation of the move list: "san" (default) or "uci"
+    pub move_notation: Option<String>,
+    /// Ignore move confirms this soon after a played move (held keys, double taps)
+    pub move_debounce_ms: Option<u64>,
+    /// Save every finished game's PGN (off by default)
+    pub auto_save_pgn: Option<bool>,
+    /// Rejoin the last session's game at startup without asking (off by default)
+    pub auto_resume_game: Option<bool>,
+    /// Directory for saved PGNs (DATA_DIR/games/pgn if unset)
+    pub pgn_dir: Option<String>,
+    /// Plies of board history kept before the oldest are trimmed
+    pub max_history_plies: Option<usize>,
+    /// Flash the board when the server acknowledges our move (off by default)
+    pub ack_flash: Option<bool>,
+    /// Show the file letters and rank numbers around the board (on by default)
+    pub show_coordinates: Option<bool>,
+    /// Show the engine evaluation bar next to the board while analyzing (on by default)
+    pub show_eval_bar: Option<bool>,
+    /// Where the Lichess session's credentials are kept: "file" (default) or "keyring"
+    pub auth_backend: Option<String>,
 }
 
 impl Default for Config {
@@ -21,6 +65,28 @@ impl Default for Config {
             selected_skin_name: Some("Default".to_string()),
             lichess_token: None,
             sound_enabled: Some(true),
+            reaction_delay_min_ms: None,
+            reaction_delay_max_ms: None,
+            auto_move_sources: None,
+            auto_move_book_only: Some(false),
+            auto_move_book_depth: None,
+            courtesy_resign: Some(false),
+            courtesy_resign_cp: None,
+            ws_strict_mode: Some(false),
+            ws_ack_scheme: Some("sequence".to_string()),
+            ws_host: None,
+            input_method: Some("two_click".to_string()),
+            spectator_orientation: Some("featured".to_string()),
+            move_notation: Some("san".to_string()),
+            move_debounce_ms: Some(crate::constants::DEFAULT_MOVE_DEBOUNCE_MS),
+            auto_save_pgn: Some(false),
+            auto_resume_game: Some(false),
+            pgn_dir: None,
+            max_history_plies: Some(crate::constants::DEFAULT_MAX_HISTORY_PLIES),
+            ack_flash: Some(false),
+            show_coordinates: Some(true),
+            show_eval_bar: Some(true),
+            auth_backend: Some("file".to_string()),
         }
     }
 }
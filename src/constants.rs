@@ -13,6 +13,18 @@ pub const NETWORK_BUFFER_SIZE: usize = 5;
 pub const SLEEP_DURATION_SHORT_MS: u64 = 50;
 pub const SLEEP_DURATION_LONG_MS: u64 = 100;
 
+// Input constants
+/// Window after a played move in which repeated confirm keys are ignored
+pub const DEFAULT_MOVE_DEBOUNCE_MS: u64 = 150;
+/// How long the board flashes once the server acknowledges our move
+pub const ACK_FLASH_DURATION_MS: u64 = 400;
+
+// History constants
+/// Plies kept in the board history before the oldest are trimmed (about 1000 moves)
+pub const DEFAULT_MAX_HISTORY_PLIES: usize = 2000;
+/// Plies skipped by Page Up / Page Down in the move list (five full moves)
+pub const HISTORY_JUMP_PLIES: usize = 10;
+
 pub const TITLE: &str = r"
  ██████╗██╗  ██╗███████╗███████╗███████╗   ████████╗██╗   ██╗██╗
 ██╔════╝██║  ██║██╔════╝██╔════╝██╔════╝   ╚══██╔══╝██║   ██║██║
@@ -39,6 +51,112 @@ impl fmt::Display for DisplayMode {
     }
 }
 
+/// How pieces are moved on the board
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum InputMethod {
+    /// Select a piece, then its target (keyboard or mouse clicks)
+    #[default]
+    TwoClick,
+    /// Press on a piece and release on the target (needs mouse support)
+    Drag,
+    /// Enter moves as text (UCI or SAN); mouse clicks are ignored
+    Type,
+}
+
+impl fmt::Display for InputMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InputMethod::TwoClick => write!(f, "two_click"),
+            InputMethod::Drag => write!(f, "drag"),
+            InputMethod::Type => write!(f, "type"),
+        }
+    }
+}
+
+impl std::str::FromStr for InputMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "two_click" => Ok(InputMethod::TwoClick),
+            "drag" => Ok(InputMethod::Drag),
+            "type" => Ok(InputMethod::Type),
+            other => Err(format!("Unknown input method: {}", other)),
+        }
+    }
+}
+
+/// Notation used for the moves in the move list
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MoveNotation {
+    /// Standard Algebraic Notation (Nf3, O-O); moves it can't be worked out for
+    /// are shown in UCI with a trailing `*`
+    #[default]
+    San,
+    /// Origin and target squares (g1f3, e1g1)
+    Uci,
+}
+
+impl fmt::Display for MoveNotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MoveNotation::San => write!(f, "san"),
+            MoveNotation::Uci => write!(f, "uci"),
+        }
+    }
+}
+
+impl std::str::FromStr for MoveNotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "san" => Ok(MoveNotation::San),
+            "uci" => Ok(MoveNotation::Uci),
+            other => Err(format!("Unknown move notation: {}", other)),
+        }
+    }
+}
+
+/// How the board is oriented while spectating (Lichess TV)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SpectatorOrientation {
+    /// Follow the featured player's side, as the TV feed suggests
+    #[default]
+    Featured,
+    /// Always White at the bottom
+    FixedWhite,
+    /// Always Black at the bottom
+    FixedBlack,
+    /// Flip each move so the side to move is at the bottom
+    SideToMove,
+}
+
+impl fmt::Display for SpectatorOrientation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SpectatorOrientation::Featured => write!(f, "featured"),
+            SpectatorOrientation::FixedWhite => write!(f, "fixed_white"),
+            SpectatorOrientation::FixedBlack => write!(f, "fixed_black"),
+            SpectatorOrientation::SideToMove => write!(f, "side_to_move"),
+        }
+    }
+}
+
+impl std::str::FromStr for SpectatorOrientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "featured" => Ok(SpectatorOrientation::Featured),
+            "fixed_white" => Ok(SpectatorOrientation::FixedWhite),
+            "fixed_black" => Ok(SpectatorOrientation::FixedBlack),
+            "side_to_move" => Ok(SpectatorOrientation::SideToMove),
+            other => Err(format!("Unknown spectator orientation: {}", other)),
+        }
+    }
+}
+
 pub fn config_dir() -> Result<PathBuf, &'static str> {
     match dirs::config_dir() {
         Some(dir) => Ok(dir),
@@ -46,6 +164,52 @@ pub fn config_dir() -> Result<PathBuf, &'static str> {
     }
 }
 
+/// Fallback data directory used when the OS config directory is unavailable
+pub const FALLBACK_DATA_DIR: &str = ".chess-tui";
+
+/// Data directory set with `--data-dir`, takes precedence over everything else
+static DATA_DIR_OVERRIDE: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+/// Override the data directory for every persisted file. Can only be set once,
+/// before anything reads `data_dir()`.
+pub fn set_data_dir(dir: PathBuf) -> Result<(), &'static str> {
+    DATA_DIR_OVERRIDE
+        .set(dir)
+        .map_err(|_| "Data directory was already set")
+}
+
+/// Directory holding everything chess-tui persists (config, skins, session, logs).
+/// Uses the `--data-dir` override if set, otherwise `<config dir>/chess-tui`.
+/// Falls back to `./.chess-tui` with a warning when the OS config directory is
+/// unavailable (no HOME, sandboxed), so local/offline modes keep working.
+pub fn data_dir() -> Result<PathBuf, &'static str> {
+    if let Some(dir) = DATA_DIR_OVERRIDE.get() {
+        return Ok(dir.clone());
+    }
+
+    if let Ok(dir) = config_dir() {
+        return Ok(dir.join("chess-tui"));
+    }
+
+    static WARN_ONCE: std::sync::Once = std::sync::Once::new();
+    match std::env::current_dir() {
+        Ok(dir) => {
+            let fallback = dir.join(FALLBACK_DATA_DIR);
+            WARN_ONCE.call_once(|| {
+                eprintln!(
+                    "Warning: could not get the config directory, using {}",
+                    fallback.display()
+                );
+                log::warn!("Config directory unavailable, using {}", fallback.display());
+            });
+            Ok(fallback)
+        }
+        Err(_) => {
+            Err("Could not get a data directory: no config directory and no working directory")
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Pages {
     Home,
@@ -54,12 +218,13 @@ pub enum Pages {
     Lichess,
     LichessMenu,
     OngoingGames,
+    LichessTv,
     Bot,
     Credit,
 }
 impl Pages {
     pub fn variant_count() -> usize {
-        7
+        8
     }
 }
 
@@ -79,4 +244,11 @@ pub enum Popups {
     EnterGameCode,
     EnterLichessToken,
     ResignConfirmation,
+    LeaveGameConfirmation,
+    EnterMove,
+    Diagnostics,
+    GameAborted,
+    ResumeGame,
+    DrawOffer,
+    TakebackOffer,
 }
@@ -0,0 +1,110 @@
+use crate::app::App;
+use crate::constants::data_dir;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Placeholder written in place of secrets
+pub const REDACTED: &str = "<redacted>";
+
+/// Build the diagnostic report for the app's current state.
+/// The Lichess token and session id never appear in it, even inside recorded frames.
+pub fn build_report(app: &App) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "chess-tui {}", env!("CARGO_PKG_VERSION"));
+
+    // Effective config, as it would be saved right now
+    let _ = writeln!(report, "\n[config]");
+    let config_path = data_dir()
+        .map(|dir| dir.join("config.toml"))
+        .unwrap_or_default();
+    let mut config = app.effective_config(&config_path);
+    if config.lichess_token.is_some() {
+        config.lichess_token = Some(REDACTED.to_string());
+    }
+    let _ = write!(report, "{}", toml::to_string(&config).unwrap_or_default());
+
+    let _ = writeln!(report, "\n[auth]");
+    let auth_kind = match (&app.lichess_token, &app.lichess_session) {
+        (Some(_), Some(_)) => "token + session",
+        (Some(_), None) => "token",
+        (None, Some(_)) => "session",
+        (None, None) => "none",
+    };
+    let _ = writeln!(report, "kind = {}", auth_kind);
+    if let Some(username) = app
+        .lichess_session
+        .as_ref()
+        .and_then(|s| s.username.as_ref())
+    {
+        let _ = writeln!(report, "session user = {}", username);
+    }
+
+    let _ = writeln!(report, "\n[connection]");
+    let _ = writeln!(report, "page = {:?}", app.current_page);
+    let opponent = match app.game.logic.opponent.as_ref() {
+        Some(opponent) if opponent.is_lichess_ws() => "lichess websocket",
+        Some(opponent) if opponent.is_lichess() => "lichess stream",
+        Some(opponent) if opponent.is_tcp_multiplayer() => "tcp multiplayer",
+        Some(_) => "unknown",
+        None => "none",
+    };
+    let _ = writeln!(report, "opponent = {}", opponent);
+    let mut frames = Vec::new();
+    match app.lichess_ws.as_ref().and_then(|ws| ws.lock().ok()) {
+        Some(ws) => {
+            let _ = writeln!(report, "websocket game = {}", ws.game_id());
+            let _ = writeln!(report, "ack scheme = {}", ws.ack_scheme());
+            let _ = writeln!(report, "move pending = {}", ws.is_move_pending());
+            let _ = writeln!(report, "game ended = {}", ws.is_game_ended());
+            frames = ws.recent_frames();
+        }
+        None => {
+            let _ = writeln!(report, "websocket = not connected");
+        }
+    }
+
+    let _ = writeln!(report, "\n[game]");
+    let _ = writeln!(report, "state = {:?}", app.game.logic.game_state);
+    let _ = writeln!(report, "turn = {:?}", app.game.logic.player_turn);
+    let _ = writeln!(report, "fen = {}", app.game.logic.game_board.fen_position());
+
+    let _ = writeln!(report, "\n[websocket frames]");
+    for frame in frames {
+        let _ = writeln!(report, "{}", frame);
+    }
+
+    redact(&report, &secrets(app))
+}
+
+/// Write the diagnostic report to a new file in the data directory and return its path
+pub fn write_report(app: &App) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = data_dir()?;
+    fs::create_dir_all(&dir)?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = dir.join(format!("diagnostics-{}.txt", timestamp));
+    fs::write(&path, build_report(app))?;
+    Ok(path)
+}
+
+/// Replace every occurrence of the given secrets with REDACTED
+pub fn redact(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |text, secret| {
+            text.replace(secret.as_str(), REDACTED)
+        })
+}
+
+fn secrets(app: &App) -> Vec<String> {
+    let mut secrets: Vec<String> = app.lichess_token.iter().cloned().collect();
+    if let Some(session) = &app.lichess_session {
+        secrets.push(session.session_id.clone());
+        secrets.extend(session.csrf_token.iter().cloned());
+        secrets.extend(session.token.iter().cloned());
+    }
+    secrets
+}
@@ -0,0 +1,284 @@
+use crate::auto_move::Eval;
+use ruci::{Go, SetOption};
+use shakmaty::fen::Fen;
+use std::borrow::Cow;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command};
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Depth of an on-demand analysis, deeper than the bot's default search
+pub const DEEP_ANALYSIS_DEPTH: u8 = 25;
+
+/// Expected number of moves left, over which the remaining clock is spread
+pub const MOVES_TO_GO: u32 = 30;
+/// Largest share of the remaining clock a single search may take
+pub const MAX_CLOCK_FRACTION: f64 = 0.4;
+/// Kept back from every search for network and UI lag
+pub const LAG_BUFFER: Duration = Duration::from_millis(300);
+/// Shortest search worth starting, even when the clock is nearly out
+pub const MIN_MOVETIME: Duration = Duration::from_millis(50);
+
+/// Time-management strategy: how long to search given our remaining time and increment.
+/// The budget is clamped to MAX_CLOCK_FRACTION of the clock minus LAG_BUFFER so the
+/// engine can never flag us. MIN_MOVETIME is applied last and wins over the ceiling:
+/// on a nearly empty clock the search still gets MIN_MOVETIME, as `go movetime 0`
+/// makes no useful move.
+pub fn movetime(remaining: Duration, increment: Duration) -> Duration {
+    let budget = remaining / MOVES_TO_GO + increment * 3 / 4;
+    let ceiling = remaining
+        .mul_f64(MAX_CLOCK_FRACTION)
+        .saturating_sub(LAG_BUFFER);
+    budget.min(ceiling).max(MIN_MOVETIME)
+}
+
+/// A source of engine moves and evaluations, so the UCI subprocess can be
+/// swapped for an in-process engine, a remote API or a test mock
+pub trait Engine: Send {
+    /// Best move for the position, in UCI
+    fn best_move(&mut self, fen: &str) -> Result<String, String> {
+        self.analyze(fen).map(|analysis| analysis.best_move)
+    }
+
+    /// Best move for the position along with the engine's evaluation
+    fn analyze(&mut self, fen: &str) -> Result<Analysis, String>;
+
+    /// Set an engine option (e.g. "Hash" to "64"); None for button options
+    fn set_option(&mut self, name: &str, value: Option<&str>) -> Result<(), String>;
+
+    /// Our remaining time and increment, for engines that manage their own search time
+    fn set_clock(&mut self, _remaining: Duration, _increment: Duration) {}
+}
+
+/// Result of analyzing a position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    /// Move the engine chose, in UCI
+    pub best_move: String,
+    /// Last score the engine reported, from the side to move's point of view
+    pub eval: Option<Eval>,
+}
+
+/// Spawn the engine process, supporting command-line arguments in the path
+/// (the first part is the command, the rest are arguments)
+pub(crate) fn spawn_engine(engine_path: &str) -> std::io::Result<Child> {
+    let parts: Vec<&str> = engine_path.split_whitespace().collect();
+    let (command, args) = if parts.is_empty() {
+        (engine_path, &[] as &[&str])
+    } else {
+        (parts[0], &parts[1..])
+    };
+
+    let mut cmd = Command::new(command);
+    if !args.is_empty() {
+        cmd.args(args);
+    }
+
+    cmd.stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+}
+
+/// Send the options and position, then run `go` until the engine's best move.
+/// `on_eval` gets every score the engine reports on the way.
+fn search<E: BufRead, G: Write>(
+    engine: &mut ruci::Engine<E, G>,
+    options: &[(String, Option<String>)],
+    fen: Fen,
+    go: &Go,
+    mut on_eval: impl FnMut(Eval),
+) -> Result<Analysis, String> {
+    for (name, value) in options {
+        engine
+            .send(SetOption {
+                name: Cow::Borrowed(name),
+                value: value.as_deref().map(Cow::Borrowed),
+            })
+            .map_err(|e| format!("Failed to set option {}: {}", name, e))?;
+    }
+
+    engine
+        .send(ruci::Position::Fen {
+            fen: Cow::Owned(fen),
+            moves: Cow::Borrowed(&[]),
+        })
+        .map_err(|e| format!("Failed to send position: {}", e))?;
+
+    let mut eval = None;
+    let best_move = engine
+        .go(go, |info| {
+            if let Some(score) = Eval::from_uci_info(&info.to_string()) {
+                eval = Some(score);
+                on_eval(score);
+            }
+        })
+        .map_err(|e| format!("Engine search failed: {}", e))?
+        .take_normal()
+        .ok_or_else(|| "Engine found no move".to_string())?;
+
+    Ok(Analysis {
+        best_move: best_move.r#move.to_string(),
+        eval,
+    })
+}
+
+/// Engine stdin shared between the search thread and whoever may stop it
+#[derive(Clone)]
+struct SharedStdin(Arc<Mutex<ChildStdin>>);
+
+impl Write for SharedStdin {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| io::Error::other("engine stdin poisoned"))?
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0
+            .lock()
+            .map_err(|_| io::Error::other("engine stdin poisoned"))?
+            .flush()
+    }
+}
+
+/// A one-shot search running in the background, which can be cut short
+pub struct AnalysisJob {
+    /// Position being searched
+    pub fen: String,
+    pub depth: u8,
+    stdin: SharedStdin,
+    result: Receiver<Result<Analysis, String>>,
+    latest_eval: Arc<Mutex<Option<Eval>>>,
+}
+
+impl AnalysisJob {
+    /// The search result, once the engine has answered
+    pub fn try_result(&self) -> Option<Result<Analysis, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("Analysis thread stopped".to_string())),
+        }
+    }
+
+    /// Last score the engine reported, from the side to move's point of view; it
+    /// changes as the search deepens
+    pub fn latest_eval(&self) -> Option<Eval> {
+        self.latest_eval.lock().ok().and_then(|eval| *eval)
+    }
+
+    /// Tell the engine to stop searching; it answers with its best move so far
+    /// and the process is then shut down
+    pub fn stop(&self) {
+        let mut stdin = self.stdin.clone();
+        if let Err(e) = stdin.write_all(b"stop\n") {
+            log::debug!("Failed to stop the analysis: {}", e);
+        }
+    }
+}
+
+/// Default engine: a UCI subprocess started for each search
+#[derive(Debug, Clone)]
+pub struct UciEngine {
+    pub engine_path: String,
+    /// Search depth sent with `go`, when no clock is known
+    pub depth: u8,
+    options: Vec<(String, Option<String>)>,
+    /// Search time from the last clock update, used instead of the depth
+    movetime: Option<Duration>,
+}
+
+impl UciEngine {
+    pub fn new(engine_path: &str, depth: u8) -> Self {
+        UciEngine {
+            engine_path: engine_path.to_string(),
+            depth,
+            options: Vec::new(),
+            movetime: None,
+        }
+    }
+
+    /// Search `fen` to `depth` on a background thread
+    pub fn spawn_analysis(&self, fen: &str, depth: u8) -> Result<AnalysisJob, String> {
+        let parsed = Fen::from_str(fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+        let mut process = spawn_engine(&self.engine_path)
+            .map_err(|e| format!("Failed to start engine '{}': {}", self.engine_path, e))?;
+        let (Some(stdin), Some(stdout)) = (process.stdin.take(), process.stdout.take()) else {
+            let _ = process.kill();
+            let _ = process.wait();
+            return Err("Failed to connect to engine: pipes not captured".to_string());
+        };
+        let stdin = SharedStdin(Arc::new(Mutex::new(stdin)));
+        let mut engine = ruci::Engine {
+            engine: BufReader::new(stdout),
+            gui: stdin.clone(),
+            strict: false,
+        };
+        let options = self.options.clone();
+        let go = Go {
+            depth: Some(depth as usize),
+            ..Default::default()
+        };
+
+        let latest_eval = Arc::new(Mutex::new(None));
+        let reported = Arc::clone(&latest_eval);
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let result = search(&mut engine, &options, parsed, &go, |eval| {
+                if let Ok(mut latest) = reported.lock() {
+                    *latest = Some(eval);
+                }
+            });
+            let _ = process.kill();
+            let _ = process.wait();
+            let _ = tx.send(result);
+        });
+        Ok(AnalysisJob {
+            fen: fen.to_string(),
+            depth,
+            stdin,
+            result: rx,
+            latest_eval,
+        })
+    }
+}
+
+impl Engine for UciEngine {
+    fn analyze(&mut self, fen: &str) -> Result<Analysis, String> {
+        let fen = Fen::from_str(fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+        let mut process = spawn_engine(&self.engine_path)
+            .map_err(|e| format!("Failed to start engine '{}': {}", self.engine_path, e))?;
+        let go = match self.movetime {
+            Some(movetime) => Go {
+                move_time: Some(movetime.as_millis() as usize),
+                ..Default::default()
+            },
+            None => Go {
+                depth: Some(self.depth as usize),
+                ..Default::default()
+            },
+        };
+        let result = ruci::Engine::from_process(&mut process, false)
+            .map_err(|e| format!("Failed to connect to engine: {}", e))
+            .and_then(|mut engine| search(&mut engine, &self.options, fen, &go, |_| {}));
+        let _ = process.kill();
+        let _ = process.wait();
+        result
+    }
+
+    fn set_option(&mut self, name: &str, value: Option<&str>) -> Result<(), String> {
+        // Applied to every engine process this spawns
+        self.options.retain(|(existing, _)| existing != name);
+        self.options
+            .push((name.to_string(), value.map(str::to_string)));
+        Ok(())
+    }
+
+    fn set_clock(&mut self, remaining: Duration, increment: Duration) {
+        self.movetime = Some(movetime(remaining, increment));
+    }
+}
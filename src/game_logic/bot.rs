@@ -1,9 +1,21 @@
-use ruci::{Engine, Go};
-use shakmaty::fen::Fen;
+use crate::engine::{spawn_engine, Engine as _, UciEngine};
+use ruci::{Engine, Id};
 use shakmaty::uci::UciMove;
-use std::borrow::Cow;
-use std::process::Command;
 use std::str::FromStr;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+/// How long an engine gets to answer `uci` before we give up on it
+const ENGINE_INFO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Identity and options an engine reports in reply to `uci`
+#[derive(Debug, Clone, Default)]
+pub struct EngineInfo {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    /// Options as the engine declared them (e.g. "name Hash type spin default 16 min 1 max 33554432")
+    pub options: Vec<String>,
+}
 
 #[derive(Clone)]
 pub struct Bot {
@@ -26,48 +38,73 @@ impl Bot {
         }
     }
 
-    pub fn get_move(&self, fen: &str) -> UciMove {
-        // Parse engine_path to support command-line arguments
-        // Split by spaces, treating first part as command and rest as args
-        let parts: Vec<&str> = self.engine_path.split_whitespace().collect();
-        let (command, args) = if parts.is_empty() {
-            (self.engine_path.as_str(), &[] as &[&str])
-        } else {
-            (parts[0], &parts[1..])
-        };
-
-        let mut cmd = Command::new(command);
-        if !args.is_empty() {
-            cmd.args(args);
+    /// Spawn the engine, send `uci` and collect its name, author and options.
+    /// Fails if the binary is missing or doesn't answer like a UCI engine.
+    pub fn engine_info(engine_path: &str) -> Result<EngineInfo, String> {
+        if engine_path.trim().is_empty() {
+            return Err("No engine path configured".to_string());
         }
 
-        let mut process = cmd
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .spawn()
-            .expect("Failed to spawn engine process");
+        let mut process = spawn_engine(engine_path)
+            .map_err(|e| format!("Failed to start engine '{}': {}", engine_path, e))?;
+        let mut engine = Engine::from_process(&mut process, false)
+            .map_err(|e| format!("Failed to connect to engine: {}", e))?;
 
-        let mut engine =
-            Engine::from_process(&mut process, false).expect("Failed to initialize engine");
+        // A binary that doesn't speak UCI may never answer, so wait on a thread
+        let (tx, rx) = channel();
+        std::thread::spawn(move || {
+            let mut options = Vec::new();
+            let result = engine
+                .use_uci(|option| options.push(option.to_string()))
+                .map(|id| {
+                    let (name, author) = match id {
+                        Some(Id::Name(name)) => (Some(name.into_owned()), None),
+                        Some(Id::Author(author)) => (None, Some(author.into_owned())),
+                        Some(Id::NameAndAuthor { name, author }) => {
+                            (Some(name.into_owned()), Some(author.into_owned()))
+                        }
+                        None => (None, None),
+                    };
+                    (name, author, options)
+                });
+            let _ = tx.send(result);
+        });
 
-        engine
-            .send(ruci::Position::Fen {
-                fen: Cow::Owned(Fen::from_str(fen).unwrap()),
-                moves: Cow::Borrowed(&[]),
-            })
-            .unwrap();
+        let result = rx.recv_timeout(ENGINE_INFO_TIMEOUT);
+        let _ = process.kill();
+        let _ = process.wait();
 
-        engine
-            .go(
-                &Go {
-                    depth: Some(self.depth as usize),
-                    ..Default::default()
-                },
-                |_| {},
-            )
-            .unwrap()
-            .take_normal()
-            .unwrap()
-            .r#move
+        let (name, author, options) = match result {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(e)) => return Err(format!("Engine did not answer 'uci': {}", e)),
+            Err(_) => {
+                return Err(format!(
+                    "Engine did not answer 'uci' within {}s, is it a UCI engine?",
+                    ENGINE_INFO_TIMEOUT.as_secs()
+                ))
+            }
+        };
+
+        Ok(EngineInfo {
+            name,
+            author,
+            options: options
+                .iter()
+                .map(|option| option.strip_prefix("option ").unwrap_or(option).to_string())
+                .collect(),
+        })
+    }
+
+    /// Engine backing this bot: the UCI subprocess at `engine_path`
+    pub fn engine(&self) -> UciEngine {
+        UciEngine::new(&self.engine_path, self.depth)
+    }
+
+    pub fn get_move(&self, fen: &str) -> UciMove {
+        let best_move = self
+            .engine()
+            .best_move(fen)
+            .expect("Failed to get a move from the engine");
+        UciMove::from_str(&best_move).expect("Engine returned an invalid move")
     }
 }
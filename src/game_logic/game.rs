@@ -1,5 +1,5 @@
 use super::{bot::Bot, coord::Coord, game_board::GameBoard, opponent::Opponent, ui::UI};
-use crate::utils::flip_square_if_needed;
+use crate::utils::{flip_square_if_needed, parse_uci};
 use shakmaty::{Color, Move, Position, Role, Square};
 
 #[derive(Clone, Debug, PartialEq, Eq, Copy)]
@@ -156,6 +156,13 @@ impl Game {
             self.logic.execute_bot_move();
         }
     }
+    /// Close the promotion dialog and take the pawn move back
+    pub fn cancel_promotion(&mut self) {
+        self.logic.cancel_promotion();
+        self.ui.promotion_cursor = 0;
+        self.ui.unselect_cell();
+    }
+
     /// Handle bot-specific logic after a move
     fn handle_after_move_bot_logic(&mut self) {
         if self.logic.bot.is_some() {
@@ -254,6 +261,45 @@ impl Game {
         self.handle_after_move_opponent_logic();
     }
 
+    /// Whether the piece on this (board orientation) square can be picked up:
+    /// it must belong to the side to move, be ours in online games, and have a legal move
+    pub fn can_select_square(&self, actual_square: Square, player_color: Option<Color>) -> bool {
+        if self.ui.free_selection {
+            return true;
+        }
+        if let Some(my_color) = player_color {
+            if self.logic.opponent.is_some() && my_color != self.logic.player_turn {
+                return false;
+            }
+        }
+        self.logic
+            .game_board
+            .get_piece_color_at_square(&actual_square)
+            == Some(self.logic.player_turn)
+            && !self
+                .logic
+                .game_board
+                .get_authorized_positions(self.logic.player_turn, &actual_square)
+                .is_empty()
+    }
+
+    /// Select the piece on a (display orientation) square with the mouse,
+    /// flashing the cell instead when it can't be selected
+    pub fn try_select_square(
+        &mut self,
+        square: Square,
+        coords: Coord,
+        player_color: Option<Color>,
+    ) -> bool {
+        let actual_square = flip_square_if_needed(square, self.logic.game_board.is_flipped);
+        if !self.can_select_square(actual_square, player_color) {
+            self.ui.flash_invalid_selection(coords);
+            return false;
+        }
+        self.ui.selected_square = Some(square);
+        true
+    }
+
     pub fn select_cell(&mut self) {
         let square = match self.ui.cursor_coordinates.to_square() {
             Some(s) => match Coord::from_square(s).to_square() {
@@ -264,32 +310,24 @@ impl Game {
         };
         let actual_square = flip_square_if_needed(square, self.logic.game_board.is_flipped);
 
-        // Check if there is a piece on the cell and if it's the right color
-        let piece_color = self
-            .logic
-            .game_board
-            .get_piece_color_at_square(&actual_square);
+        // Reject empty squares, opponent pieces and pieces that can't move
+        if !self.can_select_square(actual_square, None) {
+            self.ui.flash_invalid_selection(self.ui.cursor_coordinates);
+            return;
+        }
 
-        if self
-            .logic
-            .game_board
-            .get_role_at_square(&actual_square)
-            .is_none()
-            || piece_color != Some(self.logic.player_turn)
-        {
+        // Free selection (edit/analysis) has no destinations to move the cursor to
+        if self.ui.free_selection {
+            self.ui.selected_square = Some(square);
+            self.ui.old_cursor_position = self.ui.cursor_coordinates;
             return;
         }
 
-        // Check if the piece on the cell can move before selecting it
         let authorized_positions = self
             .logic
             .game_board
             .get_authorized_positions(self.logic.player_turn, &actual_square);
 
-        if authorized_positions.is_empty() {
-            return;
-        }
-
         // We already verified the piece color matches player_turn above, so we can proceed
         self.ui.selected_square = Some(square);
         self.ui.old_cursor_position = self.ui.cursor_coordinates;
@@ -329,6 +367,21 @@ impl GameLogic {
         }
     }
 
+    /// Take back the pawn move waiting for its promotion piece, as if it wasn't played
+    pub fn cancel_promotion(&mut self) {
+        if self.game_state != GameState::Promotion {
+            return;
+        }
+        if let Some(last_move) = self.game_board.move_history.pop() {
+            self.game_board.position_history.pop();
+            if last_move.capture().is_some() {
+                self.game_board.taken_pieces.pop();
+            }
+        }
+        self.sync_player_turn_with_position();
+        self.game_state = GameState::Playing;
+    }
+
     /// Sync player_turn with the current position's turn
     /// This is needed when navigating history to ensure moves are allowed for the correct player
     pub fn sync_player_turn_with_position(&mut self) {
@@ -479,61 +532,27 @@ impl GameLogic {
     /// Parse a move string in chess notation (e.g., "e2e4" or "e7e8q")
     /// Returns (from_square, to_square, promotion_piece) or None if invalid
     fn parse_opponent_move_string(move_str: &str) -> Option<(Square, Square, Option<Role>)> {
-        if move_str.len() < 4 {
-            return None;
-        }
-
-        let mut chars = move_str.chars();
-        let from_file_char = chars.next();
-        let from_rank_char = chars.next();
-        let to_file_char = chars.next();
-        let to_rank_char = chars.next();
-
-        // Parse promotion piece if present (5th character)
-        let promotion_piece: Option<Role> = if move_str.chars().count() == 5 {
-            match move_str.chars().nth(4) {
-                Some('q') => Some(Role::Queen),
-                Some('r') => Some(Role::Rook),
-                Some('b') => Some(Role::Bishop),
-                Some('n') => Some(Role::Knight),
-                _ => None,
-            }
-        } else {
-            None
-        };
-
         // The move is in standard chess notation (e.g., "e2e4")
         // Files are letters (a-h), ranks are digits (1-8)
-        let from_str = format!(
-            "{}{}",
-            from_file_char.unwrap_or('a'),
-            from_rank_char.unwrap_or('1')
-        );
-        let to_str = format!(
-            "{}{}",
-            to_file_char.unwrap_or('a'),
-            to_rank_char.unwrap_or('1')
-        );
-
-        let from = Square::from_ascii(from_str.as_bytes()).ok()?;
-        let to = Square::from_ascii(to_str.as_bytes()).ok()?;
+        let uci = match parse_uci(move_str) {
+            Ok(uci) => uci,
+            Err(e) => {
+                log::warn!("Rejected opponent move {:?}: {}", move_str, e);
+                return None;
+            }
+        };
 
         // Debug log for rook moves during parsing
-        if move_str.len() >= 4 {
-            // Check if this looks like a rook move (from a1, h1, a8, h8 or any file/rank move)
-            let from_file = from_str.chars().next().unwrap_or('a');
-            let from_rank = from_str.chars().nth(1).unwrap_or('1');
-            if (from_file == 'a' || from_file == 'h') && (from_rank == '1' || from_rank == '8') {
-                log::debug!(
-                    "Parsing potential rook move: {} -> {} (raw: {})",
-                    from_str,
-                    to_str,
-                    move_str
-                );
-            }
+        if matches!(uci.from, Square::A1 | Square::H1 | Square::A8 | Square::H8) {
+            log::debug!(
+                "Parsing potential rook move: {} -> {} (raw: {})",
+                uci.from,
+                uci.to,
+                move_str
+            );
         }
 
-        Some((from, to, promotion_piece))
+        Some((uci.from, uci.to, uci.promotion))
     }
 
     pub fn execute_opponent_move(&mut self) -> bool {
@@ -603,11 +622,13 @@ impl GameLogic {
             );
         }
 
-        // Check if move is already in history (duplicate detection)
-        let move_already_played = game_board
-            .move_history
-            .iter()
-            .any(|m| m.from() == Some(from) && m.to() == to);
+        // Check if move is already in history (duplicate detection); a legal move
+        // can be a repetition rather than a duplicate
+        let move_already_played = game_board.parse_typed_move(move_str).is_none()
+            && game_board
+                .move_history
+                .iter()
+                .any(|m| m.from() == Some(from) && m.to() == to);
 
         if move_already_played {
             log::warn!("Move {} already in history, skipping duplicate", move_str);
@@ -809,19 +830,21 @@ impl GameLogic {
         // Otherwise, we skip historical moves since we've already set up the board
         let history_is_empty = game_board.move_history.is_empty();
 
-        // For ongoing games with populated history, check if the move is already in history
-        let move_already_in_history = if !history_is_empty {
-            if let Some((from, to, _)) = Self::parse_opponent_move_string(move_str) {
-                game_board
-                    .move_history
-                    .iter()
-                    .any(|m| m.from() == Some(from) && m.to() == to)
+        // For ongoing games with populated history, check if the move is already in history.
+        // A move legal right now is live, since repeating a position repeats its moves
+        let move_already_in_history =
+            if !history_is_empty && game_board.parse_typed_move(move_str).is_none() {
+                if let Some((from, to, _)) = Self::parse_opponent_move_string(move_str) {
+                    game_board
+                        .move_history
+                        .iter()
+                        .any(|m| m.from() == Some(from) && m.to() == to)
+                } else {
+                    false
+                }
             } else {
                 false
-            }
-        } else {
-            false
-        };
+            };
 
         let is_historical = if history_is_empty {
             // History is empty - if initial_move_count > 0, we're joining an ongoing game
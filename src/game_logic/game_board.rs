@@ -1,5 +1,186 @@
 use super::coord::Coord;
-use shakmaty::{san::San, Chess, Color, Move, Piece, Position, Rank, Role, Square};
+use crate::constants::MoveNotation;
+use crate::utils::parse_uci;
+use shakmaty::fen::Fen;
+use shakmaty::san::SanPlus;
+use shakmaty::zobrist::{Zobrist64, ZobristHash};
+use shakmaty::{
+    attacks, san::San, Bitboard, CastlingMode, CastlingSide, Chess, Color, EnPassantMode, Move,
+    Piece, Position, Rank, Role, Square,
+};
+use std::fmt;
+
+/// Zobrist hash of a position: piece placement, side to move, castling rights and
+/// en-passant file. Move counters are ignored, so transpositions hash equal.
+pub fn position_hash(position: &Chess) -> u64 {
+    position.zobrist_hash::<Zobrist64>(EnPassantMode::Legal).0
+}
+
+/// Reasons a FEN string can be rejected by `parse_fen`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// A required field is missing (the move counters may be left out)
+    MissingField(&'static str),
+    /// The piece placement doesn't have 8 ranks
+    WrongRankCount(usize),
+    /// A rank, numbered 8 down to 1, doesn't cover exactly 8 squares
+    WrongSquareCount {
+        rank: u8,
+        squares: usize,
+    },
+    /// Not a piece letter nor an empty-square count
+    InvalidPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    /// Halfmove clock or fullmove number that isn't a number
+    InvalidCounter(String),
+    /// Well formed but not a position that can be played from (e.g. no king)
+    IllegalPosition(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "FEN is missing the {}", field),
+            FenError::WrongRankCount(count) => {
+                write!(f, "FEN piece placement must have 8 ranks, got {}", count)
+            }
+            FenError::WrongSquareCount { rank, squares } => {
+                write!(
+                    f,
+                    "FEN rank {} covers {} squares instead of 8",
+                    rank, squares
+                )
+            }
+            FenError::InvalidPiece(c) => {
+                write!(f, "Invalid character in FEN piece placement: {}", c)
+            }
+            FenError::InvalidSideToMove(side) => write!(f, "Invalid side to move in FEN: {}", side),
+            FenError::InvalidCastling(castling) => {
+                write!(f, "Invalid castling rights in FEN: {}", castling)
+            }
+            FenError::InvalidEnPassant(square) => {
+                write!(f, "Invalid en passant square in FEN: {}", square)
+            }
+            FenError::InvalidCounter(counter) => {
+                write!(f, "Invalid move counter in FEN: {}", counter)
+            }
+            FenError::IllegalPosition(e) => write!(f, "Illegal position in FEN: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// Reasons a SAN string can't be resolved by `GameBoard::parse_san`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    /// Not SAN at all, e.g. a typo or an empty string
+    Invalid(String),
+    /// No legal move in the position matches
+    Illegal(String),
+    /// Several legal moves match, e.g. `Nd2` when both knights can go there
+    Ambiguous(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::Invalid(san) => write!(f, "Not a SAN move: {}", san),
+            SanError::Illegal(san) => write!(f, "Illegal move in this position: {}", san),
+            SanError::Ambiguous(san) => write!(f, "Ambiguous move in this position: {}", san),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+/// Parse a FEN string: piece placement, side to move, castling rights, en passant
+/// square and, optionally, the halfmove clock and fullmove number
+pub fn parse_fen(fen: &str) -> Result<Chess, FenError> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let field = |index: usize, name: &'static str| -> Result<&str, FenError> {
+        fields
+            .get(index)
+            .copied()
+            .ok_or(FenError::MissingField(name))
+    };
+
+    let ranks: Vec<&str> = field(0, "piece placement")?.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::WrongRankCount(ranks.len()));
+    }
+    for (index, rank) in ranks.iter().enumerate() {
+        let mut squares = 0;
+        for c in rank.chars() {
+            match c {
+                '1'..='8' => squares += c as usize - '0' as usize,
+                'p' | 'n' | 'b' | 'r' | 'q' | 'k' | 'P' | 'N' | 'B' | 'R' | 'Q' | 'K' => {
+                    squares += 1
+                }
+                _ => return Err(FenError::InvalidPiece(c)),
+            }
+        }
+        if squares != 8 {
+            return Err(FenError::WrongSquareCount {
+                rank: 8 - index as u8,
+                squares,
+            });
+        }
+    }
+
+    let side = field(1, "side to move")?;
+    if side != "w" && side != "b" {
+        return Err(FenError::InvalidSideToMove(side.to_string()));
+    }
+    let castling = field(2, "castling rights")?;
+    if castling != "-" && !castling.chars().all(|c| "KQkq".contains(c)) {
+        return Err(FenError::InvalidCastling(castling.to_string()));
+    }
+    let en_passant = field(3, "en passant square")?;
+    if en_passant != "-"
+        && !Square::from_ascii(en_passant.as_bytes())
+            .is_ok_and(|square| matches!(square.rank(), Rank::Third | Rank::Sixth))
+    {
+        return Err(FenError::InvalidEnPassant(en_passant.to_string()));
+    }
+    for counter in fields.iter().skip(4) {
+        if counter.parse::<u32>().is_err() {
+            return Err(FenError::InvalidCounter(counter.to_string()));
+        }
+    }
+
+    // The fields are sound, so what shakmaty still rejects is the position itself
+    let fen = Fen::from_ascii(fields.join(" ").as_bytes())
+        .map_err(|e| FenError::IllegalPosition(e.to_string()))?;
+    fen.into_position(CastlingMode::Standard)
+        .map_err(|e| FenError::IllegalPosition(e.to_string()))
+}
+
+/// A played move as the move list and PGN export show it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveRecord {
+    pub chess_move: Move,
+    /// SAN with its check or mate suffix, disambiguated by file, rank or both as needed.
+    /// A move that isn't legal in its recorded position is given in UCI with a `*`.
+    pub san: String,
+    /// FEN of the position the move led to
+    pub fen: String,
+}
+
+/// Where the shown position stands, from the rules alone (draws by repetition or
+/// the fifty-move rule aren't reported here, see `GameBoard::is_draw`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    /// The side to move is in check but has a way out
+    Check,
+    /// The side to move is mated; holds the winner
+    Checkmate(Color),
+    /// The side to move isn't in check and has no legal move
+    Stalemate,
+}
 
 /// ## visual representation
 ///
@@ -37,6 +218,8 @@ pub struct GameBoard {
     pub last_ws_move: Option<(Square, Square)>,
     /// Timestamp of last WebSocket move
     pub last_ws_move_time: Option<std::time::Instant>,
+    /// Undone moves with the position each led to, latest last, until another move is played
+    pub redo_stack: Vec<(Move, Chess)>,
 }
 
 impl Default for GameBoard {
@@ -51,6 +234,7 @@ impl Default for GameBoard {
             original_flip_state: None,
             last_ws_move: None,
             last_ws_move_time: None,
+            redo_stack: Vec::new(),
         }
     }
 }
@@ -71,6 +255,47 @@ impl GameBoard {
         san.to_string()
     }
 
+    /// Every played move with its SAN and resulting FEN, oldest first. Built from the
+    /// move and position histories, so it always agrees with them.
+    pub fn history(&self) -> Vec<MoveRecord> {
+        self.move_history
+            .iter()
+            .zip(self.position_history.windows(2))
+            .map(|(chess_move, pair)| {
+                let (position, next) = (&pair[0], &pair[1]);
+                let san = if position.is_legal(chess_move) {
+                    SanPlus::from_move(position.clone(), chess_move).to_string()
+                } else {
+                    format!("{}*", chess_move.to_uci(CastlingMode::Standard))
+                };
+                MoveRecord {
+                    chess_move: chess_move.clone(),
+                    san,
+                    fen: Fen::from_position(next.clone(), EnPassantMode::Legal).to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /// The move in the requested notation. SAN needs the move to be legal in its
+    /// recorded position; when it isn't, the UCI is shown with a trailing `*`
+    pub fn move_notation(&self, move_index: usize, notation: MoveNotation) -> String {
+        let (Some(position), Some(chess_move)) = (
+            self.position_history.get(move_index),
+            self.move_history.get(move_index),
+        ) else {
+            return String::new();
+        };
+        let uci = chess_move.to_uci(CastlingMode::Standard).to_string();
+        match notation {
+            MoveNotation::Uci => uci,
+            MoveNotation::San if position.is_legal(chess_move) => {
+                San::from_move(position, chess_move).to_string()
+            }
+            MoveNotation::San => format!("{}*", uci),
+        }
+    }
+
     pub fn increment_consecutive_non_pawn_or_capture(
         &mut self,
         role_from: Role,
@@ -86,6 +311,48 @@ impl GameBoard {
         }
     }
 
+    /// Drop the oldest plies once the history holds more than `max_plies`.
+    ///
+    /// Only positions from before the last irreversible move (pawn move, capture or
+    /// lost castling right) are dropped: no later position can repeat them, so
+    /// threefold detection stays exact. Reversible stretches are kept whole, which
+    /// the fifty-move rule bounds anyway. Whole move pairs go so the move list stays
+    /// White/Black aligned. Trimmed moves are gone from the move list and from
+    /// exported PGNs, which then start from a FEN.
+    pub fn trim_history(&mut self, max_plies: usize) {
+        let plies = self.move_history.len();
+        if plies <= max_plies || self.position_history.len() != plies + 1 {
+            return;
+        }
+        let Some(first_recurrable) = (1..self.position_history.len()).rev().find(|&i| {
+            let (before, after) = (&self.position_history[i - 1], &self.position_history[i]);
+            after.halfmoves() == 0
+                || after.castles().castling_rights() != before.castles().castling_rights()
+        }) else {
+            return;
+        };
+        let cut = (plies - max_plies).min(first_recurrable) & !1;
+        if cut == 0 {
+            return;
+        }
+        self.move_history.drain(..cut);
+        self.position_history.drain(..cut);
+        if let Some(index) = self.history_position_index.as_mut() {
+            *index = index.saturating_sub(cut);
+        }
+        log::debug!("Trimmed {} plies from the board history", cut);
+    }
+
+    /// A board starting from the position in `fen`, with an empty history
+    pub fn from_fen(fen: &str) -> Result<GameBoard, FenError> {
+        let position = parse_fen(fen)?;
+        Ok(GameBoard {
+            consecutive_non_pawn_or_capture: position.halfmoves() as i32,
+            position_history: vec![position],
+            ..GameBoard::default()
+        })
+    }
+
     pub fn reset(&mut self) {
         self.move_history.clear();
         self.position_history.clear();
@@ -96,28 +363,39 @@ impl GameBoard {
         self.original_flip_state = None;
         self.last_ws_move = None;
         self.last_ws_move_time = None;
+        self.redo_stack.clear();
     }
 
     /// Set WebSocket last move highlight from UCI string (e.g., "e2e4")
     pub fn set_websocket_last_move(&mut self, uci: &str) {
-        if uci.len() < 4 {
-            log::warn!("Invalid UCI string for WebSocket move: {}", uci);
-            return;
+        match parse_uci(uci) {
+            Ok(mv) => {
+                self.last_ws_move = Some((mv.from, mv.to));
+                self.last_ws_move_time = Some(std::time::Instant::now());
+                log::info!("Set WebSocket last move highlight: {} -> {}", mv.from, mv.to);
+            }
+            Err(e) => {
+                log::warn!("Invalid UCI string for WebSocket move: {} ({})", uci, e);
+            }
         }
-        
-        // Parse from and to squares from UCI
-        let from_str = &uci[0..2];
-        let to_str = &uci[2..4];
-        
-        if let (Ok(from), Ok(to)) = (
-            Square::from_ascii(from_str.as_bytes()),
-            Square::from_ascii(to_str.as_bytes()),
-        ) {
-            self.last_ws_move = Some((from, to));
-            self.last_ws_move_time = Some(std::time::Instant::now());
-            log::info!("Set WebSocket last move highlight: {} -> {}", from, to);
-        } else {
-            log::warn!("Failed to parse UCI squares: {}", uci);
+    }
+
+    /// Origin and destination of the move that led to the shown position, for the
+    /// highlight. Castling goes from the king's square to where the king lands.
+    pub fn last_move(&self) -> Option<(Square, Square)> {
+        let shown = match self.history_position_index {
+            Some(index) => index.min(self.move_history.len()),
+            None => {
+                if let Some(ws_move) = self.last_ws_move {
+                    return Some(ws_move);
+                }
+                self.move_history.len()
+            }
+        };
+        let chess_move = self.move_history.get(shown.checked_sub(1)?)?;
+        match chess_move.to_uci(CastlingMode::Standard) {
+            shakmaty::uci::UciMove::Normal { from, to, .. } => Some((from, to)),
+            _ => None,
         }
     }
 
@@ -145,27 +423,67 @@ impl GameBoard {
         }
     }
 
-    // Check if the game is a draw by repetition
-    pub fn is_draw_by_repetition(&self) -> bool {
-        let mut position_counts = std::collections::HashMap::new();
-        for board in self.position_history.iter() {
-            let count = position_counts.entry(board).or_insert(0);
-            *count += 1;
+    /// How many times the shown position has occurred up to now, itself included.
+    /// Positions only match with the same castling rights and en passant capture,
+    /// as FIDE requires, since `position_hash` covers both. 2 is worth a warning.
+    pub fn repetition_count(&self) -> usize {
+        let shown = self
+            .history_position_index
+            .unwrap_or(usize::MAX)
+            .min(self.position_history.len().saturating_sub(1));
+        let current = position_hash(self.position_ref());
+        self.position_history
+            .iter()
+            .take(shown + 1)
+            .filter(|position| position_hash(position) == current)
+            .count()
+    }
 
-            if *count >= 3 {
-                return true;
-            }
+    /// Whether the shown position has now occurred three times
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    /// Halfmoves since the last pawn move or capture. The position keeps it, so it
+    /// follows history navigation and survives FEN imports and exports.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.position_ref().halfmoves()
+    }
+
+    /// Whether 50 moves each went by without a pawn move or capture
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock() >= 100
+    }
+
+    /// Whether neither side has the material to checkmate: bare kings, a lone minor
+    /// piece, only bishops all on one square color, or two knights against a bare
+    /// king (mate there can't be forced)
+    pub fn is_insufficient_material(&self) -> bool {
+        let board = self.position_ref().board();
+        if (board.pawns() | board.rooks() | board.queens()).any() {
+            return false;
+        }
+        let knights = board.knights();
+        let bishops = board.bishops();
+        if knights.is_empty() {
+            return (bishops & Bitboard::LIGHT_SQUARES).is_empty()
+                || (bishops & Bitboard::DARK_SQUARES).is_empty();
         }
-        false
+        // Knights only count against a bare king
+        [Color::White, Color::Black].into_iter().any(|color| {
+            let minors = board.by_color(color) & (knights | bishops);
+            board.by_color(color.other()) == board.kings() & board.by_color(color.other())
+                && (minors & bishops).is_empty()
+                && minors.count() <= 2
+        })
     }
 
     // Check if the game is a draw
     pub fn is_draw(&self) -> bool {
-        let chess = self.position_ref();
-        chess.is_stalemate()
-            || self.consecutive_non_pawn_or_capture == 50
-            || self.is_draw_by_repetition()
-            || chess.is_insufficient_material()
+        self.is_stalemate()
+            || self.is_fifty_move_draw()
+            || self.is_threefold_repetition()
+            || self.is_insufficient_material()
     }
 
     // We check manually if the last move was a pawn to one of the promotion squares
@@ -214,16 +532,71 @@ impl GameBoard {
             return vec![];
         }
 
-        // Get all legal moves
-        self.position_ref()
-            .clone()
-            .legal_moves()
+        self.legal_moves_from(*square)
             .iter()
-            .filter(|m| m.from() == Some(*square))
             .map(|m| m.to())
             .collect()
     }
 
+    /// Every legal move in the shown position: pins, check evasions, castling out of,
+    /// through or into check and en passant captures that uncover the king are all
+    /// accounted for. A `Move` gives its `from()`, `to()` and `promotion()`.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.position_ref().legal_moves().into_iter().collect()
+    }
+
+    /// Whether `color` may still castle on `side`: neither the king nor that rook has
+    /// moved, and the rook wasn't captured on its home square. Says nothing about
+    /// whether castling is legal right now, see `legal_moves`.
+    pub fn has_castling_right(&self, color: Color, side: CastlingSide) -> bool {
+        self.position_ref().castles().has(color, side)
+    }
+
+    /// Legal moves of the piece on `square`; castling is listed as the king's move
+    pub fn legal_moves_from(&self, square: Square) -> Vec<Move> {
+        self.legal_moves()
+            .into_iter()
+            .filter(|m| m.from() == Some(square))
+            .collect()
+    }
+
+    /// Resolve a typed move, in UCI ("e2e4") or SAN ("Nf3", "O-O"), against the current position
+    pub fn parse_typed_move(&self, input: &str) -> Option<Move> {
+        let input = input.trim();
+        let position = self.position_ref();
+        if let Ok(uci) = input.parse::<shakmaty::uci::UciMove>() {
+            if let Ok(chess_move) = uci.to_move(position) {
+                return Some(chess_move);
+            }
+        }
+        self.parse_san(input).ok()
+    }
+
+    /// Resolve SAN to the legal move it stands for in the shown position. Check and mate
+    /// suffixes and annotations like `!?` are ignored, so they needn't be accurate.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanError> {
+        let trimmed = san.trim();
+        let bare = trimmed.trim_end_matches(['+', '#', '!', '?']);
+        let parsed =
+            San::from_ascii(bare.as_bytes()).map_err(|_| SanError::Invalid(trimmed.to_string()))?;
+        parsed.to_move(self.position_ref()).map_err(|e| match e {
+            shakmaty::san::SanError::AmbiguousSan => SanError::Ambiguous(trimmed.to_string()),
+            shakmaty::san::SanError::IllegalSan => SanError::Illegal(trimmed.to_string()),
+        })
+    }
+
+    /// Get every square attacked by the given color's pieces in the current position
+    pub fn get_attacked_squares(&self, attacker: Color) -> Vec<Square> {
+        let board = self.position_ref().board();
+        let mut attacked = Bitboard::EMPTY;
+        for square in board.by_color(attacker) {
+            if let Some(piece) = board.piece_at(square) {
+                attacked |= attacks::attacks(square, piece, board.occupied());
+            }
+        }
+        attacked.into_iter().collect()
+    }
+
     /// Check if the king is in check
     pub fn is_getting_checked(&self, player_turn: Color) -> bool {
         let chess = self.position_ref();
@@ -252,7 +625,34 @@ impl GameBoard {
 
     /// Check if game is checkmate
     pub fn is_checkmate(&self) -> bool {
-        self.position_ref().clone().is_checkmate()
+        self.status() == GameStatus::Checkmate(self.position_ref().turn().other())
+    }
+
+    /// Whether `color`'s king is attacked, whoever's turn it is
+    pub fn is_in_check(&self, color: Color) -> bool {
+        let board = self.position_ref().board();
+        board.king_of(color).is_some_and(|king| {
+            board
+                .attacks_to(king, color.other(), board.occupied())
+                .any()
+        })
+    }
+
+    /// Check if the side to move is stalemated
+    pub fn is_stalemate(&self) -> bool {
+        self.status() == GameStatus::Stalemate
+    }
+
+    /// Check, mate or stalemate of the side to move, from its legal moves
+    pub fn status(&self) -> GameStatus {
+        let turn = self.position_ref().turn();
+        let in_check = self.is_in_check(turn);
+        match (in_check, self.legal_moves().is_empty()) {
+            (true, true) => GameStatus::Checkmate(turn.other()),
+            (true, false) => GameStatus::Check,
+            (false, true) => GameStatus::Stalemate,
+            (false, false) => GameStatus::Ongoing,
+        }
     }
 
     /// Flip the board for alternating perspectives
@@ -260,12 +660,16 @@ impl GameBoard {
         self.is_flipped = !self.is_flipped;
     }
 
+    /// Full six-field FEN of the shown position. The en passant square is only given
+    /// when a capture is actually possible, as Lichess does, so it compares equal
+    /// to the FEN the server sends with moves.
+    pub fn to_fen(&self) -> String {
+        Fen::from_position(self.position_ref().clone(), EnPassantMode::Legal).to_string()
+    }
+
     /// Get FEN position for UCI engine
     pub fn fen_position(&self) -> String {
-        // Use FEN display from shakmaty
-        use shakmaty::fen::Fen;
-        let fen = Fen::from_position(self.position_ref().clone(), shakmaty::EnPassantMode::Legal);
-        fen.to_string()
+        self.to_fen()
     }
 
     /// Get black taken pieces
@@ -286,6 +690,53 @@ impl GameBoard {
             .collect()
     }
 
+    /// Pieces of `color` missing from the shown position compared with the starting
+    /// material, most valuable first. Promoted pawns aren't counted as taken: each
+    /// piece beyond its starting count stands for a pawn that promoted.
+    pub fn captured_pieces(&self, color: Color) -> Vec<Role> {
+        let board = self.position_ref().board();
+        let count = |role: Role| (board.by_color(color) & board.by_role(role)).count();
+        let mut promoted = 0;
+        let mut captured = Vec::new();
+        for (role, start) in [
+            (Role::Queen, 1),
+            (Role::Rook, 2),
+            (Role::Bishop, 2),
+            (Role::Knight, 2),
+        ] {
+            let on_board = count(role);
+            promoted += on_board.saturating_sub(start);
+            captured.extend(std::iter::repeat_n(role, start.saturating_sub(on_board)));
+        }
+        let missing_pawns = 8usize.saturating_sub(count(Role::Pawn));
+        captured.extend(std::iter::repeat_n(
+            Role::Pawn,
+            missing_pawns.saturating_sub(promoted),
+        ));
+        captured
+    }
+
+    /// Material balance of the shown position in pawns (knights and bishops 3, rooks
+    /// 5, queens 9), positive when White is ahead
+    pub fn material_difference(&self) -> i32 {
+        let board = self.position_ref().board();
+        let material = |color: Color| -> i32 {
+            [
+                (Role::Pawn, 1),
+                (Role::Knight, 3),
+                (Role::Bishop, 3),
+                (Role::Rook, 5),
+                (Role::Queen, 9),
+            ]
+            .iter()
+            .map(|&(role, value)| {
+                (board.by_color(color) & board.by_role(role)).count() as i32 * value
+            })
+            .sum()
+        };
+        material(Color::White) - material(Color::Black)
+    }
+
     /// Reconstruct game history from a string of space-separated UCI moves
     /// Optionally verifies against an expected final FEN
     pub fn reconstruct_history(&mut self, moves_str: &str, expected_fen: Option<&str>) {
@@ -405,15 +856,14 @@ impl GameBoard {
 
                     // If positions don't match, use the FEN position as the final position
                     // Try to parse the expected FEN
-                    if let Ok(fen) = shakmaty::fen::Fen::from_ascii(fen_str.as_bytes()) {
-                        if let Ok(position) =
-                            fen.into_position::<shakmaty::Chess>(shakmaty::CastlingMode::Standard)
-                        {
+                    match parse_fen(fen_str) {
+                        Ok(position) => {
                             // Replace the last position in history with the FEN position
                             if let Some(last_pos) = self.position_history.last_mut() {
                                 *last_pos = position;
                             }
                         }
+                        Err(e) => log::warn!("Can't sync to the server's FEN: {}", e),
                     }
                 } else {
                     log::info!("Final position matches FEN - history is correct");
@@ -422,6 +872,30 @@ impl GameBoard {
         }
     }
 
+    /// Replace the history with `moves` played from `start`, e.g. the server's record of
+    /// the game, keeping every move paired with the position it led to
+    pub fn replay_from(&mut self, start: Chess, moves: &[Move]) -> Result<(), String> {
+        let mut positions = vec![start];
+        let mut taken_pieces = Vec::new();
+        for chess_move in moves {
+            let position = positions.last().unwrap();
+            taken_pieces.extend(position.board().piece_at(chess_move.to()));
+            let next = position
+                .clone()
+                .play(chess_move)
+                .map_err(|e| format!("Illegal move {}: {}", chess_move, e))?;
+            positions.push(next);
+        }
+        let is_flipped = self.is_flipped;
+        self.reset();
+        self.is_flipped = is_flipped;
+        self.consecutive_non_pawn_or_capture = positions.last().unwrap().halfmoves() as i32;
+        self.position_history = positions;
+        self.move_history = moves.to_vec();
+        self.taken_pieces = taken_pieces;
+        Ok(())
+    }
+
     /// Execute a move on the board
     /// Returns the executed Move if successful, None if illegal
     pub fn execute_move(
@@ -432,11 +906,6 @@ impl GameBoard {
     ) -> Option<Move> {
         let chess = self.position_ref().clone();
 
-        // Track captures before executing move
-        if let Some(captured_piece) = chess.board().piece_at(to) {
-            self.taken_pieces.push(captured_piece);
-        }
-
         // Find matching legal move
         let legal_moves = chess.legal_moves();
 
@@ -498,10 +967,20 @@ impl GameBoard {
 
         if let Some(shakmaty_move) = matching_move {
             // Execute move
-            match chess.play(shakmaty_move) {
+            match chess.clone().play(shakmaty_move) {
                 Ok(new_chess) => {
+                    // Track captures, including the pawn taken en passant, which
+                    // isn't on the square the capturing pawn lands on
+                    if let Some(role) = shakmaty_move.capture() {
+                        self.taken_pieces.push(Piece {
+                            color: chess.turn().other(),
+                            role,
+                        });
+                    }
                     // Update history
                     self.position_history.push(new_chess);
+                    self.redo_stack.clear();
+                    self.last_ws_move = None;
                     // Reset history navigation when a new move is made
                     self.history_position_index = None;
                     self.original_flip_state = None;
@@ -534,6 +1013,8 @@ impl GameBoard {
                     match chess_clone.play(castle_move) {
                         Ok(new_chess) => {
                             self.position_history.push(new_chess);
+                            self.redo_stack.clear();
+                            self.last_ws_move = None;
                             self.history_position_index = None;
                             self.original_flip_state = None;
                             log::info!("Castling move executed successfully: {:?}", castle_move);
@@ -679,6 +1160,35 @@ impl GameBoard {
         }
     }
 
+    /// Index in `position_history` of the position on the board: the browsed one, or
+    /// the latest. It's also the number of plies played to reach it.
+    pub fn shown_index(&self) -> usize {
+        let latest = self.position_history.len().saturating_sub(1);
+        self.history_position_index
+            .map_or(latest, |index| index.min(latest))
+    }
+
+    /// Show the position at `index` in the history, read-only; the latest index or
+    /// beyond goes back to live play. Returns whether the shown position changed.
+    /// is_solo_mode: whether we're in solo mode (affects board flipping)
+    pub fn navigate_history_to(&mut self, index: usize, is_solo_mode: bool) -> bool {
+        let latest = self.position_history.len().saturating_sub(1);
+        if index >= latest {
+            let was_browsing = self.history_position_index.is_some();
+            self.reset_history_navigation(is_solo_mode);
+            return was_browsing;
+        }
+        if self.history_position_index == Some(index) {
+            return false;
+        }
+        if self.history_position_index.is_none() {
+            self.original_flip_state = Some(self.is_flipped);
+        }
+        self.history_position_index = Some(index);
+        self.is_flipped = self.get_flip_state_for_index(index, is_solo_mode);
+        true
+    }
+
     /// Reset history navigation to view the latest position
     /// is_solo_mode: whether we're in solo mode (affects board flipping)
     pub fn reset_history_navigation(&mut self, is_solo_mode: bool) {
@@ -717,5 +1227,59 @@ impl GameBoard {
         // Reset history navigation since we're now at the latest position
         self.history_position_index = None;
         self.original_flip_state = None;
+        self.redo_stack.clear();
+        self.last_ws_move = None;
+    }
+
+    /// Whether there is a move to take back
+    pub fn can_undo(&self) -> bool {
+        !self.move_history.is_empty() && self.position_history.len() > 1
+    }
+
+    /// Whether an undone move can be played again
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Take back the last move. The previous position is kept whole in the history, so
+    /// its castling rights, en passant square and halfmove clock come back as they
+    /// were, and a captured piece leaves the taken pieces.
+    pub fn undo(&mut self) -> Option<Move> {
+        if !self.can_undo() {
+            return None;
+        }
+        let position = self.position_history.pop()?;
+        let chess_move = self.move_history.pop()?;
+        if let Some(role) = chess_move.capture() {
+            let piece = Piece {
+                color: position.turn(),
+                role,
+            };
+            if let Some(index) = self.taken_pieces.iter().rposition(|taken| *taken == piece) {
+                self.taken_pieces.remove(index);
+            }
+        }
+        self.history_position_index = None;
+        self.original_flip_state = None;
+        self.redo_stack.push((chess_move.clone(), position));
+        self.last_ws_move = None;
+        Some(chess_move)
+    }
+
+    /// Play the last undone move again
+    pub fn redo(&mut self) -> Option<Move> {
+        let (chess_move, position) = self.redo_stack.pop()?;
+        if let Some(role) = chess_move.capture() {
+            self.taken_pieces.push(Piece {
+                color: position.turn(),
+                role,
+            });
+        }
+        self.move_history.push(chess_move.clone());
+        self.position_history.push(position);
+        self.history_position_index = None;
+        self.original_flip_state = None;
+        self.last_ws_move = None;
+        Some(chess_move)
     }
 }
@@ -3,5 +3,8 @@ pub mod coord;
 pub mod game;
 pub mod game_board;
 pub mod opponent;
+pub mod perft;
 pub mod puzzle;
 pub mod ui;
+
+pub use perft::{perft, perft_divide};
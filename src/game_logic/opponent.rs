@@ -242,9 +242,12 @@ impl Opponent {
                 }
             }
             Some(OpponentKind::LichessWs { ws_handle, .. }) => {
-                // For WebSocket, use the send_move method with default lag
+                // For WebSocket, report the lag the server last measured
                 let ws = ws_handle.lock().unwrap();
-                if let Err(e) = ws.send_move(&move_str, 20, false) {
+                let lag_ms = ws
+                    .last_lag_ms()
+                    .unwrap_or(crate::lichess_ws::DEFAULT_LAG_MS);
+                if let Err(e) = ws.send_move(&move_str, lag_ms, false) {
                     eprintln!("Failed to send move via WebSocket: {}", e);
                 }
             }
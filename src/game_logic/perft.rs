@@ -0,0 +1,43 @@
+use super::game_board::GameBoard;
+use shakmaty::{Chess, Move, Position};
+
+/// Number of leaf positions `depth` plies below the shown position. Comparing it with
+/// published numbers catches castling, en passant and promotion bugs.
+pub fn perft(board: &GameBoard, depth: u32) -> u64 {
+    count_leaves(board.position_ref(), depth)
+}
+
+/// Leaf counts below each legal move of the shown position, in move generation order,
+/// to find the move a wrong total comes from
+pub fn perft_divide(board: &GameBoard, depth: u32) -> Vec<(Move, u64)> {
+    let position = board.position_ref();
+    position
+        .legal_moves()
+        .into_iter()
+        .map(|chess_move| {
+            let mut next = position.clone();
+            next.play_unchecked(&chess_move);
+            let leaves = count_leaves(&next, depth.saturating_sub(1));
+            (chess_move, leaves)
+        })
+        .collect()
+}
+
+fn count_leaves(position: &Chess, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = position.legal_moves();
+    match depth {
+        // Counting the last ply's moves saves playing each of them
+        1 => moves.len() as u64,
+        _ => moves
+            .iter()
+            .map(|chess_move| {
+                let mut next = position.clone();
+                next.play_unchecked(chess_move);
+                count_leaves(&next, depth - 1)
+            })
+            .sum(),
+    }
+}
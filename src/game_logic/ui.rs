@@ -3,7 +3,7 @@ use super::{
     game::{Game, GameLogic},
 };
 use crate::{
-    constants::{DisplayMode, BLACK, WHITE},
+    constants::{DisplayMode, MoveNotation, BLACK, WHITE},
     pieces::{role_to_utf_enum, PieceSize},
     skin::Skin,
     ui::{main_ui::render_cell, prompt::Prompt},
@@ -48,6 +48,12 @@ pub struct UI {
     pub cursor_blink_visible: bool,
     /// Counter to control how often the cursor blink toggles (in ticks)
     pub cursor_blink_counter: u8,
+    /// Tint the squares attacked by the opponent (training overlay)
+    pub show_threats: bool,
+    /// Cell flashed after an invalid selection and the ticks left on the flash
+    pub selection_error: Option<(Coord, u8)>,
+    /// Allow selecting any square (edit/analysis modes), skipping the piece checks
+    pub free_selection: bool,
 }
 
 impl Default for UI {
@@ -68,6 +74,9 @@ impl Default for UI {
             skin: Skin::default(),
             cursor_blink_visible: true,
             cursor_blink_counter: 0,
+            show_threats: false,
+            selection_error: None,
+            free_selection: false,
         }
     }
 }
@@ -86,11 +95,18 @@ impl UI {
         self.mouse_used = false;
         self.cursor_blink_visible = true;
         self.cursor_blink_counter = 0;
+        self.selection_error = None;
     }
 
     /// Update the cursor blink state. This is called from the global tick handler.
     /// When a piece is selected, the cursor cell will toggle visibility every few ticks.
     pub fn update_cursor_blink(&mut self) {
+        // Fade out the invalid selection flash
+        self.selection_error = match self.selection_error {
+            Some((coord, ticks)) if ticks > 1 => Some((coord, ticks - 1)),
+            _ => None,
+        };
+
         if self.is_cell_selected() {
             // Number of ticks between visibility toggles (higher = slower blink)
             const BLINK_INTERVAL_TICKS: u8 = 2;
@@ -107,6 +123,13 @@ impl UI {
         }
     }
 
+    /// Briefly flash a cell to signal that it can't be selected
+    pub fn flash_invalid_selection(&mut self, coord: Coord) {
+        // Number of ticks the flash stays visible
+        const INVALID_SELECTION_FLASH_TICKS: u8 = 3;
+        self.selection_error = Some((coord, INVALID_SELECTION_FLASH_TICKS));
+    }
+
     /// Check if a cell has been selected
     pub fn is_cell_selected(&self) -> bool {
         self.selected_square.is_some()
@@ -322,7 +345,13 @@ impl UI {
     }
 
     /// Method to render the right panel history
-    pub fn history_render(&self, area: Rect, frame: &mut Frame, game: &Game) {
+    pub fn history_render(
+        &self,
+        area: Rect,
+        frame: &mut Frame,
+        game: &Game,
+        notation: MoveNotation,
+    ) {
         // We write the history board on the side
         let history_block = Block::default()
             .title("History")
@@ -353,12 +382,31 @@ impl UI {
         let use_medium_mode = !use_big_mode && available_width >= MEDIUM_MODE_WIDTH;
 
         let mut lines: Vec<Line> = vec![];
+        // The move that led to the shown position is highlighted, and kept in view
+        let shown_move = game.logic.game_board.shown_index().checked_sub(1);
+        let mut shown_line = 0;
+        let move_style = |index: usize| {
+            if shown_move == Some(index) {
+                Style::default().fg(WHITE).add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default().fg(WHITE)
+            }
+        };
+
+        // Taken from the positions, so numbering survives a trimmed history
+        let move_number = |i: usize| {
+            game.logic
+                .game_board
+                .position_history
+                .get(i)
+                .map_or(i as u32 / 2 + 1, |position| position.fullmoves().get())
+        };
 
         for i in (0..game.logic.game_board.move_history.len()).step_by(2) {
             let role_from = game.logic.game_board.move_history[i].role();
 
             let utf_icon_white = role_to_utf_enum(&role_from, Some(shakmaty::Color::White));
-            let move_white = game.logic.game_board.move_to_san(i);
+            let move_white = game.logic.game_board.move_notation(i, notation);
 
             let mut utf_icon_black = "   ";
             let mut move_black: String = "   ".to_string();
@@ -367,10 +415,19 @@ impl UI {
             if i + 1 < game.logic.game_board.move_history.len() {
                 let role_to = game.logic.game_board.move_history[i + 1].role();
 
-                move_black = game.logic.game_board.move_to_san(i + 1);
+                move_black = game.logic.game_board.move_notation(i + 1, notation);
                 utf_icon_black = role_to_utf_enum(&role_to, Some(shakmaty::Color::Black));
             }
 
+            if shown_move.is_some_and(|index| index / 2 == i / 2) {
+                // One line per move in small mode, the black move on the second
+                shown_line = if use_big_mode || use_medium_mode {
+                    lines.len()
+                } else {
+                    lines.len() + shown_move.map_or(0, |index| index - i)
+                };
+            }
+
             if use_big_mode {
                 // Format white move: icon + space + move notation (fixed total width of 8 chars)
                 let white_move_formatted =
@@ -381,9 +438,9 @@ impl UI {
 
                 // Big mode: "  1. ♙ e4    ♟ d5   " (centered, with numbers, 2 moves on one line)
                 lines.push(Line::from(vec![
-                    Span::raw(format!("{:>3}. ", i / 2 + 1)), // line number (right-aligned, 3 chars + ". ")
-                    Span::styled(white_move_formatted, Style::default().fg(WHITE)), // white icon + move (fixed width)
-                    Span::styled(black_move_formatted, Style::default().fg(WHITE)), // black icon + move (fixed width)
+                    Span::raw(format!("{:>3}. ", move_number(i))), // line number (right-aligned, 3 chars + ". ")
+                    Span::styled(white_move_formatted, move_style(i)), // white icon + move (fixed width)
+                    Span::styled(black_move_formatted, move_style(i + 1)), // black icon + move (fixed width)
                 ]));
             } else if use_medium_mode {
                 // Format white move: icon + space + move notation (fixed total width of 8 chars)
@@ -395,8 +452,8 @@ impl UI {
 
                 // Medium mode: "♙ e4    ♟ d5   " (centered, no numbers, 2 moves on one line)
                 lines.push(Line::from(vec![
-                    Span::styled(white_move_formatted, Style::default().fg(WHITE)), // white icon + move (fixed width)
-                    Span::styled(black_move_formatted, Style::default().fg(WHITE)), // black icon + move (fixed width)
+                    Span::styled(white_move_formatted, move_style(i)), // white icon + move (fixed width)
+                    Span::styled(black_move_formatted, move_style(i + 1)), // black icon + move (fixed width)
                 ]));
             } else {
                 // Small mode: one move per line, no numbers
@@ -404,12 +461,12 @@ impl UI {
                 // "♟ d5"
                 let white_move_text = format!("{utf_icon_white} {}", move_white);
                 lines.push(Line::from(vec![
-                    Span::styled(white_move_text, Style::default().fg(WHITE)), // white move
+                    Span::styled(white_move_text, move_style(i)), // white move
                 ]));
                 if !move_black.trim().is_empty() {
                     let black_move_text = format!("{utf_icon_black} {}", move_black);
                     lines.push(Line::from(vec![
-                        Span::styled(black_move_text, Style::default().fg(WHITE)), // black move
+                        Span::styled(black_move_text, move_style(i + 1)), // black move
                     ]));
                 }
             }
@@ -418,24 +475,46 @@ impl UI {
         // Determine alignment: center for all modes
         let alignment = Alignment::Center;
 
-        let history_paragraph = Paragraph::new(lines).alignment(alignment);
+        // Scroll long games so the shown move stays on the last visible line at most
+        let scroll = shown_line.saturating_sub(inner_area.height.saturating_sub(1) as usize);
+        let history_paragraph = Paragraph::new(lines)
+            .alignment(alignment)
+            .scroll((scroll as u16, 0));
 
         frame.render_widget(history_block.clone(), right_panel_layout[0]);
         frame.render_widget(history_paragraph, inner_area);
     }
 
+    /// Frame of a material panel, marked in the skin's side-to-move color
+    /// while it's that player's turn. A material lead is added to the title.
+    fn material_block(&self, title: &str, lead: i32, to_move: bool) -> Block<'static> {
+        let title = if lead > 0 {
+            format!("{} +{}", title, lead)
+        } else {
+            title.to_string()
+        };
+        let (title, border_color) = if to_move {
+            (format!("▶ {}", title), self.skin.side_to_move_color)
+        } else {
+            (title, WHITE)
+        };
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .border_type(BorderType::Rounded)
+    }
+
     /// Method to render the white material
     pub fn white_material_render(
         &self,
         area: Rect,
         frame: &mut Frame,
         white_taken_pieces: &[Role],
+        lead: i32,
+        to_move: bool,
     ) {
-        let white_block = Block::default()
-            .title("White material")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(WHITE))
-            .border_type(BorderType::Rounded);
+        let white_block = self.material_block("White material", lead, to_move);
 
         let mut pieces: String = String::new();
 
@@ -474,13 +553,11 @@ impl UI {
         area: Rect,
         frame: &mut Frame,
         white_taken_pieces: &[Role],
+        lead: i32,
         is_puzzle_mode: bool,
+        to_move: bool,
     ) {
-        let white_block = Block::default()
-            .title("White material")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(WHITE))
-            .border_type(BorderType::Rounded);
+        let white_block = self.material_block("White material", lead, to_move);
 
         let mut pieces: String = String::new();
 
@@ -523,12 +600,10 @@ impl UI {
         area: Rect,
         frame: &mut Frame,
         black_taken_pieces: &[Role],
+        lead: i32,
+        to_move: bool,
     ) {
-        let black_block = Block::default()
-            .title("Black material")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(WHITE))
-            .border_type(BorderType::Rounded);
+        let black_block = self.material_block("Black material", lead, to_move);
 
         let mut pieces: String = String::new();
 
@@ -558,18 +633,12 @@ impl UI {
 
     /// Method to render the board
     fn get_last_move_squares(&self, logic: &GameLogic) -> (Option<Square>, Option<Square>) {
-        // Prioritize WebSocket move highlights
-        if let Some((from, to)) = logic.game_board.last_ws_move {
-            return (Some(from), Some(to));
-        }
-        
-        if logic.game_board.move_history.is_empty() {
+        // The move that led to the shown position, WebSocket moves included
+        let Some((from, to)) = logic.game_board.last_move() else {
             return (None, None);
-        }
-
-        let last_move = logic.game_board.move_history.last();
-        let last_move_from = last_move.map(|m| m.from()).unwrap();
-        let last_move_to = last_move.map(|m| m.to());
+        };
+        let last_move_from = Some(from);
+        let last_move_to = Some(to);
 
         // Check if this is multiplayer mode first (TCP or Lichess)
         // In multiplayer modes, always show the last move regardless of who made it
@@ -607,6 +676,33 @@ impl UI {
         (last_move_from, last_move_to)
     }
 
+    /// Squares attacked by the opponent, in display coordinates, when the overlay is on
+    fn get_threatened_positions_for_render(&self, logic: &GameLogic) -> Vec<Coord> {
+        if !self.show_threats {
+            return vec![];
+        }
+
+        // The opponent is the remote player or the bot; in solo mode it's the side not to move
+        let attacker = if let Some(opponent) = logic.opponent.as_ref() {
+            opponent.color
+        } else if let Some(bot) = logic.bot.as_ref() {
+            if bot.is_bot_starting {
+                shakmaty::Color::White
+            } else {
+                shakmaty::Color::Black
+            }
+        } else {
+            logic.player_turn.other()
+        };
+
+        logic
+            .game_board
+            .get_attacked_squares(attacker)
+            .iter()
+            .map(|s| get_coord_from_square(Some(*s), logic.game_board.is_flipped))
+            .collect()
+    }
+
     fn get_authorized_positions_for_render(
         &self,
         logic: &GameLogic,
@@ -654,9 +750,9 @@ impl UI {
         }
 
         let mut actual_square = self.selected_square;
-        if self.selected_square.is_some() {
+        if let Some(selected_square) = self.selected_square {
             actual_square = Some(flip_square_if_needed(
-                self.selected_square.unwrap(),
+                selected_square,
                 logic.game_board.is_flipped,
             ));
         }
@@ -731,6 +827,8 @@ impl UI {
                 let authorized_positions =
                     self.get_authorized_positions_for_render(logic, actual_square);
 
+                let threatened_positions = self.get_threatened_positions_for_render(logic);
+
                 let is_cell_in_positions = |positions: &Vec<Coord>, i: u8, j: u8| {
                     positions.iter().any(|&coord| coord == Coord::new(i, j))
                 };
@@ -738,13 +836,22 @@ impl UI {
                 let square = lines[j as usize + 1];
                 // Here we have all the possibilities for a cell:
                 // - selected cell: green
+                // - invalid selection flash: red
                 // - cursor cell: blue
                 // - available move cell: grey
                 // - checked king cell: magenta
                 // - last move cell: green
+                // - threatened cell (overlay): dark red
                 // - default cell: white or black
+                // Flash the cell red if it was just rejected as a selection
+                if self
+                    .selection_error
+                    .is_some_and(|(coord, _)| coord == Coord::new(i, j))
+                {
+                    render_cell(frame, square, Color::Rgb(170, 60, 60), None);
+                }
                 // Draw the cell blue if this is the current cursor cell
-                if i == self.cursor_coordinates.row
+                else if i == self.cursor_coordinates.row
                     && j == self.cursor_coordinates.col
                     && !self.mouse_used
                     // When a piece is selected, only draw the cursor cell on "visible" ticks
@@ -759,7 +866,7 @@ impl UI {
                     render_cell(frame, square, cursor_color, None);
                 }
                 // Draw the cell magenta if the king is getting checked
-                else if logic.game_board.is_getting_checked(logic.player_turn)
+                else if logic.game_board.is_in_check(logic.player_turn)
                     && Coord::new(i, j) == logic.game_board.get_king_coordinates(logic.player_turn)
                 {
                     render_cell(frame, square, Color::Magenta, Some(Modifier::SLOW_BLINK));
@@ -796,6 +903,8 @@ impl UI {
                     render_cell(frame, square, highlight_color, None);
                 } else if is_cell_in_positions(&authorized_positions, i, j) {
                     render_cell(frame, square, Color::Rgb(100, 100, 100), None);
+                } else if is_cell_in_positions(&threatened_positions, i, j) {
+                    render_cell(frame, square, Color::Rgb(150, 60, 60), None);
                 }
                 // else as a last resort we draw the cell with the default color either white or black
                 else {
@@ -872,7 +981,7 @@ impl UI {
             lines.push(
                 Line::from(*rank)
                     .alignment(Alignment::Center)
-                    .style(Style::default().fg(Color::Gray)),
+                    .style(Style::default().fg(self.skin.coordinate_color)),
             );
             for _ in 0..bottom_padding {
                 lines.push(Line::from(""));
@@ -911,7 +1020,7 @@ impl UI {
             lines.push(
                 Line::from(*file)
                     .alignment(Alignment::Center)
-                    .style(Style::default().fg(Color::Gray)),
+                    .style(Style::default().fg(self.skin.coordinate_color)),
             );
             for _ in 0..bottom_padding {
                 lines.push(Line::from(""));
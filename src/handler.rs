@@ -1,4 +1,4 @@
-use crate::constants::Popups;
+use crate::constants::{InputMethod, Popups, HISTORY_JUMP_PLIES};
 use crate::game_logic::coord::Coord;
 use crate::game_logic::game::GameState;
 use crate::utils::{flip_square_if_needed, get_coord_from_square};
@@ -133,6 +133,15 @@ fn handle_popup_input(app: &mut App, key_event: KeyEvent, popup: Popups) {
                     app.current_popup = None;
                 }
             }
+            KeyCode::Char('x') | KeyCode::Char('X') if app.game.logic.bot.is_some() => {
+                // Play the same bot game again from the other side
+                app.swap_sides_and_restart();
+                app.current_popup = None;
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') if !app.game_record_saved => {
+                // Keep the game as a PGN file
+                app.save_game_on_request();
+            }
             KeyCode::Char('b') | KeyCode::Char('B') => {
                 // Go back to home menu - completely reset all game state
                 app.reset_home();
@@ -287,11 +296,104 @@ fn handle_popup_input(app: &mut App, key_event: KeyEvent, popup: Popups) {
             }
             _ => fallback_key_handler(app, key_event),
         },
+        // Typed move entry (type input method)
+        Popups::EnterMove => match key_event.code {
+            KeyCode::Enter => {
+                app.game.ui.prompt.submit_message();
+                let typed_move = app.game.ui.prompt.message.clone();
+                if typed_move.trim().is_empty() {
+                    app.current_popup = None;
+                } else {
+                    match app.submit_typed_move(&typed_move) {
+                        Ok(()) => {
+                            app.error_message = None;
+                            // The move may have opened the end screen
+                            if app.current_popup == Some(Popups::EnterMove) {
+                                app.current_popup = None;
+                            }
+                        }
+                        // Keep the prompt open so the move can be corrected
+                        Err(e) => app.error_message = Some(e),
+                    }
+                }
+            }
+            KeyCode::Char(to_insert) => app.game.ui.prompt.enter_char(to_insert),
+            KeyCode::Backspace => app.game.ui.prompt.delete_char(),
+            KeyCode::Left => app.game.ui.prompt.move_cursor_left(),
+            KeyCode::Right => app.game.ui.prompt.move_cursor_right(),
+            KeyCode::Esc => {
+                app.error_message = None;
+                app.current_popup = None;
+            }
+            _ => fallback_key_handler(app, key_event),
+        },
+        // Diagnostics popup - shows where the bundle was written, stays on the page
+        Popups::Diagnostics => match key_event.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char(' ') => {
+                app.current_popup = None;
+                app.error_message = None;
+            }
+            _ => fallback_key_handler(app, key_event),
+        },
+        // Aborted game - no result, so offer a new seek instead of a rematch
+        Popups::GameAborted => match key_event.code {
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Enter => {
+                app.seek_new_lichess_game();
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') | KeyCode::Esc => {
+                app.reset_home();
+                app.current_page = Pages::LichessMenu;
+            }
+            _ => fallback_key_handler(app, key_event),
+        },
+        Popups::ResumeGame => match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                app.resume_saved_game();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app.current_popup = None;
+            }
+            _ => fallback_key_handler(app, key_event),
+        },
+        Popups::DrawOffer => match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => app.answer_draw_offer(true),
+            KeyCode::Char('n') | KeyCode::Char('N') => app.answer_draw_offer(false),
+            // Keep the offer open and get back to the board
+            KeyCode::Esc => app.current_popup = None,
+            _ => fallback_key_handler(app, key_event),
+        },
+        Popups::TakebackOffer => match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                app.answer_takeback_offer(true);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app.answer_takeback_offer(false);
+            }
+            _ => fallback_key_handler(app, key_event),
+        },
+        Popups::LeaveGameConfirmation => match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                // Closes the connection and resets the per-game state
+                app.reset_home();
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app.current_popup = None;
+            }
+            _ => fallback_key_handler(app, key_event),
+        },
     };
 }
 
 /// Routes keyboard input to the appropriate page handler based on current page.
 fn handle_page_input(app: &mut App, key_event: KeyEvent) {
+    let is_game_page = matches!(
+        app.current_page,
+        Pages::Solo | Pages::Multiplayer | Pages::Lichess | Pages::Bot
+    );
+    if is_game_page && app.game.logic.game_state == GameState::Promotion {
+        handle_promotion_events(app, key_event);
+        return;
+    }
     match &app.current_page {
         Pages::Home => handle_home_page_events(app, key_event),
         Pages::Solo => handle_solo_page_events(app, key_event),
@@ -299,11 +401,34 @@ fn handle_page_input(app: &mut App, key_event: KeyEvent) {
         Pages::Lichess => handle_multiplayer_page_events(app, key_event),
         Pages::LichessMenu => handle_lichess_menu_page_events(app, key_event),
         Pages::OngoingGames => handle_ongoing_games_page_events(app, key_event),
+        Pages::LichessTv => handle_lichess_tv_page_events(app, key_event),
         Pages::Bot => handle_bot_page_events(app, key_event),
         Pages::Credit => handle_credit_page_events(app, key_event),
     }
 }
 
+/// Handles keyboard input while the promotion dialog is open.
+/// Q/R/B/N pick a piece directly, Esc takes the pawn move back.
+fn handle_promotion_events(app: &mut App, key_event: KeyEvent) {
+    let now = std::time::Instant::now();
+    match key_event.code {
+        KeyCode::Right | KeyCode::Char('l') => app.game.ui.cursor_right_promotion(),
+        KeyCode::Left | KeyCode::Char('h') => app.game.ui.cursor_left_promotion(),
+        KeyCode::Char(' ') | KeyCode::Enter => app.confirm_cell(now),
+        KeyCode::Char(c @ ('q' | 'r' | 'b' | 'n' | 'Q' | 'R' | 'B' | 'N')) => {
+            app.game.ui.promotion_cursor = match c.to_ascii_lowercase() {
+                'q' => 0,
+                'r' => 1,
+                'b' => 2,
+                _ => 3,
+            };
+            app.confirm_cell(now);
+        }
+        KeyCode::Esc => app.cancel_promotion(),
+        _ => fallback_key_handler(app, key_event),
+    }
+}
+
 /// Handles keyboard input on the home/menu page.
 /// Supports navigation through menu items and selection.
 fn handle_home_page_events(app: &mut App, key_event: KeyEvent) {
@@ -364,6 +489,21 @@ fn handle_solo_page_events(app: &mut App, key_event: KeyEvent) {
                 app.navigate_history_previous();
             }
         }
+        // Jump through the move list: to the start, back to live, or a few moves at a time
+        KeyCode::Home | KeyCode::End | KeyCode::PageUp | KeyCode::PageDown
+            if app.puzzle_game.is_none()
+                && app.game.logic.game_state != GameState::Checkmate
+                && app.game.logic.game_state != GameState::Draw =>
+        {
+            let shown = app.game.logic.game_board.shown_index();
+            let index = match key_event.code {
+                KeyCode::Home => 0,
+                KeyCode::PageUp => shown.saturating_sub(HISTORY_JUMP_PLIES),
+                KeyCode::PageDown => shown + HISTORY_JUMP_PLIES,
+                _ => usize::MAX,
+            };
+            app.navigate_history_to(index);
+        }
         KeyCode::Char('t' | 'T') if app.puzzle_game.is_some() && app.current_popup.is_none() => {
             // Show hint in puzzle mode (only when no popup is active)
             app.show_puzzle_hint();
@@ -380,6 +520,23 @@ fn chess_inputs(app: &mut App, key_event: KeyEvent) {
     let is_playing = app.game.logic.game_state == GameState::Playing;
 
     match key_event.code {
+        // Training overlay: tint the squares attacked by the opponent
+        KeyCode::Char('a' | 'A') => app.toggle_threats_overlay(),
+        // One-shot deep engine analysis of the current position
+        KeyCode::Char('e' | 'E') => app.start_deep_analysis(),
+        // View the board from the other side
+        KeyCode::Char('o' | 'O') => app.flip_board(),
+        // Show or hide the coordinate labels, remembered in the config
+        KeyCode::Char('g' | 'G') => {
+            app.toggle_coordinates();
+            app.update_config();
+        }
+        // Show or hide the evaluation bar, remembered in the config
+        KeyCode::Char('v' | 'V') => {
+            app.toggle_eval_bar();
+            app.update_config();
+        }
+
         // Vertical cursor movement (only during active play)
         KeyCode::Up | KeyCode::Char('k') if is_playing => app.go_up_in_game(),
         KeyCode::Down | KeyCode::Char('j') if is_playing => app.go_down_in_game(),
@@ -438,10 +595,18 @@ fn chess_inputs(app: &mut App, key_event: KeyEvent) {
                 }
             }
         },
+        // Type mode: enter the move as text instead of picking squares
+        KeyCode::Char(' ') | KeyCode::Enter
+            if app.input_method == InputMethod::Type && is_playing =>
+        {
+            app.game.ui.prompt.reset();
+            app.error_message = None;
+            app.current_popup = Some(Popups::EnterMove);
+        }
         // Select/move piece or confirm action
         KeyCode::Char(' ') | KeyCode::Enter => {
             // In Lichess mode, only allow input if it's our turn
-            app.process_cell_click();
+            app.confirm_cell(std::time::Instant::now());
         }
         KeyCode::Char('?') => app.toggle_help_popup(), // Toggle help popup
         KeyCode::Char('s' | 'S') => {
@@ -458,8 +623,28 @@ fn chess_inputs(app: &mut App, key_event: KeyEvent) {
 fn handle_multiplayer_page_events(app: &mut App, key_event: KeyEvent) {
     match key_event.code {
         KeyCode::Char('b') => {
-            // Return to home menu - disconnect from opponent and reset state
-            app.reset_home();
+            // Return to home menu - asks first if the game is still being played
+            app.request_leave_game();
+        }
+        // Claim victory once the opponent's disconnect countdown expired
+        KeyCode::Char('c') if app.can_claim_victory() => app.claim_victory(),
+        // Ask the opponent to take back our last move (Lichess games)
+        KeyCode::Char('u') | KeyCode::Char('U') if app.lichess_ws.is_some() => {
+            if let Err(e) = app.propose_takeback() {
+                log::warn!("Takeback not proposed: {}", e);
+            }
+        }
+        // Resign the Lichess game, after a confirmation
+        KeyCode::Char('r') | KeyCode::Char('R') if app.lichess_ws.is_some() => {
+            app.request_resign();
+        }
+        // Offer a draw (Lichess games), withdrawn by our next move; accepts a standing offer
+        KeyCode::Char('d')
+            if app.lichess_ws.is_some() && key_event.modifiers != KeyModifiers::CONTROL =>
+        {
+            if let Err(e) = app.offer_draw() {
+                log::warn!("Draw offer refused: {}", e);
+            }
         }
 
         _ => chess_inputs(app, key_event), // Delegate chess-specific inputs
@@ -471,6 +656,7 @@ fn handle_multiplayer_page_events(app: &mut App, key_event: KeyEvent) {
 fn handle_bot_page_events(app: &mut App, key_event: KeyEvent) {
     match key_event.code {
         KeyCode::Char('r') => app.restart(), // Restart current game
+        KeyCode::Char('x') => app.swap_sides_and_restart(), // Restart with colors swapped
         KeyCode::Char('b') => {
             // Return to home menu - clean up bot and reset state
             app.reset_home();
@@ -479,6 +665,16 @@ fn handle_bot_page_events(app: &mut App, key_event: KeyEvent) {
     }
 }
 
+/// Handles keyboard input while spectating Lichess TV.
+/// The board is read-only, so only leaving and global shortcuts apply.
+fn handle_lichess_tv_page_events(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
+        KeyCode::Esc | KeyCode::Char('b') => app.stop_lichess_tv(),
+        KeyCode::Char('?') => app.toggle_help_popup(),
+        _ => fallback_key_handler(app, key_event),
+    }
+}
+
 /// Handles keyboard input on the credits page.
 fn handle_credit_page_events(app: &mut App, key_event: KeyEvent) {
     match key_event.code {
@@ -493,7 +689,13 @@ fn fallback_key_handler(app: &mut App, key_event: KeyEvent) {
     match key_event.code {
         KeyCode::Char('q') => app.quit(), // Quit application
         KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => app.quit(), // Ctrl+C to quit
+        KeyCode::Char('d' | 'D')
+            if key_event.modifiers == KeyModifiers::CONTROL && app.current_popup.is_none() =>
+        {
+            app.dump_diagnostics() // Ctrl+D writes a diagnostic bundle
+        }
         KeyCode::Char('s') => app.cycle_skin(), // Cycle through available skins
+        KeyCode::Char('f' | 'F') => app.toggle_fair_play_lock(), // Toggle fair-play lock
         _ => (),                                // Ignore other keys
     }
 }
@@ -504,7 +706,31 @@ fn fallback_key_handler(app: &mut App, key_event: KeyEvent) {
 /// Handles both board clicks and promotion selection clicks.
 pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<()> {
     // Mouse control only implemented for game pages, not home or credits
-    if app.current_page == Pages::Home || app.current_page == Pages::Credit {
+    // (Lichess TV is spectate-only)
+    if app.current_page == Pages::Home
+        || app.current_page == Pages::Credit
+        || app.current_page == Pages::LichessTv
+    {
+        return Ok(());
+    }
+
+    // Type mode only takes moves from the keyboard
+    if app.input_method == InputMethod::Type {
+        return Ok(());
+    }
+
+    // Drag mode: releasing the button over another square drops the held piece there
+    if mouse_event.kind == MouseEventKind::Up(MouseButton::Left)
+        && app.input_method == InputMethod::Drag
+    {
+        if app.current_popup.is_some() || app.game.logic.game_state != GameState::Playing {
+            return Ok(());
+        }
+        if let Some((square, coords)) = board_square_at(app, mouse_event.column, mouse_event.row) {
+            if app.game.ui.selected_square.is_some_and(|s| s != square) {
+                app.try_mouse_move(square, coords);
+            }
+        }
         return Ok(());
     }
 
@@ -641,8 +867,9 @@ pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<
 
         // Handle click on empty square
         if piece_color.is_none() {
-            // If no piece was previously selected, ignore the click
+            // If no piece was previously selected, there is nothing to pick up here
             if app.game.ui.selected_square.is_none() {
+                app.game.ui.flash_invalid_selection(coords);
                 return Ok(());
             } else {
                 // Piece was selected - try to execute move to empty square
@@ -684,7 +911,8 @@ pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<
                             if app.try_mouse_move(square, coords) {
                                 return Ok(());
                             }
-                            app.game.ui.selected_square = Some(square);
+                            app.game
+                                .try_select_square(square, coords, app.selected_color);
                             return Ok(());
                         }
                     };
@@ -720,25 +948,43 @@ pub fn handle_mouse_events(mouse_event: MouseEvent, app: &mut App) -> AppResult<
                     return Ok(());
                 }
             }
-            // Otherwise, select the clicked piece
-            app.game.ui.selected_square = Some(square);
+            // Otherwise, select the clicked piece if it can move
+            app.game
+                .try_select_square(square, coords, app.selected_color);
         } else {
             // Clicked on opponent's piece - try to capture if valid
             if app.game.ui.selected_square.is_some() {
                 app.try_mouse_move(square, coords);
+            } else {
+                // No piece selected and clicked opponent piece - can't pick it up
+                app.game.ui.flash_invalid_selection(coords);
             }
-            // No piece selected and clicked opponent piece - ignore (try_execute_move handles this)
         }
     }
     Ok(())
 }
 
+/// Maps a terminal position to the board square under it (display orientation) and its coordinates.
+fn board_square_at(app: &App, column: u16, row: u16) -> Option<(Square, Coord)> {
+    let ui = &app.game.ui;
+    if column < ui.top_x || row < ui.top_y || ui.width == 0 || ui.height == 0 {
+        return None;
+    }
+    let x = (column - ui.top_x) / ui.width;
+    let y = (row - ui.top_y) / ui.height;
+    if x > 7 || y > 7 {
+        return None;
+    }
+    let coords = Coord::new(y as u8, x as u8);
+    coords.try_to_square().map(|square| (square, coords))
+}
+
 /// Handles keyboard input on the Lichess menu page.
 /// Supports navigation through menu items and selection.
 fn handle_lichess_menu_page_events(app: &mut App, key_event: KeyEvent) {
     match key_event.code {
-        KeyCode::Up | KeyCode::Char('k') => app.menu_cursor_up(5), // 5 menu options
-        KeyCode::Down | KeyCode::Char('j') => app.menu_cursor_down(5),
+        KeyCode::Up | KeyCode::Char('k') => app.menu_cursor_up(6), // 6 menu options
+        KeyCode::Down | KeyCode::Char('j') => app.menu_cursor_down(6),
         KeyCode::Char(' ') | KeyCode::Enter => {
             // Handle menu selection
             match app.menu_cursor {
@@ -814,6 +1060,10 @@ fn handle_lichess_menu_page_events(app: &mut App, key_event: KeyEvent) {
                     app.game.ui.prompt.reset();
                 }
                 4 => {
+                    // Lichess TV (no token needed)
+                    app.start_lichess_tv();
+                }
+                5 => {
                     // Disconnect
                     app.disconnect_lichess();
                 }
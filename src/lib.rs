@@ -45,5 +45,14 @@ pub mod lichess_auth;
 // Auto-Move Controller
 pub mod auto_move;
 
+// Chess engine backends
+pub mod engine;
+
 // Sound effects
 pub mod sound;
+
+// Diagnostic bundles for bug reports
+pub mod diagnostics;
+
+// PGN parsing and simulated playback
+pub mod pgn;
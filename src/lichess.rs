@@ -1,12 +1,28 @@
+use crate::lichess_auth::LichessSession;
 use reqwest::blocking::Client;
-use serde::Deserialize;
-use shakmaty::Color;
+use serde::{Deserialize, Serialize};
+use shakmaty::fen::Fen;
+use shakmaty::{Board, CastlingMode, Chess, Color, FromSetup, PositionError, Setup};
+use std::collections::HashSet;
 use std::error::Error;
 use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
-const LICHESS_API_URL: &str = "https://lichess.org/api";
+pub(crate) const LICHESS_API_URL: &str = "https://lichess.org/api";
+/// Site root, for the endpoints that live outside the API (game exports)
+pub(crate) const LICHESS_URL: &str = "https://lichess.org";
+
+/// Tries at startup before giving up on reaching Lichess
+pub const VALIDATE_ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled after each failed attempt
+pub const VALIDATE_BACKOFF: Duration = Duration::from_millis(500);
+/// Per-request timeout, so a dead connection can't stall startup
+pub(crate) const VALIDATE_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type")]
@@ -56,6 +72,13 @@ pub struct OngoingGame {
     pub opponent: OpponentInfo,
     #[serde(rename = "isMyTurn")]
     pub is_my_turn: bool,
+    #[serde(default)]
+    pub rated: bool,
+    #[serde(default)]
+    pub variant: Option<VariantInfo>,
+    /// "bullet", "blitz", ..., "correspondence"
+    #[serde(default)]
+    pub speed: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -65,9 +88,190 @@ pub struct OpponentInfo {
     pub rating: Option<u32>,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct VariantInfo {
+    pub key: String,
+    pub name: String,
+}
+
+/// The Lichess game last joined, kept on disk so the next launch can offer to resume it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub game_id: String,
+    /// "white" or "black", as in the ongoing games list
+    pub color: String,
+}
+
+impl SavedGame {
+    /// Where the saved game is stored
+    pub fn path() -> Result<PathBuf, Box<dyn Error>> {
+        let data_dir = crate::constants::data_dir()
+            .map_err(|e| format!("Cannot store the current game: {}", e))?;
+        Ok(data_dir.join("current_game.json"))
+    }
+
+    /// The saved game, if there is a readable one
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path().ok()?).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(saved) => Some(saved),
+            Err(e) => {
+                log::warn!("Ignoring unreadable saved game: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn clear() {
+        if let Ok(path) = Self::path() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Index of the saved game in the ongoing games; None once it has ended
+    pub fn find_in(&self, ongoing: &[OngoingGame]) -> Option<usize> {
+        ongoing.iter().position(|game| game.game_id == self.game_id)
+    }
+}
+
+/// What we know about the current game, shown in the info panel. Every field
+/// may be missing (anonymous opponents, partial snapshots), so all are optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameMeta {
+    pub game_id: Option<String>,
+    pub opponent_name: Option<String>,
+    pub opponent_rating: Option<u32>,
+    /// Initial time and increment, in seconds
+    pub clock: Option<(u32, u32)>,
+    pub rated: Option<bool>,
+    pub variant: Option<String>,
+    pub speed: Option<String>,
+}
+
+impl GameMeta {
+    /// Metadata for a game we only know the id of
+    pub fn for_game(game_id: &str) -> Self {
+        GameMeta {
+            game_id: Some(game_id.to_string()),
+            ..GameMeta::default()
+        }
+    }
+
+    /// Metadata from the ongoing games list
+    pub fn from_ongoing(game: &OngoingGame) -> Self {
+        GameMeta {
+            game_id: Some(game.game_id.clone()),
+            opponent_name: Some(game.opponent.username.clone()),
+            opponent_rating: game.opponent.rating,
+            clock: None,
+            rated: Some(game.rated),
+            variant: game.variant.as_ref().map(|v| v.name.clone()),
+            speed: game.speed.clone(),
+        }
+    }
+
+    /// Metadata from a socket snapshot payload (`game`, `clock` and `opponent` objects)
+    pub fn from_socket(d: &serde_json::Value) -> Self {
+        let game = &d["game"];
+        let opponent = &d["opponent"];
+        let clock = &d["clock"];
+        GameMeta {
+            game_id: game["id"].as_str().map(str::to_string),
+            opponent_name: opponent["user"]["username"]
+                .as_str()
+                .or(opponent["username"].as_str())
+                .map(str::to_string),
+            opponent_rating: opponent["rating"].as_u64().map(|r| r as u32),
+            clock: clock["initial"]
+                .as_u64()
+                .zip(clock["increment"].as_u64())
+                .map(|(initial, increment)| (initial as u32, increment as u32)),
+            rated: game["rated"].as_bool(),
+            variant: game["variant"]["name"]
+                .as_str()
+                .or(game["variant"].as_str())
+                .map(str::to_string),
+            speed: game["speed"].as_str().map(str::to_string),
+        }
+    }
+
+    /// Overlay the fields `other` knows about
+    pub fn merge(&mut self, other: GameMeta) {
+        let GameMeta {
+            game_id,
+            opponent_name,
+            opponent_rating,
+            clock,
+            rated,
+            variant,
+            speed,
+        } = other;
+        self.game_id = game_id.or(self.game_id.take());
+        self.opponent_name = opponent_name.or(self.opponent_name.take());
+        self.opponent_rating = opponent_rating.or(self.opponent_rating);
+        self.clock = clock.or(self.clock);
+        self.rated = rated.or(self.rated);
+        self.variant = variant.or(self.variant.take());
+        self.speed = speed.or(self.speed.take());
+    }
+
+    /// "name (rating)", "Anonymous" when the opponent has no account
+    pub fn opponent_label(&self) -> String {
+        let name = self.opponent_name.as_deref().unwrap_or("Anonymous");
+        match self.opponent_rating {
+            Some(rating) => format!("{} ({})", name, rating),
+            None => name.to_string(),
+        }
+    }
+
+    /// Time control as Lichess writes it ("3+2", "½+0"), or the speed if there's no clock
+    pub fn time_control_label(&self) -> String {
+        match (self.clock, self.speed.as_deref()) {
+            (Some((initial, increment)), _) => {
+                let minutes = match initial {
+                    15 => "¼".to_string(),
+                    30 => "½".to_string(),
+                    45 => "¾".to_string(),
+                    90 => "1.5".to_string(),
+                    _ => (initial / 60).to_string(),
+                };
+                format!("{}+{}", minutes, increment)
+            }
+            (None, Some("correspondence")) => "Correspondence".to_string(),
+            (None, _) => "?".to_string(),
+        }
+    }
+
+    /// Lines of the info panel
+    pub fn lines(&self) -> Vec<String> {
+        let rated = match self.rated {
+            Some(true) => "Rated",
+            Some(false) => "Casual",
+            None => "?",
+        };
+        vec![
+            format!("vs {}", self.opponent_label()),
+            format!("{} • {}", self.time_control_label(), rated),
+            self.variant
+                .clone()
+                .unwrap_or_else(|| "Standard".to_string()),
+            format!("#{}", self.game_id.as_deref().unwrap_or("?")),
+        ]
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct OngoingGamesResponse {
-    #[serde(rename = "nowPlaying")]
+    #[serde(rename = "nowPlaying", default)]
     now_playing: Vec<OngoingGame>,
 }
 
@@ -199,6 +403,603 @@ pub struct Perf {
     pub prog: Option<i32>,
 }
 
+/// A frame of the Lichess TV feed (`/api/tv/feed`, ND-JSON)
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(tag = "t", content = "d")]
+pub enum TvFeedFrame {
+    /// A new game became the featured game
+    #[serde(rename = "featured")]
+    Featured(TvFeatured),
+    /// A move was played in the featured game
+    #[serde(rename = "fen")]
+    Fen(TvFen),
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TvFeatured {
+    pub id: String,
+    pub orientation: String,
+    #[serde(default)]
+    pub players: Vec<TvPlayer>,
+    pub fen: String,
+}
+
+impl TvFeatured {
+    /// Human readable "White (rating) vs Black (rating)" label
+    pub fn players_label(&self) -> String {
+        let label = |color: &str| {
+            self.players
+                .iter()
+                .find(|p| p.color == color)
+                .map(|p| {
+                    let name = p
+                        .user
+                        .as_ref()
+                        .map(|u| match &u.title {
+                            Some(title) => format!("{} {}", title, u.name),
+                            None => u.name.clone(),
+                        })
+                        .unwrap_or_else(|| "Anonymous".to_string());
+                    match p.rating {
+                        Some(rating) => format!("{} ({})", name, rating),
+                        None => name,
+                    }
+                })
+                .unwrap_or_else(|| "?".to_string())
+        };
+        format!("{} vs {}", label("white"), label("black"))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TvPlayer {
+    pub color: String,
+    #[serde(default)]
+    pub user: Option<TvUser>,
+    #[serde(default)]
+    pub rating: Option<u32>,
+    #[serde(default)]
+    pub seconds: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TvUser {
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TvFen {
+    pub fen: String,
+    /// Last move in UCI notation
+    #[serde(default)]
+    pub lm: Option<String>,
+    /// White clock in seconds
+    #[serde(default)]
+    pub wc: Option<u32>,
+    /// Black clock in seconds
+    #[serde(default)]
+    pub bc: Option<u32>,
+}
+
+/// Build a position from a TV feed FEN.
+/// The feed only sends the board part, so the side to move is deduced from the
+/// piece that made the last move (white to move when there is none).
+pub fn tv_feed_position(fen: &str, last_move: Option<&str>) -> Option<Chess> {
+    // Full FENs carry everything we need
+    if fen.contains(' ') {
+        return Fen::from_ascii(fen.as_bytes())
+            .ok()?
+            .into_position(CastlingMode::Standard)
+            .ok();
+    }
+
+    let board = Board::from_ascii_board_fen(fen.as_bytes()).ok()?;
+    let turn = last_move
+        .and_then(|lm| crate::utils::parse_uci(lm).ok())
+        .and_then(|lm| board.color_at(lm.to))
+        .map(|mover| mover.other())
+        .unwrap_or(Color::White);
+
+    let mut setup = Setup::empty();
+    setup.board = board;
+    setup.turn = turn;
+    Chess::from_setup(setup, CastlingMode::Standard)
+        .or_else(PositionError::ignore_impossible_check)
+        .ok()
+}
+
+/// Why a token couldn't be validated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Lichess rejected the token (401): a new one is needed
+    Unauthorized,
+    /// The account is closed or was disabled for a TOS violation (403): a new token won't help
+    AccountClosed,
+    /// Too many requests (429): Lichess asks clients to wait a minute
+    RateLimited,
+    /// Nothing there (404), e.g. a challenge withdrawn before we answered it
+    NotFound,
+    /// Network blip or server error: worth retrying
+    Transient(String),
+}
+
+impl ValidationError {
+    /// Classify a failed response from the account endpoint
+    pub fn from_status(status: reqwest::StatusCode) -> Self {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED => ValidationError::Unauthorized,
+            reqwest::StatusCode::FORBIDDEN => ValidationError::AccountClosed,
+            reqwest::StatusCode::TOO_MANY_REQUESTS => ValidationError::RateLimited,
+            reqwest::StatusCode::NOT_FOUND => ValidationError::NotFound,
+            status => ValidationError::Transient(status.to_string()),
+        }
+    }
+
+    /// Whether logging in again with a new token can fix it
+    pub fn needs_relogin(&self) -> bool {
+        matches!(self, ValidationError::Unauthorized)
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidationError::Unauthorized => write!(
+                f,
+                "Your Lichess token has expired or been revoked, please re-login"
+            ),
+            ValidationError::AccountClosed => write!(
+                f,
+                "This Lichess account is closed or disabled, logging in again won't help"
+            ),
+            ValidationError::RateLimited => {
+                write!(f, "Lichess is rate limiting requests, try again later")
+            }
+            ValidationError::NotFound => write!(f, "No longer available on Lichess"),
+            ValidationError::Transient(e) => write!(f, "Could not reach Lichess: {}", e),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Run `validate` up to `attempts` times, sleeping `backoff` (doubled each time)
+/// between transient failures. Anything else (401, 403, 429) is definitive and
+/// returned straight away: retrying quickly would only dig a rate limit deeper.
+pub fn validate_with_retry<T>(
+    attempts: u32,
+    backoff: Duration,
+    mut validate: impl FnMut() -> Result<T, ValidationError>,
+) -> Result<T, ValidationError> {
+    let mut delay = backoff;
+    let mut attempt = 1;
+    loop {
+        match validate() {
+            Err(ValidationError::Transient(e)) if attempt < attempts => {
+                log::warn!(
+                    "Token validation attempt {}/{} failed: {}, retrying in {:?}",
+                    attempt,
+                    attempts,
+                    e,
+                    delay
+                );
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Request to the Lichess API authenticated with the session's token or cookie,
+/// timing out after `timeout` (never if None, for streams)
+fn session_request(
+    session: &LichessSession,
+    method: reqwest::Method,
+    url: &str,
+    timeout: Option<Duration>,
+) -> Result<reqwest::blocking::RequestBuilder, ValidationError> {
+    let client = Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    let (name, value) = session.auth_header();
+    Ok(client
+        .request(method, url)
+        .header(
+            "User-Agent",
+            "chess-tui (https://github.com/thomas-mauran/chess-tui)",
+        )
+        .header(name, value))
+}
+
+/// Games the session's account is playing, to pick one to connect the socket to.
+/// A 401 comes back as `ValidationError::Unauthorized`, asking for a new login.
+pub fn ongoing_games(session: &LichessSession) -> Result<Vec<OngoingGame>, ValidationError> {
+    ongoing_games_at(LICHESS_API_URL, session)
+}
+
+/// Like `ongoing_games`, against another API root (a mirror or a mock server)
+pub fn ongoing_games_at(
+    api_url: &str,
+    session: &LichessSession,
+) -> Result<Vec<OngoingGame>, ValidationError> {
+    let url = format!("{}/account/playing", api_url);
+    let response = session_request(session, reqwest::Method::GET, &url, Some(VALIDATE_TIMEOUT))?
+        .send()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(ValidationError::from_status(response.status()));
+    }
+    let games: OngoingGamesResponse = response
+        .json()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    log::info!("Found {} ongoing games", games.now_playing.len());
+    Ok(games.now_playing)
+}
+
+/// What an exported PGN carries besides the moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PgnExportOptions {
+    /// `[%clk]` annotations after every move
+    pub clocks: bool,
+    /// `[%eval]` annotations, for games Lichess has analysed
+    pub evals: bool,
+}
+
+impl Default for PgnExportOptions {
+    fn default() -> Self {
+        PgnExportOptions {
+            clocks: true,
+            evals: false,
+        }
+    }
+}
+
+/// Download a game's PGN, e.g. to save or review it once it's over.
+/// A game still going on comes back with the moves so far and a `*` result.
+pub fn export_pgn(
+    session: &LichessSession,
+    game_id: &str,
+    options: PgnExportOptions,
+) -> Result<String, ValidationError> {
+    export_pgn_at(LICHESS_URL, session, game_id, options)
+}
+
+/// Like `export_pgn`, against another site root (a mirror or a mock server)
+pub fn export_pgn_at(
+    site_url: &str,
+    session: &LichessSession,
+    game_id: &str,
+    options: PgnExportOptions,
+) -> Result<String, ValidationError> {
+    let url = format!(
+        "{}/game/export/{}.pgn?pgnInJson=false&clocks={}&evals={}",
+        site_url, game_id, options.clocks, options.evals
+    );
+    let response = session_request(session, reqwest::Method::GET, &url, Some(VALIDATE_TIMEOUT))?
+        .header("Accept", "application/x-chess-pgn")
+        .send()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(ValidationError::from_status(response.status()));
+    }
+    let pgn = response
+        .text()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    if pgn.trim().is_empty() {
+        return Err(ValidationError::NotFound);
+    }
+    Ok(pgn)
+}
+
+/// A challenge someone sent us
+#[derive(Debug, Clone, Deserialize)]
+pub struct Challenge {
+    pub id: String,
+    pub challenger: ChallengeUser,
+    #[serde(default)]
+    pub rated: bool,
+    /// "bullet", "blitz", ..., "correspondence"
+    #[serde(default)]
+    pub speed: Option<String>,
+    #[serde(rename = "timeControl")]
+    pub time_control: ChallengeTimeControl,
+    #[serde(default)]
+    pub variant: Option<VariantInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChallengeUser {
+    pub name: String,
+    #[serde(default)]
+    pub rating: Option<u32>,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChallengeTimeControl {
+    /// "clock", "correspondence" or "unlimited"
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Initial time in seconds, for clock games
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Increment in seconds, for clock games
+    #[serde(default)]
+    pub increment: Option<u32>,
+}
+
+impl Challenge {
+    /// Game details shown before accepting, in the same shape as a joined game's
+    pub fn meta(&self) -> GameMeta {
+        let opponent_name = match &self.challenger.title {
+            Some(title) => format!("{} {}", title, self.challenger.name),
+            None => self.challenger.name.clone(),
+        };
+        GameMeta {
+            game_id: Some(self.id.clone()),
+            opponent_name: Some(opponent_name),
+            opponent_rating: self.challenger.rating,
+            clock: self.time_control.limit.zip(self.time_control.increment),
+            rated: Some(self.rated),
+            variant: self.variant.as_ref().map(|variant| variant.name.clone()),
+            speed: self.speed.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChallengesResponse {
+    #[serde(rename = "in", default)]
+    incoming: Vec<Challenge>,
+}
+
+/// Challenges waiting for the session account to answer
+pub fn list_challenges(session: &LichessSession) -> Result<Vec<Challenge>, ValidationError> {
+    list_challenges_at(LICHESS_API_URL, session)
+}
+
+/// Like `list_challenges`, against another API root (a mirror or a mock server)
+pub fn list_challenges_at(
+    api_url: &str,
+    session: &LichessSession,
+) -> Result<Vec<Challenge>, ValidationError> {
+    let url = format!("{}/challenge", api_url);
+    let response = session_request(session, reqwest::Method::GET, &url, Some(VALIDATE_TIMEOUT))?
+        .send()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(ValidationError::from_status(response.status()));
+    }
+    let challenges: ChallengesResponse = response
+        .json()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    Ok(challenges.incoming)
+}
+
+/// Accept a challenge; the game then starts under the challenge's id.
+/// `ValidationError::NotFound` means it was withdrawn first.
+pub fn accept_challenge(
+    session: &LichessSession,
+    challenge_id: &str,
+) -> Result<(), ValidationError> {
+    answer_challenge_at(LICHESS_API_URL, session, challenge_id, "accept")
+}
+
+/// Decline a challenge
+pub fn decline_challenge(
+    session: &LichessSession,
+    challenge_id: &str,
+) -> Result<(), ValidationError> {
+    answer_challenge_at(LICHESS_API_URL, session, challenge_id, "decline")
+}
+
+/// Post "accept" or "decline" for a challenge, against the given API root
+pub fn answer_challenge_at(
+    api_url: &str,
+    session: &LichessSession,
+    challenge_id: &str,
+    answer: &str,
+) -> Result<(), ValidationError> {
+    let url = format!("{}/challenge/{}/{}", api_url, challenge_id, answer);
+    let response = session_request(session, reqwest::Method::POST, &url, Some(VALIDATE_TIMEOUT))?
+        .send()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(ValidationError::from_status(response.status()));
+    }
+    log::info!("Challenge {}: {}", challenge_id, answer);
+    Ok(())
+}
+
+/// An event from the account's incoming event stream
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum LichessEvent {
+    /// A game of ours started: connect the socket to it
+    #[serde(rename = "gameStart")]
+    GameStart { game: OngoingGame },
+    #[serde(rename = "gameFinish")]
+    GameFinish { game: OngoingGame },
+    /// Someone challenged us
+    #[serde(rename = "challenge")]
+    Challenge { challenge: Challenge },
+    /// A challenge to us was withdrawn
+    #[serde(rename = "challengeCanceled")]
+    ChallengeCanceled { challenge: Challenge },
+}
+
+/// Follow the account's event stream (`/api/stream/event`) on a background thread.
+/// Event types we don't know are skipped. Once the receiver is dropped the thread
+/// hangs up on the next event it fails to hand over.
+pub fn stream_events(session: &LichessSession) -> Result<Receiver<LichessEvent>, ValidationError> {
+    stream_events_at(LICHESS_API_URL, session)
+}
+
+/// Like `stream_events`, against another API root (a mirror or a mock server)
+pub fn stream_events_at(
+    api_url: &str,
+    session: &LichessSession,
+) -> Result<Receiver<LichessEvent>, ValidationError> {
+    let url = format!("{}/stream/event", api_url);
+    let response = session_request(session, reqwest::Method::GET, &url, None)?
+        .send()
+        .map_err(|e| ValidationError::Transient(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(ValidationError::from_status(response.status()));
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(response).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                // Keep-alive
+                continue;
+            }
+            match serde_json::from_str::<LichessEvent>(&line) {
+                Ok(event) => {
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => log::debug!("Skipping event {}: {}", line, e),
+            }
+        }
+        log::info!("Lichess event stream closed");
+    });
+    Ok(rx)
+}
+
+/// Longest a seek waits for an opponent unless told otherwise
+pub const SEEK_TIMEOUT: Duration = Duration::from_secs(300);
+/// How often a running seek checks the ongoing games for the match
+pub const SEEK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A game to seek on Lichess
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeekParams {
+    /// Minutes per side (0 with no increment seeks a correspondence game)
+    pub time: u32,
+    /// Seconds added per move
+    pub increment: u32,
+    pub rated: bool,
+    /// Side to play, random if None
+    pub color: Option<Color>,
+    /// Give up when no opponent is found this quickly
+    pub timeout: Duration,
+}
+
+impl SeekParams {
+    /// Casual seek for `time`+`increment` with a random color and the default timeout
+    pub fn new(time: u32, increment: u32) -> Self {
+        SeekParams {
+            time,
+            increment,
+            rated: false,
+            color: None,
+            timeout: SEEK_TIMEOUT,
+        }
+    }
+}
+
+/// Seek a game and return its id once an opponent accepts, to connect the socket to.
+/// Setting `cancel` withdraws the seek by dropping the connection; Lichess sends a
+/// keep-alive every few seconds, so that happens at the next one.
+pub fn create_seek(
+    session: &LichessSession,
+    params: &SeekParams,
+    cancel: Arc<AtomicBool>,
+) -> Result<String, Box<dyn Error>> {
+    create_seek_at(LICHESS_API_URL, session, params, cancel)
+}
+
+/// Like `create_seek`, against another API root (a mirror or a mock server)
+pub fn create_seek_at(
+    api_url: &str,
+    session: &LichessSession,
+    params: &SeekParams,
+    cancel: Arc<AtomicBool>,
+) -> Result<String, Box<dyn Error>> {
+    // The seek stream doesn't name the game, it shows up among the ongoing ones
+    let known: HashSet<String> = ongoing_games_at(api_url, session)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|game| game.game_id)
+        .collect();
+    let mut body = if params.time == 0 && params.increment == 0 {
+        serde_json::json!({ "days": 3 })
+    } else {
+        serde_json::json!({ "time": params.time, "increment": params.increment })
+    };
+    body["rated"] = params.rated.into();
+    body["color"] = match params.color {
+        Some(Color::White) => "white",
+        Some(Color::Black) => "black",
+        None => "random",
+    }
+    .into();
+
+    let url = format!("{}/board/seek", api_url);
+    let response = session_request(session, reqwest::Method::POST, &url, Some(params.timeout))?
+        .json(&body)
+        .send()?;
+    if !response.status().is_success() {
+        return Err(Box::new(ValidationError::from_status(response.status())));
+    }
+    log::info!("Seeking a {}+{} game", params.time, params.increment);
+
+    // Hold the seek open on its own thread until it ends or we let go of it
+    let stop = Arc::new(AtomicBool::new(false));
+    let (closed_tx, closed_rx) = std::sync::mpsc::channel();
+    let stream_stop = stop.clone();
+    thread::spawn(move || {
+        for line in BufReader::new(response).lines() {
+            if line.is_err() || stream_stop.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+        let _ = closed_tx.send(());
+    });
+
+    let deadline = std::time::Instant::now() + params.timeout;
+    let mut polls_after_close = 0;
+    let result = loop {
+        if cancel.load(Ordering::Relaxed) {
+            break Err("Seek cancelled".into());
+        }
+        let new_game = ongoing_games_at(api_url, session).ok().and_then(|games| {
+            games
+                .into_iter()
+                .find(|game| !known.contains(&game.game_id))
+        });
+        if let Some(game) = new_game {
+            log::info!("Seek matched: {}", game.game_id);
+            break Ok(game.game_id);
+        }
+        // Lichess closes the seek when it's matched; give the game a few polls to show up
+        if closed_rx.try_recv().is_ok() || polls_after_close > 0 {
+            polls_after_close += 1;
+            if polls_after_close > 3 {
+                break Err("The seek ended without a game".into());
+            }
+        }
+        if std::time::Instant::now() >= deadline {
+            break Err("No opponent found in time".into());
+        }
+        thread::sleep(SEEK_POLL_INTERVAL);
+    };
+    stop.store(true, Ordering::Relaxed);
+    result
+}
+
 #[derive(Clone)]
 pub struct LichessClient {
     token: String,
@@ -237,6 +1038,30 @@ impl LichessClient {
         player.id.ok_or("Profile missing ID".into())
     }
 
+    /// Check the token against the account endpoint, telling a rejected token
+    /// apart from a failure to reach Lichess
+    pub fn validate(&self) -> Result<UserProfile, ValidationError> {
+        let url = format!("{}/account", LICHESS_API_URL);
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "User-Agent",
+                "chess-tui (https://github.com/thomas-mauran/chess-tui)",
+            )
+            .bearer_auth(&self.token)
+            .timeout(VALIDATE_TIMEOUT)
+            .send()
+            .map_err(|e| ValidationError::Transient(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ValidationError::from_status(response.status()));
+        }
+        response
+            .json()
+            .map_err(|e| ValidationError::Transient(e.to_string()))
+    }
+
     pub fn get_user_profile(&self) -> Result<UserProfile, Box<dyn Error>> {
         let url = format!("{}/account", LICHESS_API_URL);
         log::info!("Fetching user profile from: {}", url);
@@ -1090,11 +1915,19 @@ impl LichessClient {
                 // Check if we received a signal that the player made a move
                 // This ensures we poll immediately to check for opponent's response
                 if let Some(ref rx) = player_move_rx {
-                    if rx.try_recv().is_ok() {
-                        log::debug!(
-                            "Player made a move, will poll to check for opponent's response"
-                        );
-                        // Continue to poll immediately to check for opponent's response
+                    match rx.try_recv() {
+                        Ok(()) => {
+                            log::debug!(
+                                "Player made a move, will poll to check for opponent's response"
+                            );
+                            // Continue to poll immediately to check for opponent's response
+                        }
+                        Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                            // The app dropped the opponent, so nobody is listening anymore
+                            log::info!("Game {} was left, stopping poll", game_id);
+                            break;
+                        }
+                        Err(std::sync::mpsc::TryRecvError::Empty) => {}
                     }
                 }
 
@@ -1415,6 +2248,55 @@ impl LichessClient {
         Ok(())
     }
 
+    /// Stream the Lichess TV featured game feed. No authentication is needed.
+    /// Frames are forwarded until the receiver is dropped.
+    pub fn stream_tv_feed(frame_tx: Sender<TvFeedFrame>) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/tv/feed", LICHESS_API_URL);
+        // The feed is long-lived, so no read timeout
+        let client = Client::builder().timeout(None).build()?;
+        let response = client
+            .get(&url)
+            .header(
+                "User-Agent",
+                "chess-tui (https://github.com/thomas-mauran/chess-tui)",
+            )
+            .send()?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to open TV feed: {}", response.status()).into());
+        }
+
+        thread::spawn(move || {
+            let reader = BufReader::new(response);
+            for line in reader.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(e) => {
+                        log::error!("TV feed read error: {}", e);
+                        break;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<TvFeedFrame>(&line) {
+                    Ok(frame) => {
+                        if frame_tx.send(frame).is_err() {
+                            // Receiver dropped, user left TV mode
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to parse TV feed line: {} - {}", line, e);
+                    }
+                }
+            }
+            log::info!("TV feed closed");
+        });
+
+        Ok(())
+    }
+
     pub fn make_move(&self, game_id: &str, move_str: &str) -> Result<(), Box<dyn Error>> {
         let url = format!(
             "{}/board/game/{}/move/{}",
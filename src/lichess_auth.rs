@@ -1,32 +1,189 @@
 use log::{error, info, warn};
+use rand::Rng;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
 use std::path::PathBuf;
+use std::process::Command;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Account profile stored in `lichess_session.json`, as before profiles existed
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Keyring service the session secrets are stored under
+pub const KEYRING_SERVICE: &str = "chess-tui";
+
+/// Where session credentials are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthBackend {
+    /// Everything in the session file
+    #[default]
+    File,
+    /// Secrets in the OS keyring, only the username in the session file
+    Keyring,
+}
+
+impl std::fmt::Display for AuthBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AuthBackend::File => write!(f, "file"),
+            AuthBackend::Keyring => write!(f, "keyring"),
+        }
+    }
+}
+
+impl std::str::FromStr for AuthBackend {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "file" => Ok(AuthBackend::File),
+            "keyring" => Ok(AuthBackend::Keyring),
+            other => Err(format!("Unknown auth backend: {}", other)),
+        }
+    }
+}
+
+static AUTH_BACKEND: RwLock<AuthBackend> = RwLock::new(AuthBackend::File);
+
+/// Choose where `save` puts credentials from now on
+pub fn set_auth_backend(backend: AuthBackend) {
+    if let Ok(mut current) = AUTH_BACKEND.write() {
+        *current = backend;
+    }
+}
+
+/// Where `save` puts credentials
+pub fn auth_backend() -> AuthBackend {
+    AUTH_BACKEND
+        .read()
+        .map(|backend| *backend)
+        .unwrap_or_default()
+}
+
+/// Credentials moved to the keyring, as one JSON secret
+#[derive(Serialize, Deserialize)]
+struct KeyringSecrets {
+    session_id: String,
+    csrf_token: Option<String>,
+    token: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LichessSession {
+    /// The `lila2` session cookie, empty for token sessions
     pub session_id: String,
     pub csrf_token: Option<String>,
     pub username: Option<String>,
+    /// Personal access token, used instead of the cookie when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Account profile the session is stored under (the default one if None)
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// When the token stops working, in seconds since the Unix epoch (never if None)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Keyring account holding the secrets, when they're not in the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keyring_account: Option<String>,
 }
 
 impl LichessSession {
     /// Get the path for session storage
     pub fn session_path() -> Result<PathBuf, Box<dyn Error>> {
-        let config_dir = crate::constants::config_dir()?;
-        let session_path = config_dir.join("chess-tui/lichess_session.json");
-        Ok(session_path)
+        Self::session_path_for(DEFAULT_PROFILE)
+    }
+    
+    /// Get the path for a named profile's session: `lichess_session_<profile>.json`,
+    /// or `lichess_session.json` for the default profile
+    pub fn session_path_for(profile: &str) -> Result<PathBuf, Box<dyn Error>> {
+        if profile.is_empty()
+            || !profile
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid profile name '{}': use letters, digits, '-' and '_'",
+                profile
+            )
+            .into());
+        }
+        let data_dir = crate::constants::data_dir()
+            .map_err(|e| format!("Cannot store the Lichess session: {}", e))?;
+        if profile == DEFAULT_PROFILE {
+            Ok(data_dir.join("lichess_session.json"))
+        } else {
+            Ok(data_dir.join(format!("lichess_session_{}.json", profile)))
+        }
+    }
+    
+    /// Profiles with a stored session, sorted, the default one as DEFAULT_PROFILE
+    pub fn list_profiles() -> Vec<String> {
+        let Ok(data_dir) = crate::constants::data_dir() else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(data_dir) else {
+            return Vec::new();
+        };
+        let mut profiles: Vec<String> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let stem = name.strip_suffix(".json")?;
+                match stem.strip_prefix("lichess_session") {
+                    Some("") => Some(DEFAULT_PROFILE.to_string()),
+                    Some(rest) => rest.strip_prefix('_').map(str::to_string),
+                    None => None,
+                }
+            })
+            .collect();
+        profiles.sort();
+        profiles.dedup();
+        profiles
     }
     
     /// Load existing session or return error prompting login
     pub fn load_or_create() -> Result<Self, Box<dyn Error>> {
-        let session_path = Self::session_path()?;
+        Self::load_or_create_profile(DEFAULT_PROFILE)
+    }
+    
+    /// Load a named profile's session or return error prompting login
+    pub fn load_or_create_profile(profile: &str) -> Result<Self, Box<dyn Error>> {
+        let session_path = Self::session_path_for(profile)?;
         
         if session_path.exists() {
             let content = fs::read_to_string(&session_path)?;
-            let session: LichessSession = serde_json::from_str(&content)?;
-            info!("[LichessAuth] Loaded session for user: {:?}", session.username);
+            let mut session: LichessSession = serde_json::from_str(&content)?;
+            session.profile = (profile != DEFAULT_PROFILE).then(|| profile.to_string());
+            if let Some(account) = session.keyring_account.clone() {
+                let secret = keyring::Entry::new(KEYRING_SERVICE, &account)
+                    .and_then(|entry| entry.get_password())
+                    .map_err(|e| format!("Cannot read the session from the keyring: {}", e))?;
+                let secrets: KeyringSecrets = serde_json::from_str(&secret)?;
+                session.session_id = secrets.session_id;
+                session.csrf_token = secrets.csrf_token;
+                session.token = secrets.token;
+            }
+            if session.is_expired() {
+                warn!(
+                    "[LichessAuth] Session for {:?} has expired",
+                    session.username
+                );
+                return Err(
+                    "Lichess session expired. Please log in again with --lichess-login.".into(),
+                );
+            }
+            info!(
+                "[LichessAuth] Loaded session for user: {:?} (profile {})",
+                session.username, profile
+            );
             Ok(session)
         } else {
             error!("[LichessAuth] No session found. Please run with --lichess-login to authenticate.");
@@ -34,43 +191,150 @@ impl LichessSession {
         }
     }
     
-    /// Save the session to disk
+    /// Check if the token's expiry time has passed
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| unix_now() >= expires_at)
+    }
+    
+    /// Profile the session is stored under
+    pub fn profile(&self) -> &str {
+        self.profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+    
+    /// Save the session to disk, under its profile
     pub fn save(&self) -> Result<(), Box<dyn Error>> {
-        let session_path = Self::session_path()?;
+        let session_path = Self::session_path_for(self.profile())?;
         
         // Ensure directory exists
         if let Some(parent) = session_path.parent() {
             fs::create_dir_all(parent)?;
         }
         
-        let content = serde_json::to_string_pretty(self)?;
+        let content = match auth_backend() {
+            AuthBackend::Keyring => match self.save_to_keyring() {
+                Ok(stub) => serde_json::to_string_pretty(&stub)?,
+                Err(e) => {
+                    warn!(
+                        "[LichessAuth] Keyring unavailable, saving the session to a file: {}",
+                        e
+                    );
+                    serde_json::to_string_pretty(self)?
+                }
+            },
+            AuthBackend::File => serde_json::to_string_pretty(self)?,
+        };
         fs::write(&session_path, content)?;
         
         info!("[LichessAuth] Session saved for user: {:?}", self.username);
         Ok(())
     }
     
-    /// Validate the session by checking against Lichess API
-    pub fn validate(&self) -> Result<bool, Box<dyn Error>> {
-        // For now, we'll assume session is valid if it exists
-        // In a full implementation, we'd make an API call to /api/account
-        // using the session cookie to verify it's still valid
-        warn!("[LichessAuth] Session validation not fully implemented - assuming valid");
+    /// Store the secrets in the keyring, returning what's left for the session file
+    fn save_to_keyring(&self) -> Result<LichessSession, Box<dyn Error>> {
+        let account = self
+            .username
+            .clone()
+            .unwrap_or_else(|| self.profile().to_string());
+        let secrets = KeyringSecrets {
+            session_id: self.session_id.clone(),
+            csrf_token: self.csrf_token.clone(),
+            token: self.token.clone(),
+        };
+        keyring::Entry::new(KEYRING_SERVICE, &account)?
+            .set_password(&serde_json::to_string(&secrets)?)?;
+        Ok(LichessSession {
+            session_id: String::new(),
+            csrf_token: None,
+            username: self.username.clone(),
+            token: None,
+            profile: self.profile.clone(),
+            expires_at: self.expires_at,
+            keyring_account: Some(account),
+        })
+    }
+    
+    /// Delete the stored session, if there is one
+    pub fn logout() -> Result<(), Box<dyn Error>> {
+        Self::logout_profile(DEFAULT_PROFILE)
+    }
+    
+    /// Delete a named profile's stored session, if there is one
+    pub fn logout_profile(profile: &str) -> Result<(), Box<dyn Error>> {
+        let session_path = Self::session_path_for(profile)?;
+        let keyring_account = fs::read_to_string(&session_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<LichessSession>(&content).ok())
+            .and_then(|session| session.keyring_account.clone());
+        if let Some(account) = keyring_account {
+            let cleared = keyring::Entry::new(KEYRING_SERVICE, &account)
+                .and_then(|entry| entry.delete_credential());
+            if let Err(e) = cleared {
+                warn!(
+                    "[LichessAuth] Failed to remove the session from the keyring: {}",
+                    e
+                );
+            }
+        }
+        match fs::remove_file(&session_path) {
+            Ok(()) => {
+                info!("[LichessAuth] Session removed: {}", session_path.display());
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    /// Validate the session against the Lichess account endpoint, filling in the
+    /// username. Ok(false) means Lichess rejected the cookie or token and a new login is needed;
+    /// an error means Lichess couldn't be asked.
+    pub fn validate(&mut self) -> Result<bool, Box<dyn Error>> {
+        let url = format!("{}/account", crate::lichess::LICHESS_API_URL);
+        self.validate_against(&url)
+    }
+    
+    /// Like `validate`, against another account endpoint (a mirror or a mock server).
+    /// Expired sessions are rejected without asking.
+    pub fn validate_against(&mut self, account_url: &str) -> Result<bool, Box<dyn Error>> {
+        // Lichess issues no refresh tokens, so an expired one needs a new login
+        if self.is_expired() {
+            warn!("[LichessAuth] Session expired, please log in again");
+            return Ok(false);
+        }
+        let client = Client::builder()
+            .timeout(crate::lichess::VALIDATE_TIMEOUT)
+            .build()?;
+        let (name, value) = self.auth_header();
+        let mut request = client
+            .get(account_url)
+            .header(
+                "User-Agent",
+                "chess-tui (https://github.com/thomas-mauran/chess-tui)",
+            )
+            .header(name, value);
+        if let (None, Some(csrf)) = (&self.token, &self.csrf_token) {
+            request = request.header("X-CSRF-Token", csrf);
+        }
+        let response = request.send()?;
+        
+        if response.status() == StatusCode::UNAUTHORIZED {
+            warn!("[LichessAuth] Session rejected by Lichess, please log in again");
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Failed to validate session: {}", response.status()).into());
+        }
+        
+        let profile: crate::lichess::UserProfile = response.json()?;
+        info!("[LichessAuth] Session valid for user: {}", profile.username);
+        self.username = Some(profile.username);
         Ok(true)
     }
     
     /// Generate a Socket Request ID (12-char alphanumeric)
     pub fn generate_sri() -> String {
-        use rand::Rng;
-        const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-        let mut rng = rand::thread_rng();
-        
-        (0..12)
-            .map(|_| {
-                let idx = rng.gen_range(0..CHARSET.len());
-                CHARSET[idx] as char
-            })
-            .collect()
+        crate::lichess_ws::LichessWebSocket::generate_sri()
     }
     
     /// Create a new session (called after browser login)
@@ -79,6 +343,255 @@ impl LichessSession {
             session_id,
             csrf_token,
             username,
+            token: None,
+            profile: None,
+            expires_at: None,
+            keyring_account: None,
+        }
+    }
+    
+    /// Create a session from a personal access token instead of a browser cookie
+    pub fn from_token(token: String) -> Self {
+        LichessSession {
+            session_id: String::new(),
+            csrf_token: None,
+            username: None,
+            token: Some(token),
+            profile: None,
+            expires_at: None,
+            keyring_account: None,
+        }
+    }
+    
+    /// Header authenticating requests: the bearer token if there is one, else the cookie
+    pub fn auth_header(&self) -> (&'static str, String) {
+        match &self.token {
+            Some(token) => ("Authorization", format!("Bearer {}", token)),
+            None => ("Cookie", format!("lila2={}", self.session_id)),
+        }
+    }
+}
+
+impl Drop for LichessSession {
+    /// Overwrite the credentials so they don't linger in freed memory
+    fn drop(&mut self) {
+        wipe(&mut self.session_id);
+        if let Some(token) = self.token.as_mut() {
+            wipe(token);
+        }
+    }
+}
+
+fn wipe(secret: &mut String) {
+    let mut bytes = std::mem::take(secret).into_bytes();
+    bytes.fill(0);
+    std::hint::black_box(&bytes);
+}
+
+/// Lichess OAuth endpoints and the client id sent with them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthEndpoints {
+    pub authorize_url: String,
+    pub token_url: String,
+    pub client_id: String,
+}
+
+impl Default for OAuthEndpoints {
+    fn default() -> Self {
+        OAuthEndpoints {
+            authorize_url: "https://lichess.org/oauth".to_string(),
+            token_url: format!("{}/token", crate::lichess::LICHESS_API_URL),
+            client_id: "chess-tui".to_string(),
+        }
+    }
+}
+
+/// Scopes requested at login, enough to play games through the Board API
+pub const OAUTH_SCOPES: &str = "board:play challenge:read challenge:write";
+/// How long to wait for the browser to come back before giving up
+pub const OAUTH_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Token endpoint answer
+#[derive(Deserialize)]
+struct OAuthToken {
+    access_token: String,
+    /// Seconds the token is valid for
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+/// Log in through the browser with the Lichess OAuth PKCE flow and save the
+/// resulting token session under `profile`
+pub fn oauth_login(profile: &str) -> Result<LichessSession, Box<dyn Error>> {
+    let mut session = oauth_login_with(&OAuthEndpoints::default(), OAUTH_TIMEOUT, |url| {
+        println!(
+            "Opening Lichess in your browser. If it doesn't open, visit:\n{}",
+            url
+        );
+        if let Err(e) = open_browser(url) {
+            warn!("[LichessAuth] Failed to open the browser: {}", e);
+        }
+    })?;
+    session.profile = (profile != DEFAULT_PROFILE).then(|| profile.to_string());
+    if let Err(e) = session.validate() {
+        warn!("[LichessAuth] Couldn't look up the account name: {}", e);
+    }
+    session.save()?;
+    Ok(session)
+}
+
+/// Run the PKCE flow against `endpoints`: listen for the redirect on a free
+/// localhost port, hand the authorize URL to `open`, then trade the code for a token.
+/// Nothing is saved.
+pub fn oauth_login_with(
+    endpoints: &OAuthEndpoints,
+    timeout: Duration,
+    open: impl FnOnce(&str),
+) -> Result<LichessSession, Box<dyn Error>> {
+    // Port 0 lets the OS pick a free port, so a busy one can't block the login
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .map_err(|e| format!("Cannot listen for the login redirect: {}", e))?;
+    let redirect_uri = format!(
+        "http://127.0.0.1:{}/callback",
+        listener.local_addr()?.port()
+    );
+    let verifier = random_string(64);
+    let state = random_string(16);
+
+    let mut authorize_url = Url::parse(&endpoints.authorize_url)?;
+    authorize_url
+        .query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &endpoints.client_id)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("code_challenge", &pkce_challenge(&verifier))
+        .append_pair("scope", OAUTH_SCOPES)
+        .append_pair("state", &state);
+    open(authorize_url.as_str());
+
+    let code = wait_for_code(&listener, &state, timeout)?;
+    let response = Client::builder()
+        .timeout(crate::lichess::VALIDATE_TIMEOUT)
+        .build()?
+        .post(&endpoints.token_url)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("code_verifier", verifier.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("client_id", endpoints.client_id.as_str()),
+        ])
+        .send()?;
+    if !response.status().is_success() {
+        return Err(format!("Lichess refused the login: {}", response.status()).into());
+    }
+    let token: OAuthToken = response.json()?;
+    info!("[LichessAuth] Logged in through OAuth");
+    let mut session = LichessSession::from_token(token.access_token);
+    session.expires_at = token.expires_in.map(|expires_in| unix_now() + expires_in);
+    Ok(session)
+}
+
+/// Accept redirects until one carries our state, answering the browser either way
+fn wait_for_code(
+    listener: &TcpListener,
+    state: &str,
+    timeout: Duration,
+) -> Result<String, Box<dyn Error>> {
+    let deadline = Instant::now() + timeout;
+    listener.set_nonblocking(true)?;
+    loop {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err("Timed out waiting for the Lichess login".into());
+                }
+                std::thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut request_line = String::new();
+        BufReader::new(&stream).read_line(&mut request_line)?;
+        let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let url = Url::parse("http://127.0.0.1")?.join(target)?;
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        if url.path() != "/callback" || param("state").as_deref() != Some(state) {
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+            continue;
+        }
+        let (message, result) = match (param("code"), param("error")) {
+            (Some(code), _) => ("Logged in, you can close this tab.", Ok(code)),
+            (None, error) => (
+                "Login cancelled, you can close this tab.",
+                Err(format!(
+                    "Lichess login failed: {}",
+                    error.unwrap_or_else(|| "no code".to_string())
+                )),
+            ),
+        };
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            message.len(),
+            message
+        );
+        return result.map_err(Into::into);
+    }
+}
+
+/// Seconds since the Unix epoch
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+/// Open `url` in the default browser
+pub fn open_browser(url: &str) -> std::io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = Command::new("xdg-open");
+    command.arg(url).spawn().map(|_| ())
+}
+
+/// Random string from the PKCE verifier alphabet
+fn random_string(len: usize) -> String {
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+/// S256 code challenge for a PKCE verifier: unpadded base64url of its SHA-256
+pub fn pkce_challenge(verifier: &str) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let digest = Sha256::digest(verifier.as_bytes());
+    let mut encoded = String::new();
+    for chunk in digest.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
         }
     }
+    encoded
 }
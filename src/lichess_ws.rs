@@ -1,12 +1,157 @@
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::protocol::CloseFrame;
 use tungstenite::{connect, Message, WebSocket};
 use tungstenite::stream::MaybeTlsStream;
 use std::net::TcpStream;
 use url::Url;
 
+use crate::utils::parse_uci;
+
+/// Number of recent frames kept for diagnostics
+pub const RECORDED_FRAMES: usize = 50;
+
+/// How often a ping is sent so Lichess doesn't drop an idle socket
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How long close() waits for the server to answer the close frame
+pub const CLOSE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Lag reported with our moves until the server has measured ours
+pub const DEFAULT_LAG_MS: u32 = 20;
+/// Stored in place of a lag before the server reports one
+const NO_LAG: u32 = u32::MAX;
+
+/// Wait for a move's ack before sending it again
+pub const ACK_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Resends of an unacknowledged move before giving up on it
+pub const MAX_MOVE_RESENDS: u32 = 3;
+
+/// Longest chat line Lichess accepts, in characters
+pub const MAX_CHAT_LENGTH: usize = 140;
+/// Chat lines kept for the current game
+pub const CHAT_LINES: usize = 50;
+
+/// Chat text as Lichess accepts it: control characters stripped, surrounding
+/// whitespace trimmed and cut to MAX_CHAT_LENGTH characters
+pub fn sanitize_chat(text: &str) -> String {
+    let cleaned: String = text.chars().filter(|c| !c.is_control()).collect();
+    cleaned.trim().chars().take(MAX_CHAT_LENGTH).collect()
+}
+
+/// Game socket host used when none is configured (Lichess serves socket1 to socket5)
+pub const DEFAULT_WS_HOST: &str = "socket5.lichess.org";
+
+/// URL of a game's socket on `host`, which is either a bare hostname (wss is assumed)
+/// or a scheme and host like `ws://localhost:9000`
+pub fn game_socket_url(host: &str, game_id: &str, sri: &str) -> Result<Url, String> {
+    let host = host.trim();
+    let base = if host.contains("://") {
+        host.to_string()
+    } else {
+        format!("wss://{}", host)
+    };
+    let mut url =
+        Url::parse(&base).map_err(|e| format!("Invalid WebSocket host '{}': {}", host, e))?;
+    if !matches!(url.scheme(), "ws" | "wss") {
+        return Err(format!(
+            "Invalid WebSocket host '{}': the scheme must be ws or wss",
+            host
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(format!("Invalid WebSocket host '{}': no hostname", host));
+    }
+    url.set_path(&format!("/play/{}/v6", game_id));
+    url.set_query(Some(&format!("sri={}", sri)));
+    Ok(url)
+}
+
+/// Reconnect attempts after a dropped connection before giving up
+pub const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Wait before the first reconnect attempt, doubled after each failure
+pub const RECONNECT_BACKOFF_INITIAL: Duration = Duration::from_millis(500);
+/// Longest wait between two reconnect attempts
+pub const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// Each wait is randomly stretched or shrunk by up to this share, so clients
+/// dropped together don't all come back at the same instant
+pub const RECONNECT_JITTER: f64 = 0.2;
+
+/// Wait before reconnect attempt `attempt` (counted from 1), without jitter
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    RECONNECT_BACKOFF_INITIAL
+        .saturating_mul(factor)
+        .min(RECONNECT_BACKOFF_MAX)
+}
+
+/// `delay` moved randomly within ±RECONNECT_JITTER
+pub fn jittered<R: rand::Rng + ?Sized>(delay: Duration, rng: &mut R) -> Duration {
+    delay.mul_f64(rng.gen_range(1.0 - RECONNECT_JITTER..=1.0 + RECONNECT_JITTER))
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// The last move frame sent, kept so a lost move can be resent unchanged
+#[derive(Debug, Clone)]
+struct SentMove {
+    uci: String,
+    frame: String,
+    sent_at: Instant,
+    resends: u32,
+}
+
+/// A standing takeback proposal, ours or the opponent's
+#[derive(Debug, Clone)]
+struct Takeback {
+    /// Color of the proposer, once the server has told us
+    by: Option<String>,
+    ours: bool,
+}
+
+/// Plies a takeback undoes at `ply`: only the proposer's last move if it's the
+/// opponent's turn, or the opponent's reply too once they've moved
+pub fn takeback_plies(ply: u32, proposer: Option<&str>) -> u32 {
+    let to_move = if ply.is_multiple_of(2) { "white" } else { "black" };
+    let plies = if proposer == Some(to_move) { 2 } else { 1 };
+    plies.min(ply)
+}
+
+/// Why the socket couldn't be brought back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectError {
+    /// The game is over, so there is nothing to rejoin
+    GameEnded,
+    /// Every attempt failed; the socket stays disconnected
+    RetriesExhausted { attempts: u32, last_error: String },
+}
+
+impl std::fmt::Display for ReconnectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ReconnectError::GameEnded => write!(f, "Game has ended"),
+            ReconnectError::RetriesExhausted {
+                attempts,
+                last_error,
+            } => write!(
+                f,
+                "Disconnected: {} reconnect attempts failed ({})",
+                attempts, last_error
+            ),
+        }
+    }
+}
+
 /// WebSocket message types for Lichess protocol
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "t")]
@@ -32,7 +177,24 @@ pub enum LichessMessage {
     #[serde(rename = "crowd")]
     Crowd {
         #[serde(default)]
-        d: Option<serde_json::Value>,
+        d: Option<CrowdData>,
+    },
+    /// Color of the player offering a draw, None once offers are cleared
+    #[serde(rename = "drawOffer")]
+    DrawOffer {
+        #[serde(default)]
+        d: Option<String>,
+    },
+    /// A chat line from a player (or "lichess" for system notices)
+    #[serde(rename = "message")]
+    Chat {
+        d: ChatData,
+    },
+    /// Who is proposing a takeback, None once proposals are cleared
+    #[serde(rename = "takebackOffers")]
+    Takeback {
+        #[serde(default)]
+        d: Option<TakebackOffers>,
     },
 }
 
@@ -54,9 +216,76 @@ pub struct MoveData {
     pub ply: Option<u32>,    // ply number
     #[serde(skip_serializing_if = "Option::is_none")]
     pub uci: Option<String>, // alternative UCI field
+    /// Clocks after the move; absent in correspondence games
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockData>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Time left on each clock, in seconds (Lichess sends fractions down to the tenth)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClockData {
+    pub white: f32,
+    pub black: f32,
+}
+
+impl ClockData {
+    /// (white, black) in milliseconds
+    pub fn to_millis(self) -> (u64, u64) {
+        let ms = |secs: f32| (secs.max(0.0) as f64 * 1000.0).round() as u64;
+        (ms(self.white), ms(self.black))
+    }
+}
+
+/// Who is connected to the game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrowdData {
+    #[serde(rename = "white", default)]
+    pub white_present: bool,
+    #[serde(rename = "black", default)]
+    pub black_present: bool,
+    /// Spectators, when Lichess reports them
+    #[serde(default, deserialize_with = "deserialize_watchers")]
+    pub watchers: Option<u32>,
+}
+
+/// Watchers come as `{"nb":3,"users":[...]}`, or as a bare count
+fn deserialize_watchers<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u32>, D::Error> {
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value
+        .and_then(|v| v.get("nb").and_then(|nb| nb.as_u64()).or_else(|| v.as_u64()))
+        .map(|nb| nb as u32))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatData {
+    pub u: String, // username
+    pub t: String, // text
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TakebackOffers {
+    #[serde(default)]
+    pub white: bool,
+    #[serde(default)]
+    pub black: bool,
+}
+
+impl TakebackOffers {
+    /// Color of the proposing player, if any
+    pub fn proposer(&self) -> Option<&'static str> {
+        if self.white {
+            Some("white")
+        } else if self.black {
+            Some("black")
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EndData {
     #[serde(default)]
     pub status: Option<String>,
@@ -64,14 +293,237 @@ pub struct EndData {
     pub winner: Option<String>,
 }
 
+/// Something that happened in the game, as reported by process_events
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// A move was played, in UCI; our own moves are echoed back too
+    Move(String),
+    /// Position after the last move
+    Fen(String),
+    /// Seconds left on each clock after the last move
+    Clock { white: f32, black: f32 },
+    /// The game is over: the server's verdict from endData, or None when a
+    /// move came with a status and the board has to tell
+    GameEnd(Option<EndData>),
+    /// The server wants the game reloaded
+    Reload,
+    /// The server wants the game resynced
+    Resync,
+    /// Our pending move, in UCI, was accepted
+    Ack(String),
+    /// The whole game arrived on connect, see take_snapshot
+    Snapshot,
+    /// The socket dropped and is being reconnected
+    Disconnected,
+    Reconnected,
+    /// Reconnecting failed for good
+    ConnectionLost(String),
+    /// The opponent left; victory can be claimed in this many seconds
+    OpponentGone(u64),
+    OpponentBack,
+    /// Which players are connected, and how many are watching
+    Presence(CrowdData),
+    /// The opponent offers a draw
+    DrawOffer,
+    DrawOfferCleared,
+    /// The opponent asks for a takeback
+    TakebackOffer,
+    TakebackCleared,
+    /// A takeback went through, undoing this many plies
+    TakenBack(u32),
+    Chat(ChatData),
+    /// Our lag in ms, as the server measured it on our last move
+    Lag(u32),
+    /// Our premove, in UCI, went out after the opponent's move
+    PremoveSent(String),
+    /// Output of a custom handler
+    Custom(String),
+}
+
+impl GameEvent {
+    /// The `PREFIX:payload` string process_messages has always returned for this
+    /// event; None for reloads and resyncs, which it never reported
+    pub fn to_message(&self) -> Option<String> {
+        Some(match self {
+            GameEvent::Move(uci) => format!("MOVE:{}", uci),
+            GameEvent::Fen(fen) => format!("FEN:{}", fen),
+            GameEvent::Clock { white, black } => {
+                let (white, black) = ClockData {
+                    white: *white,
+                    black: *black,
+                }
+                .to_millis();
+                format!("CLOCK:{},{}", white, black)
+            }
+            GameEvent::GameEnd(Some(end)) => format!(
+                "GAME_END:{},{}",
+                end.status.as_deref().unwrap_or("unknown"),
+                end.winner.as_deref().unwrap_or("none")
+            ),
+            GameEvent::GameEnd(None) => "GAME_END".to_string(),
+            GameEvent::Reload | GameEvent::Resync => return None,
+            GameEvent::Ack(uci) => format!("ACK:{}", uci),
+            GameEvent::Snapshot => "SNAPSHOT".to_string(),
+            GameEvent::Disconnected => "DISCONNECTED".to_string(),
+            GameEvent::Reconnected => "RECONNECTED".to_string(),
+            GameEvent::ConnectionLost(reason) => format!("CONNECTION_LOST:{}", reason),
+            GameEvent::OpponentGone(secs) => format!("OPPONENT_GONE:{}", secs),
+            GameEvent::OpponentBack => "OPPONENT_BACK".to_string(),
+            // Watchers stay empty when Lichess didn't count them
+            GameEvent::Presence(crowd) => format!(
+                "PRESENCE:{},{},{}",
+                crowd.white_present as u8,
+                crowd.black_present as u8,
+                crowd.watchers.map(|n| n.to_string()).unwrap_or_default()
+            ),
+            GameEvent::DrawOffer => "DRAW_OFFER".to_string(),
+            GameEvent::DrawOfferCleared => "DRAW_OFFER_CLEARED".to_string(),
+            GameEvent::TakebackOffer => "TAKEBACK_OFFER".to_string(),
+            GameEvent::TakebackCleared => "TAKEBACK_CLEARED".to_string(),
+            GameEvent::TakenBack(plies) => format!("RESYNC:{}", plies),
+            // A line break would split the message in two
+            GameEvent::Chat(chat) => {
+                format!("CHAT:{}:{}", chat.u, chat.t.replace(['\n', '\r'], " "))
+            }
+            GameEvent::Lag(ms) => format!("LAG:{}", ms),
+            GameEvent::PremoveSent(uci) => format!("PREMOVE:{}", uci),
+            GameEvent::Custom(out) => out.clone(),
+        })
+    }
+}
+
+/// What a sent move's `a` (ack) field carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AckScheme {
+    /// Client sequence number, incremented for every move sent
+    #[default]
+    Sequence,
+    /// Last ply seen from the server (legacy behavior, fallback for desynced games)
+    Ply,
+}
+
+impl std::fmt::Display for AckScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AckScheme::Sequence => write!(f, "sequence"),
+            AckScheme::Ply => write!(f, "ply"),
+        }
+    }
+}
+
+impl std::str::FromStr for AckScheme {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sequence" => Ok(AckScheme::Sequence),
+            "ply" => Ok(AckScheme::Ply),
+            other => Err(format!("Unknown ack scheme: {}", other)),
+        }
+    }
+}
+
+/// Full game state sent on connect instead of incremental moves
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GameSnapshot {
+    /// Every move played so far, in UCI
+    pub moves: Vec<String>,
+    /// Current position, if the server sent it
+    pub fen: Option<String>,
+    /// Remaining time (white, black) in milliseconds
+    pub clocks_ms: Option<(u64, u64)>,
+    /// Game details the snapshot carries (opponent, time control, ...)
+    pub meta: crate::lichess::GameMeta,
+}
+
+impl GameSnapshot {
+    /// Parse the `d` payload of a snapshot message: `moves` as a space-separated
+    /// string or `steps` as [{"uci": ...}], plus optional `fen` and `clock` in seconds
+    pub fn from_json(d: &serde_json::Value) -> Self {
+        let moves = match d.get("moves").and_then(|m| m.as_str()) {
+            Some(moves) => moves.split_whitespace().map(str::to_string).collect(),
+            None => d
+                .get("steps")
+                .and_then(|s| s.as_array())
+                .map(|steps| {
+                    steps
+                        .iter()
+                        .filter_map(|step| step.get("uci").and_then(|u| u.as_str()))
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        let fen = d.get("fen").and_then(|f| f.as_str()).map(str::to_string);
+        let clocks_ms = d.get("clock").and_then(|clock| {
+            let ms = |color: &str| clock.get(color).and_then(|t| t.as_f64()).map(|secs| (secs * 1000.0) as u64);
+            Some((ms("white")?, ms("black")?))
+        });
+        GameSnapshot {
+            moves,
+            fen,
+            clocks_ms,
+            meta: crate::lichess::GameMeta::from_socket(d),
+        }
+    }
+}
+
+/// Custom handler for a message type the client doesn't know about.
+/// Receives the full JSON message; a returned string is forwarded like the built-in messages.
+pub type MessageHandler = Box<dyn Fn(&serde_json::Value) -> Option<String> + Send + Sync>;
+
+/// Checks a premove against the position after the opponent's move: receives the
+/// premove in UCI and the FEN sent with that move, if any
+pub type PremoveValidator = Box<dyn Fn(&str, Option<&str>) -> bool + Send + Sync>;
+
 /// WebSocket client for Lichess real-time game communication
 pub struct LichessWebSocket {
     ws: Arc<Mutex<WebSocket<MaybeTlsStream<TcpStream>>>>,
     current_ack: Arc<AtomicU32>,
+    move_seq: Arc<AtomicU32>,
+    ack_scheme: Arc<Mutex<AckScheme>>,
     game_ended: Arc<AtomicBool>,
     pending_move: Arc<Mutex<Option<String>>>,
+    draw_offered: Arc<AtomicBool>,
+    opponent_draw_offer: Arc<AtomicBool>,
+    resigned: Arc<AtomicBool>,
+    takeback: Arc<Mutex<Option<Takeback>>>,
+    opponent_gone: Arc<AtomicBool>,
+    /// When victory can be claimed, while the opponent is gone
+    claim_win_at: Arc<Mutex<Option<Instant>>>,
     last_move_acked: Arc<AtomicBool>,
     game_id: String,
+    strict_mode: Arc<AtomicBool>,
+    custom_handlers: Arc<Mutex<HashMap<String, MessageHandler>>>,
+    recent_frames: Arc<Mutex<VecDeque<String>>>,
+    snapshot: Arc<Mutex<Option<GameSnapshot>>>,
+    /// URL the socket was opened with, sri included, so reconnects rejoin as the same client
+    ws_url: String,
+    connected: Arc<AtomicBool>,
+    reconnect_attempts: Arc<AtomicU32>,
+    next_reconnect: Arc<Mutex<Option<Instant>>>,
+    /// Set once the reconnect attempts ran out
+    reconnect_failed: Arc<AtomicBool>,
+    /// When the last ping went out, in milliseconds since the epoch (0 before the first)
+    last_ping_ms: Arc<AtomicU64>,
+    last_sent_move: Arc<Mutex<Option<SentMove>>>,
+    /// Our lag as last reported by the server, NO_LAG until then
+    last_lag_ms: Arc<AtomicU32>,
+    /// Move to send as soon as the opponent has moved
+    premove: Arc<Mutex<Option<String>>>,
+    premove_validator: Arc<Mutex<Option<PremoveValidator>>>,
+    /// Set by close(), for good
+    closed: Arc<AtomicBool>,
+}
+
+/// Best-effort close, so quitting doesn't leave a half-open game socket on Lichess's side.
+/// It doesn't wait for the server's answer, so dropping never holds up the caller.
+impl Drop for LichessWebSocket {
+    fn drop(&mut self) {
+        if let Err(e) = self.shutdown("client dropped", false) {
+            debug!("[LichessWS] Close on drop failed: {}", e);
+        }
+    }
 }
 
 impl std::fmt::Debug for LichessWebSocket {
@@ -79,8 +531,12 @@ impl std::fmt::Debug for LichessWebSocket {
         f.debug_struct("LichessWebSocket")
             .field("game_id", &self.game_id)
             .field("current_ack", &self.current_ack.load(Ordering::Relaxed))
+            .field("move_seq", &self.move_seq.load(Ordering::Relaxed))
+            .field("ack_scheme", &*self.ack_scheme.lock().unwrap())
             .field("game_ended", &self.game_ended.load(Ordering::Relaxed))
             .field("last_move_acked", &self.last_move_acked.load(Ordering::Relaxed))
+            .field("strict_mode", &self.strict_mode.load(Ordering::Relaxed))
+            .field("connected", &self.connected.load(Ordering::Relaxed))
             .finish()
     }
 }
@@ -88,29 +544,205 @@ impl std::fmt::Debug for LichessWebSocket {
 impl LichessWebSocket {
     /// Create a new WebSocket connection to Lichess
     pub fn new(game_id: &str, sri: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let ws_url = format!("wss://socket5.lichess.org/play/{}/v6?sri={}", game_id, sri);
-        info!("[LichessWS] Connecting to: {}", ws_url);
-        
-        let url = Url::parse(&ws_url)?;
-        let (ws, _) = connect(url)?;
-        
-        info!("[LichessWS] ✅ Connected successfully");
+        Self::new_with_host(game_id, sri, DEFAULT_WS_HOST)
+    }
+    
+    /// Connect to a game on another socket host (see game_socket_url for the accepted forms)
+    pub fn new_with_host(
+        game_id: &str,
+        sri: &str,
+        host: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let ws_url = game_socket_url(host, game_id, sri)?;
+        Self::connect(ws_url.as_str(), game_id)
+    }
+    
+    /// Connect to a game socket at an explicit URL (e.g. a local test server over ws://)
+    pub fn connect(ws_url: &str, game_id: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let ws = Self::open_socket(ws_url)?;
         
         Ok(LichessWebSocket {
             ws: Arc::new(Mutex::new(ws)),
             current_ack: Arc::new(AtomicU32::new(0)),
+            move_seq: Arc::new(AtomicU32::new(0)),
+            ack_scheme: Arc::new(Mutex::new(AckScheme::default())),
             game_ended: Arc::new(AtomicBool::new(false)),
             pending_move: Arc::new(Mutex::new(None)),
+            draw_offered: Arc::new(AtomicBool::new(false)),
+            opponent_draw_offer: Arc::new(AtomicBool::new(false)),
+            resigned: Arc::new(AtomicBool::new(false)),
+            takeback: Arc::new(Mutex::new(None)),
+            opponent_gone: Arc::new(AtomicBool::new(false)),
+            claim_win_at: Arc::new(Mutex::new(None)),
             last_move_acked: Arc::new(AtomicBool::new(false)),
             game_id: game_id.to_string(),
+            strict_mode: Arc::new(AtomicBool::new(false)),
+            custom_handlers: Arc::new(Mutex::new(HashMap::new())),
+            recent_frames: Arc::new(Mutex::new(VecDeque::with_capacity(RECORDED_FRAMES))),
+            snapshot: Arc::new(Mutex::new(None)),
+            ws_url: ws_url.to_string(),
+            connected: Arc::new(AtomicBool::new(true)),
+            reconnect_attempts: Arc::new(AtomicU32::new(0)),
+            next_reconnect: Arc::new(Mutex::new(None)),
+            reconnect_failed: Arc::new(AtomicBool::new(false)),
+            last_ping_ms: Arc::new(AtomicU64::new(0)),
+            last_sent_move: Arc::new(Mutex::new(None)),
+            last_lag_ms: Arc::new(AtomicU32::new(NO_LAG)),
+            premove: Arc::new(Mutex::new(None)),
+            premove_validator: Arc::new(Mutex::new(None)),
+            closed: Arc::new(AtomicBool::new(false)),
         })
     }
     
+    /// Open a socket to `ws_url`, set up for non-blocking reads
+    fn open_socket(
+        ws_url: &str,
+    ) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, Box<dyn std::error::Error>> {
+        info!("[LichessWS] Connecting to: {}", ws_url);
+        
+        let url = Url::parse(ws_url)?;
+        let (ws, _) = connect(url)?;
+        
+        // process_messages polls the socket, so reads must not block the UI
+        match ws.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.set_nonblocking(true)?,
+            MaybeTlsStream::NativeTls(stream) => stream.get_ref().set_nonblocking(true)?,
+            _ => warn!("[LichessWS] Unknown stream type, reads may block"),
+        }
+        
+        info!("[LichessWS] ✅ Connected successfully");
+        Ok(ws)
+    }
+    
+    /// Replace a dropped socket with a new one to the same game and sri.
+    /// The ack counters carry over; a pending move is dropped since the server
+    /// may have moved on without it.
+    pub fn reconnect(&self) -> Result<(), String> {
+        let ws = Self::open_socket(&self.ws_url)
+            .map_err(|e| format!("Failed to reconnect: {}", e))?;
+        *self.ws.lock().unwrap() = ws;
+        if let Some(uci) = self.pending_move.lock().unwrap().take() {
+            warn!("[LichessWS] Pending move {} dropped on reconnect", uci);
+        }
+        self.connected.store(true, Ordering::Relaxed);
+        self.reconnect_failed.store(false, Ordering::Relaxed);
+        info!("[LichessWS] 🔄 Reconnected to game {}", self.game_id);
+        Ok(())
+    }
+    
+    /// Whether the socket is up; false while reconnecting after a drop
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+    
+    /// Whether the socket dropped mid-game and reconnect attempts are still being made
+    pub fn is_reconnecting(&self) -> bool {
+        !self.is_connected() && !self.is_game_ended() && !self.has_reconnect_failed()
+    }
+    
+    /// Whether the reconnect attempts ran out, leaving the socket disconnected
+    pub fn has_reconnect_failed(&self) -> bool {
+        self.reconnect_failed.load(Ordering::Relaxed)
+    }
+    
+    /// Jittered wait before reconnect attempt `attempt`, logged with the attempt number
+    fn backoff(attempt: u32, max_retries: u32) -> Duration {
+        let delay = jittered(reconnect_delay(attempt), &mut rand::thread_rng());
+        warn!(
+            "[LichessWS] Reconnect attempt {}/{} in {:?}",
+            attempt, max_retries, delay
+        );
+        delay
+    }
+    
+    /// Reconnect, blocking, waiting RECONNECT_BACKOFF_INITIAL before the first attempt and
+    /// doubling the wait (up to RECONNECT_BACKOFF_MAX, with jitter) after each failure.
+    /// Errors with RetriesExhausted once `max_retries` attempts have failed.
+    pub fn reconnect_with_backoff(&self, max_retries: u32) -> Result<(), ReconnectError> {
+        self.connected.store(false, Ordering::Relaxed);
+        let mut last_error = String::new();
+        for attempt in 1..=max_retries {
+            if self.is_game_ended() {
+                return Err(ReconnectError::GameEnded);
+            }
+            std::thread::sleep(Self::backoff(attempt, max_retries));
+            match self.reconnect() {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    warn!(
+                        "[LichessWS] Reconnect attempt {}/{} failed: {}",
+                        attempt, max_retries, e
+                    );
+                    last_error = e;
+                }
+            }
+        }
+        self.reconnect_failed.store(true, Ordering::Relaxed);
+        Err(ReconnectError::RetriesExhausted {
+            attempts: max_retries,
+            last_error,
+        })
+    }
+    
+    /// Non-blocking reconnect_with_backoff for the polling loop: schedules the next
+    /// attempt and makes it once it's due. Errors once MAX_RECONNECT_ATTEMPTS have failed.
+    fn try_reconnect(&self) -> Result<(), ReconnectError> {
+        let mut next = self.next_reconnect.lock().unwrap();
+        let Some(due) = *next else {
+            // Just dropped: wait before the first attempt too
+            *next = Some(Instant::now() + Self::backoff(1, MAX_RECONNECT_ATTEMPTS));
+            return Ok(());
+        };
+        if Instant::now() < due {
+            return Ok(());
+        }
+        let attempt = self.reconnect_attempts.load(Ordering::Relaxed) + 1;
+        self.reconnect_attempts.store(attempt, Ordering::Relaxed);
+        match self.reconnect() {
+            Ok(()) => {
+                self.reconnect_attempts.store(0, Ordering::Relaxed);
+                *next = None;
+                Ok(())
+            }
+            Err(e) => {
+                warn!(
+                    "[LichessWS] Reconnect attempt {}/{} failed: {}",
+                    attempt, MAX_RECONNECT_ATTEMPTS, e
+                );
+                if attempt >= MAX_RECONNECT_ATTEMPTS {
+                    self.reconnect_failed.store(true, Ordering::Relaxed);
+                    return Err(ReconnectError::RetriesExhausted {
+                        attempts: attempt,
+                        last_error: e,
+                    });
+                }
+                *next = Some(Instant::now() + Self::backoff(attempt + 1, MAX_RECONNECT_ATTEMPTS));
+                Ok(())
+            }
+        }
+    }
+    
+    /// Run a due reconnect attempt, reporting its outcome as RECONNECTED or, once the
+    /// attempts run out, CONNECTION_LOST:<reason>
+    fn poll_reconnect(&self, events: &mut Vec<GameEvent>) {
+        match self.try_reconnect() {
+            Ok(()) if self.is_connected() => events.push(GameEvent::Reconnected),
+            Ok(()) => {}
+            Err(e) => {
+                error!("[LichessWS] Giving up on game {}: {}", self.game_id, e);
+                events.push(GameEvent::ConnectionLost(e.to_string()));
+            }
+        }
+    }
+    
     /// Generate a Socket Request ID (12-char alphanumeric)
     pub fn generate_sri() -> String {
-        use rand::Rng;
+        Self::generate_sri_with(&mut rand::thread_rng())
+    }
+    
+    /// Generate a Socket Request ID from the given RNG (seed it for reproducible output)
+    pub fn generate_sri_with<R: rand::Rng + ?Sized>(rng: &mut R) -> String {
         const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-        let mut rng = rand::thread_rng();
         
         (0..12)
             .map(|_| {
@@ -123,10 +755,24 @@ impl LichessWebSocket {
     /// Send a move to Lichess
     pub fn send_move(&self, uci: &str, lag_ms: u32, berserked: bool) -> Result<(), String> {
         // Check guards
+        if self.is_closed() {
+            error!("[LichessWS] ❌ Socket closed, blocking move: {}", uci);
+            return Err("Connection closed".to_string());
+        }
         if self.game_ended.load(Ordering::Relaxed) {
             error!("[LichessWS] ❌ Game ended, blocking move: {}", uci);
             return Err("Game has ended".to_string());
         }
+        if !self.is_connected() {
+            warn!("[LichessWS] ❌ Reconnecting, blocking move: {}", uci);
+            return Err("Connection lost, reconnecting".to_string());
+        }
+        
+        // Reject malformed UCI before it reaches the server
+        if let Err(e) = parse_uci(uci) {
+            error!("[LichessWS] ❌ Invalid UCI, blocking move: {} ({})", uci, e);
+            return Err(format!("Invalid move: {}", e));
+        }
         
         // Check if there's a pending move
         {
@@ -145,7 +791,10 @@ impl LichessWebSocket {
         self.last_move_acked.store(false, Ordering::Relaxed);
         
         // Construct move message
-        let ack = self.current_ack.load(Ordering::Relaxed);
+        let ack = match *self.ack_scheme.lock().unwrap() {
+            AckScheme::Sequence => self.move_seq.fetch_add(1, Ordering::Relaxed) + 1,
+            AckScheme::Ply => self.current_ack.load(Ordering::Relaxed),
+        };
         let move_msg = serde_json::json!({
             "t": "move",
             "d": {
@@ -159,16 +808,85 @@ impl LichessWebSocket {
         info!("[Exec] ✅ Sending: {} | Lag: {}ms", uci, lag_ms);
         
         // Send the message
+        self.record_frame("⬆️", &move_msg.to_string());
+        *self.last_sent_move.lock().unwrap() = Some(SentMove {
+            uci: uci.to_string(),
+            frame: move_msg.to_string(),
+            sent_at: Instant::now(),
+            resends: 0,
+        });
         let mut ws = self.ws.lock().unwrap();
         ws.send(Message::Text(move_msg.to_string()))
             .map_err(|e| format!("Failed to send move: {}", e))?;
         
+        // Lichess withdraws our draw offer as soon as we move
+        if self.draw_offered.swap(false, Ordering::Relaxed) {
+            info!("[Game] Draw offer withdrawn by move {}", uci);
+        }
+        // and moving on declines the opponent's
+        self.opponent_draw_offer.store(false, Ordering::Relaxed);
+        
         Ok(())
     }
     
-    /// Process incoming WebSocket messages
+    /// Send the Lichess ping, carrying the last ply seen. It doesn't touch the pending
+    /// move, and isn't kept in the recent frames so it can't crowd out the game's.
+    pub fn send_ping(&self) -> Result<(), String> {
+        if !self.is_connected() {
+            return Err("Connection lost, reconnecting".to_string());
+        }
+        let ping = serde_json::json!({
+            "t": "p",
+            "v": self.current_ack.load(Ordering::Relaxed),
+        });
+        self.ws
+            .lock()
+            .unwrap()
+            .send(Message::Text(ping.to_string()))
+            .map_err(|e| format!("Failed to send ping: {}", e))?;
+        self.last_ping_ms.store(epoch_millis(), Ordering::Relaxed);
+        Ok(())
+    }
+    
+    /// Ping if `interval` has passed since the last ping; call it from the polling loop.
+    /// Returns whether a ping went out. Nothing is sent while disconnected or after the game.
+    pub fn tick_keepalive(&self, interval: Duration) -> Result<bool, String> {
+        if !self.is_connected() || self.is_game_ended() {
+            return Ok(false);
+        }
+        let since_last = epoch_millis().saturating_sub(self.last_ping_ms.load(Ordering::Relaxed));
+        if since_last < interval.as_millis() as u64 {
+            return Ok(false);
+        }
+        self.send_ping()?;
+        Ok(true)
+    }
+    
+    /// Process incoming WebSocket messages, as the legacy `PREFIX:payload` strings
+    /// (see GameEvent::to_message); new code should use process_events
     pub fn process_messages(&self) -> Result<Vec<String>, String> {
-        let mut messages = Vec::new();
+        Ok(self
+            .process_events()?
+            .iter()
+            .filter_map(GameEvent::to_message)
+            .collect())
+    }
+    
+    /// Process incoming WebSocket messages
+    pub fn process_events(&self) -> Result<Vec<GameEvent>, String> {
+        let mut events = Vec::new();
+        if !self.is_connected() {
+            if self.has_reconnect_failed() {
+                return Ok(events);
+            }
+            self.poll_reconnect(&mut events);
+            if !self.is_connected() {
+                return Ok(events);
+            }
+        }
+        let mut connection_lost = false;
+        // Sent once the socket is free again
+        let mut premove_due = None;
         let mut ws = self.ws.lock().unwrap();
         
         // Read all available messages (non-blocking)
@@ -177,7 +895,12 @@ impl LichessWebSocket {
                 Ok(msg) => {
                     match msg {
                         Message::Text(text) => {
+                            // Lichess answers our pings with a bare 0
+                            if text == "0" {
+                                continue;
+                            }
                             debug!("[LichessWS] ⬇️ Received: {}", text);
+                            self.record_frame("⬇️", &text);
                             
                             // Try to parse as JSON
                             if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
@@ -188,12 +911,27 @@ impl LichessWebSocket {
                                             let mut pending = self.pending_move.lock().unwrap();
                                             if let Some(uci) = pending.take() {
                                                 info!("[ACK] Move accepted: {}", uci);
+                                                events.push(GameEvent::Ack(uci));
                                             }
                                         }
                                         "endData" => {
                                             self.game_ended.store(true, Ordering::Relaxed);
-                                            info!("[Game] Ended - blocking further moves");
-                                            messages.push("GAME_END".to_string());
+                                            // Forward the server's verdict so the app can check it
+                                            // against the local board
+                                            let d = json.get("d");
+                                            let status = d
+                                                .and_then(|d| d.get("status"))
+                                                .and_then(|s| s.as_str().or_else(|| s.get("name").and_then(|n| n.as_str())))
+                                                .unwrap_or("unknown");
+                                            let winner = d
+                                                .and_then(|d| d.get("winner"))
+                                                .and_then(|w| w.as_str())
+                                                .unwrap_or("none");
+                                            info!("[Game] Ended ({}, winner: {}) - blocking further moves", status, winner);
+                                            events.push(GameEvent::GameEnd(Some(EndData {
+                                                status: Some(status.to_string()),
+                                                winner: Some(winner.to_string()).filter(|w| w != "none"),
+                                            })));
                                         }
                                         "move" => {
                                             if let Some(d) = json.get("d") {
@@ -201,38 +939,176 @@ impl LichessWebSocket {
                                                 if let Some(ply) = d.get("ply").and_then(|p| p.as_u64()) {
                                                     self.current_ack.store(ply as u32, Ordering::Relaxed);
                                                 }
+                                                // Play went on, so no takeback is coming
+                                                self.takeback.lock().unwrap().take();
                                                 
                                                 // Extract UCI move for highlighting
-                                                if let Some(uci) = d.get("uci").and_then(|u| u.as_str()) {
-                                                    messages.push(format!("MOVE:{}", uci));
-                                                } else if let Some(u) = d.get("u").and_then(|u| u.as_str()) {
-                                                    messages.push(format!("MOVE:{}", u));
+                                                let played = d.get("uci").or_else(|| d.get("u")).and_then(|u| u.as_str());
+                                                if let Some(uci) = played {
+                                                    events.push(GameEvent::Move(uci.to_string()));
+                                                }
+                                                
+                                                // Anything but the echo of our last move is the opponent's,
+                                                // which makes it our turn: time for the premove
+                                                let ours = self.last_sent_move.lock().unwrap().as_ref().map(|sent| sent.uci.clone());
+                                                if played.is_some() && played != ours.as_deref() {
+                                                    let fen = d.get("fen").and_then(|f| f.as_str());
+                                                    if let Some(uci) = self.take_premove(fen) {
+                                                        premove_due = Some(uci);
+                                                    }
+                                                } else if let Some(lag) = d.get("l").and_then(|l| l.as_u64()) {
+                                                    // Our echo carries the lag the server measured;
+                                                    // without one the last measure stands
+                                                    self.last_lag_ms.store(lag as u32, Ordering::Relaxed);
+                                                    events.push(GameEvent::Lag(lag as u32));
                                                 }
                                                 
                                                 // Extract FEN for sync
                                                 if let Some(fen) = d.get("fen").and_then(|f| f.as_str()) {
-                                                    messages.push(format!("FEN:{}", fen));
+                                                    events.push(GameEvent::Fen(fen.to_string()));
+                                                }
+                                                
+                                                // Clocks, when the game has them
+                                                match serde_json::from_value::<MoveData>(d.clone()) {
+                                                    Ok(MoveData { clock: Some(clock), .. }) => {
+                                                        events.push(GameEvent::Clock {
+                                                            white: clock.white,
+                                                            black: clock.black,
+                                                        });
+                                                    }
+                                                    Ok(_) => {}
+                                                    Err(e) => debug!("[LichessWS] Unreadable move payload: {}", e),
                                                 }
                                                 
                                                 // Check for game end in move response
                                                 if d.get("status").is_some() || d.get("winner").is_some() {
                                                     self.game_ended.store(true, Ordering::Relaxed);
-                                                    messages.push("GAME_END".to_string());
+                                                    events.push(GameEvent::GameEnd(None));
                                                 }
                                             }
                                         }
+                                        "full" => {
+                                            // Whole game on connect: one SNAPSHOT event, not a move per ply
+                                            let d = json.get("d").cloned().unwrap_or_default();
+                                            let snapshot = GameSnapshot::from_json(&d);
+                                            info!("[Game] Snapshot received ({} moves)", snapshot.moves.len());
+                                            self.current_ack.store(snapshot.moves.len() as u32, Ordering::Relaxed);
+                                            *self.snapshot.lock().unwrap() = Some(snapshot);
+                                            events.retain(|e| *e != GameEvent::Snapshot);
+                                            events.push(GameEvent::Snapshot);
+                                        }
                                         "reload" | "resync" => {
                                             info!("[WebSocket] 🔄 {} received, resetting state", msg_type);
-                                            // Clear pending move on reload/resync
-                                            let mut pending = self.pending_move.lock().unwrap();
-                                            *pending = None;
+                                            // The opponent accepting our takeback reloads the game
+                                            let takeback = self.takeback.lock().unwrap().take();
+                                            match takeback {
+                                                Some(takeback) if takeback.ours => {
+                                                    let plies = self.roll_back(takeback.by.as_deref());
+                                                    events.push(GameEvent::TakenBack(plies));
+                                                }
+                                                _ => {
+                                                    // Clear pending move on reload/resync
+                                                    let mut pending = self.pending_move.lock().unwrap();
+                                                    *pending = None;
+                                                }
+                                            }
+                                            events.push(if msg_type == "reload" {
+                                                GameEvent::Reload
+                                            } else {
+                                                GameEvent::Resync
+                                            });
+                                        }
+                                        "message" => {
+                                            match json.get("d").map(|d| serde_json::from_value::<ChatData>(d.clone())) {
+                                                Some(Ok(chat)) => events.push(GameEvent::Chat(chat)),
+                                                _ => debug!("[LichessWS] Unreadable chat message: {}", text),
+                                            }
+                                        }
+                                        "takebackOffers" => {
+                                            let offers: TakebackOffers = json
+                                                .get("d")
+                                                .and_then(|d| serde_json::from_value(d.clone()).ok())
+                                                .unwrap_or_default();
+                                            let mut takeback = self.takeback.lock().unwrap();
+                                            match (offers.proposer(), takeback.as_mut()) {
+                                                // The echo of our own proposal tells our color
+                                                (Some(by), Some(ours)) if ours.ours => {
+                                                    ours.by = Some(by.to_string());
+                                                }
+                                                (Some(by), _) => {
+                                                    info!("[Game] ↩️ Takeback proposed by {}", by);
+                                                    *takeback = Some(Takeback {
+                                                        by: Some(by.to_string()),
+                                                        ours: false,
+                                                    });
+                                                    events.push(GameEvent::TakebackOffer);
+                                                }
+                                                // Ours stays until the reload or the next move settles it
+                                                (None, Some(ours)) if ours.ours => {}
+                                                (None, _) => {
+                                                    if takeback.take().is_some() {
+                                                        events.push(GameEvent::TakebackCleared);
+                                                    }
+                                                }
+                                            }
                                         }
                                         "crowd" => {
-                                            // Player presence - log but don't process
-                                            debug!("[LichessWS] Crowd update");
+                                            // Player presence and spectators
+                                            let crowd: CrowdData = json
+                                                .get("d")
+                                                .and_then(|d| serde_json::from_value(d.clone()).ok())
+                                                .unwrap_or_default();
+                                            debug!("[LichessWS] Crowd update: {:?}", crowd);
+                                            events.push(GameEvent::Presence(crowd));
+                                        }
+                                        "drawOffer" => {
+                                            // Our own offer is echoed back too; while it stands,
+                                            // a second offer means the game is drawn, so only
+                                            // offers arriving without ours are the opponent's
+                                            let by = json.get("d").and_then(|d| d.as_str());
+                                            if by.is_none() {
+                                                self.draw_offered.store(false, Ordering::Relaxed);
+                                                if self.opponent_draw_offer.swap(false, Ordering::Relaxed) {
+                                                    events.push(GameEvent::DrawOfferCleared);
+                                                }
+                                            } else if !self.draw_offered.load(Ordering::Relaxed) {
+                                                info!("[Game] ½ Draw offered by {}", by.unwrap_or_default());
+                                                self.opponent_draw_offer.store(true, Ordering::Relaxed);
+                                                events.push(GameEvent::DrawOffer);
+                                            }
+                                        }
+                                        "gone" => {
+                                            // true: opponent left and victory can be claimed now
+                                            let gone = json.get("d").and_then(|d| d.as_bool()).unwrap_or(false);
+                                            if gone {
+                                                info!("[Game] ⚠️ Opponent gone, victory claimable");
+                                                self.set_opponent_gone(Some(Duration::ZERO));
+                                                events.push(GameEvent::OpponentGone(0));
+                                            } else {
+                                                info!("[Game] Opponent back");
+                                                self.set_opponent_gone(None);
+                                                events.push(GameEvent::OpponentBack);
+                                            }
+                                        }
+                                        "goneIn" => {
+                                            // Seconds until victory can be claimed
+                                            if let Some(secs) = json.get("d").and_then(|d| d.as_u64()) {
+                                                info!("[Game] ⚠️ Opponent gone, claimable in {}s", secs);
+                                                self.set_opponent_gone(Some(Duration::from_secs(secs)));
+                                                events.push(GameEvent::OpponentGone(secs));
+                                            }
                                         }
                                         _ => {
-                                            debug!("[LichessWS] Unhandled message type: {}", msg_type);
+                                            let handlers = self.custom_handlers.lock().unwrap();
+                                            if let Some(handler) = handlers.get(msg_type) {
+                                                if let Some(out) = handler(&json) {
+                                                    events.push(GameEvent::Custom(out));
+                                                }
+                                            } else if self.strict_mode.load(Ordering::Relaxed) {
+                                                warn!("[LichessWS] ⚠️ Unhandled message type: {} | Payload: {}", msg_type, text);
+                                            } else {
+                                                debug!("[LichessWS] Unhandled message type: {}", msg_type);
+                                            }
                                         }
                                     }
                                 }
@@ -244,6 +1120,7 @@ impl LichessWebSocket {
                         }
                         Message::Close(_) => {
                             info!("[LichessWS] Connection closed");
+                            connection_lost = true;
                             break;
                         }
                         _ => {}
@@ -255,12 +1132,432 @@ impl LichessWebSocket {
                 }
                 Err(e) => {
                     error!("[LichessWS] Error reading message: {}", e);
+                    connection_lost = true;
                     break;
                 }
             }
         }
+        drop(ws);
+        
+        if let Some(uci) = premove_due {
+            match self.send_move(&uci, self.last_lag_ms().unwrap_or(DEFAULT_LAG_MS), false) {
+                Ok(()) => {
+                    info!("[Game] ⚡ Premove {} sent", uci);
+                    events.push(GameEvent::PremoveSent(uci));
+                }
+                Err(e) => warn!("[LichessWS] ❌ Premove {} not sent: {}", uci, e),
+            }
+        }
+        
+        // A drop mid-game is retried; after close() or the game's end it's expected
+        if connection_lost && !self.game_ended.load(Ordering::Relaxed) {
+            warn!("[LichessWS] Connection to game {} lost, reconnecting", self.game_id);
+            self.connected.store(false, Ordering::Relaxed);
+            events.push(GameEvent::Disconnected);
+            self.poll_reconnect(&mut events);
+        }
+        
+        Ok(events)
+    }
+    
+    /// Log unhandled message types at warn level with their full payload
+    pub fn set_strict_mode(&self, strict: bool) {
+        self.strict_mode.store(strict, Ordering::Relaxed);
+        info!("[LichessWS] Strict mode {}", if strict { "enabled" } else { "disabled" });
+    }
+    
+    /// Check if strict mode is enabled
+    pub fn is_strict_mode(&self) -> bool {
+        self.strict_mode.load(Ordering::Relaxed)
+    }
+    
+    /// Choose what the `a` field of sent moves carries
+    pub fn set_ack_scheme(&self, scheme: AckScheme) {
+        *self.ack_scheme.lock().unwrap() = scheme;
+        info!("[LichessWS] Ack scheme: {}", scheme);
+    }
+    
+    /// The active ack scheme
+    pub fn ack_scheme(&self) -> AckScheme {
+        *self.ack_scheme.lock().unwrap()
+    }
+    
+    /// Register a handler for a message type (`t`) not handled by the client.
+    /// Built-in types always take precedence.
+    pub fn register_handler(&self, msg_type: &str, handler: MessageHandler) {
+        info!("[LichessWS] Registered handler for: {}", msg_type);
+        self.custom_handlers
+            .lock()
+            .unwrap()
+            .insert(msg_type.to_string(), handler);
+    }
+    
+    /// Our lag as the server last measured it, None before our first echoed move
+    pub fn last_lag_ms(&self) -> Option<u32> {
+        Some(self.last_lag_ms.load(Ordering::Relaxed)).filter(|&lag| lag != NO_LAG)
+    }
+    
+    /// Queue a move to send the moment the opponent's move arrives, replacing any earlier one
+    pub fn set_premove(&self, uci: &str) -> Result<(), String> {
+        parse_uci(uci).map_err(|e| format!("Invalid premove: {}", e))?;
+        info!("[Game] Premove set: {}", uci);
+        *self.premove.lock().unwrap() = Some(uci.to_string());
+        Ok(())
+    }
+    
+    /// Drop the queued premove
+    pub fn clear_premove(&self) {
+        if let Some(uci) = self.premove.lock().unwrap().take() {
+            debug!("[Game] Premove {} cleared", uci);
+        }
+    }
+    
+    /// Check if a premove is queued
+    pub fn has_premove(&self) -> bool {
+        self.premove.lock().unwrap().is_some()
+    }
+    
+    /// Check premoves against the new position before they're sent; without a
+    /// validator they go out as is and the server rejects illegal ones
+    pub fn set_premove_validator(&self, validator: PremoveValidator) {
+        *self.premove_validator.lock().unwrap() = Some(validator);
+    }
+    
+    /// The queued premove if it's legal after the opponent's move; it's cleared either way
+    fn take_premove(&self, fen: Option<&str>) -> Option<String> {
+        let uci = self.premove.lock().unwrap().take()?;
+        let legal = self
+            .premove_validator
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_none_or(|validate| validate(&uci, fen));
+        if !legal {
+            info!("[Game] Premove {} is illegal now, dropped", uci);
+            return None;
+        }
+        Some(uci)
+    }
+    
+    /// Check if the opponent has left the game
+    pub fn opponent_gone(&self) -> bool {
+        self.opponent_gone.load(Ordering::Relaxed)
+    }
+    
+    /// Time left before victory can be claimed, while the opponent is gone
+    pub fn claim_win_in(&self) -> Option<Duration> {
+        if !self.opponent_gone() {
+            return None;
+        }
+        self.claim_win_at
+            .lock()
+            .unwrap()
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+    
+    /// Opponent gone with victory claimable after `claim_in`, or back with None
+    fn set_opponent_gone(&self, claim_in: Option<Duration>) {
+        self.opponent_gone.store(claim_in.is_some(), Ordering::Relaxed);
+        *self.claim_win_at.lock().unwrap() = claim_in.map(|delay| Instant::now() + delay);
+    }
+    
+    /// Claim victory once the opponent has been gone long enough
+    pub fn claim_win(&self) -> Result<(), String> {
+        if self.game_ended.load(Ordering::Relaxed) {
+            return Err("Game has ended".to_string());
+        }
+        match self.claim_win_in() {
+            None => return Err("Opponent is not gone".to_string()),
+            Some(remaining) if !remaining.is_zero() => {
+                return Err(format!("Victory claimable in {}s", remaining.as_secs() + 1));
+            }
+            Some(_) => {}
+        }
+        
+        let claim_msg = serde_json::json!({ "t": "resign-force" });
+        info!("[Exec] ✅ Claiming victory");
+        
+        self.record_frame("⬆️", &claim_msg.to_string());
+        let mut ws = self.ws.lock().unwrap();
+        ws.send(Message::Text(claim_msg.to_string()))
+            .map_err(|e| format!("Failed to claim victory: {}", e))?;
+        
+        Ok(())
+    }
+    
+    /// Resign the game; the game ends once the server's endData arrives.
+    /// Resigning a game that's over or already resigned does nothing.
+    pub fn resign(&self) -> Result<(), String> {
+        if self.game_ended.load(Ordering::Relaxed) || self.resigned.swap(true, Ordering::Relaxed) {
+            debug!("[LichessWS] Game {} already over, not resigning", self.game_id);
+            return Ok(());
+        }
+        
+        let resign_msg = serde_json::json!({ "t": "resign" });
+        info!("[Game] 🏳️ Resigning game {}", self.game_id);
+        
+        self.record_frame("⬆️", &resign_msg.to_string());
+        let mut ws = self.ws.lock().unwrap();
+        if let Err(e) = ws.send(Message::Text(resign_msg.to_string())) {
+            self.resigned.store(false, Ordering::Relaxed);
+            return Err(format!("Failed to resign: {}", e));
+        }
+        
+        Ok(())
+    }
+    
+    /// Offer a draw, or accept the opponent's; refused while a move is waiting for its ack
+    pub fn offer_draw(&self) -> Result<(), String> {
+        if self.game_ended.load(Ordering::Relaxed) {
+            return Err("Game has ended".to_string());
+        }
+        if self.pending_move.lock().unwrap().is_some() {
+            warn!("[LichessWS] ❌ Move pending, blocking draw offer");
+            return Err("Move pending, draw offer not sent".to_string());
+        }
+        if self.draw_offered.load(Ordering::Relaxed) {
+            return Err("Draw already offered".to_string());
+        }
+        
+        let draw_msg = serde_json::json!({ "t": "draw-yes" });
+        info!("[Exec] ✅ Offering draw");
+        
+        self.record_frame("⬆️", &draw_msg.to_string());
+        let mut ws = self.ws.lock().unwrap();
+        ws.send(Message::Text(draw_msg.to_string()))
+            .map_err(|e| format!("Failed to offer draw: {}", e))?;
+        self.draw_offered.store(true, Ordering::Relaxed);
+        
+        Ok(())
+    }
+    
+    /// Check if our draw offer is still standing
+    pub fn is_draw_offered(&self) -> bool {
+        self.draw_offered.load(Ordering::Relaxed) && !self.game_ended.load(Ordering::Relaxed)
+    }
+    
+    /// Check if the opponent's draw offer is waiting for an answer
+    pub fn has_opponent_draw_offer(&self) -> bool {
+        self.opponent_draw_offer.load(Ordering::Relaxed) && !self.game_ended.load(Ordering::Relaxed)
+    }
+    
+    /// Decline the opponent's draw offer (accepting it is offer_draw)
+    pub fn decline_draw(&self) -> Result<(), String> {
+        if self.game_ended.load(Ordering::Relaxed) {
+            return Err("Game has ended".to_string());
+        }
+        if !self.opponent_draw_offer.load(Ordering::Relaxed) {
+            return Err("No draw offer to decline".to_string());
+        }
+        
+        let decline_msg = serde_json::json!({ "t": "draw-no" });
+        info!("[Exec] ✅ Declining draw");
+        
+        self.record_frame("⬆️", &decline_msg.to_string());
+        let mut ws = self.ws.lock().unwrap();
+        ws.send(Message::Text(decline_msg.to_string()))
+            .map_err(|e| format!("Failed to decline draw: {}", e))?;
+        self.opponent_draw_offer.store(false, Ordering::Relaxed);
+        
+        Ok(())
+    }
+    
+    /// Send a chat line to the opponent, sanitized to what Lichess accepts
+    pub fn send_chat(&self, text: &str) -> Result<(), String> {
+        let text = sanitize_chat(text);
+        if text.is_empty() {
+            return Err("Chat message is empty".to_string());
+        }
+        
+        let talk_msg = serde_json::json!({ "t": "talk", "d": text });
+        debug!("[Exec] Sending chat: {}", text);
+        
+        self.record_frame("⬆️", &talk_msg.to_string());
+        let mut ws = self.ws.lock().unwrap();
+        ws.send(Message::Text(talk_msg.to_string()))
+            .map_err(|e| format!("Failed to send chat: {}", e))?;
+        
+        Ok(())
+    }
+    
+    /// Ask the opponent to take back our last move; refused while a move is waiting for its ack
+    pub fn propose_takeback(&self) -> Result<(), String> {
+        if self.game_ended.load(Ordering::Relaxed) {
+            return Err("Game has ended".to_string());
+        }
+        if self.pending_move.lock().unwrap().is_some() {
+            warn!("[LichessWS] ❌ Move pending, blocking takeback proposal");
+            return Err("Move pending, takeback not proposed".to_string());
+        }
+        let mut takeback = self.takeback.lock().unwrap();
+        if takeback.is_some() {
+            return Err("A takeback is already proposed".to_string());
+        }
+        
+        let takeback_msg = serde_json::json!({ "t": "takeback-yes" });
+        info!("[Exec] ✅ Proposing takeback");
+        
+        self.record_frame("⬆️", &takeback_msg.to_string());
+        let mut ws = self.ws.lock().unwrap();
+        ws.send(Message::Text(takeback_msg.to_string()))
+            .map_err(|e| format!("Failed to propose takeback: {}", e))?;
+        *takeback = Some(Takeback { by: None, ours: true });
+        
+        Ok(())
+    }
+    
+    /// Answer the opponent's takeback proposal. Accepting rolls the ply back right away
+    /// and returns how many plies were undone, so the board can follow.
+    pub fn respond_takeback(&self, accept: bool) -> Result<u32, String> {
+        if self.game_ended.load(Ordering::Relaxed) {
+            return Err("Game has ended".to_string());
+        }
+        let mut takeback = self.takeback.lock().unwrap();
+        let by = match takeback.as_ref() {
+            Some(offer) if !offer.ours => offer.by.clone(),
+            _ => return Err("No takeback to answer".to_string()),
+        };
+        
+        let answer_msg = serde_json::json!({ "t": if accept { "takeback-yes" } else { "takeback-no" } });
+        info!("[Exec] ✅ {} takeback", if accept { "Accepting" } else { "Declining" });
+        
+        self.record_frame("⬆️", &answer_msg.to_string());
+        let mut ws = self.ws.lock().unwrap();
+        ws.send(Message::Text(answer_msg.to_string()))
+            .map_err(|e| format!("Failed to answer takeback: {}", e))?;
+        drop(ws);
+        takeback.take();
+        
+        Ok(if accept { self.roll_back(by.as_deref()) } else { 0 })
+    }
+    
+    /// Check if the opponent's takeback proposal is waiting for an answer
+    pub fn has_opponent_takeback(&self) -> bool {
+        self.takeback.lock().unwrap().as_ref().is_some_and(|takeback| !takeback.ours)
+    }
+    
+    /// Undo a takeback's plies: the ply goes back and any move in flight is dropped
+    fn roll_back(&self, proposer: Option<&str>) -> u32 {
+        let ply = self.current_ack.load(Ordering::Relaxed);
+        let plies = takeback_plies(ply, proposer);
+        self.current_ack.store(ply - plies, Ordering::Relaxed);
+        self.pending_move.lock().unwrap().take();
+        self.last_sent_move.lock().unwrap().take();
+        info!("[Game] ↩️ Took back {} plies, now at ply {}", plies, ply - plies);
+        plies
+    }
+    
+    /// Close the socket and mark the game as ended so no further moves are sent
+    pub fn close(&self) -> Result<(), String> {
+        self.shutdown("closed by the client", true)
+    }
+    
+    /// Check if close() has run; nothing more is sent after it
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+    
+    /// Send a close frame, then, with `wait_for_server`, read what the server still
+    /// sends until it answers the close or CLOSE_TIMEOUT passes. Only the first call
+    /// does anything. Poisoned locks are reported rather than panicking, as this also
+    /// runs on drop.
+    fn shutdown(&self, reason: &str, wait_for_server: bool) -> Result<(), String> {
+        if self.closed.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.game_ended.store(true, Ordering::Relaxed);
+        if let Ok(mut pending) = self.pending_move.lock() {
+            pending.take();
+        }
+        
+        info!("[LichessWS] Closing connection for game {} ({})", self.game_id, reason);
+        
+        let Ok(mut ws) = self.ws.lock() else {
+            return Err("WebSocket lock poisoned".to_string());
+        };
+        let frame = CloseFrame {
+            code: CloseCode::Normal,
+            reason: reason.to_string().into(),
+        };
+        ws.close(Some(frame))
+            .map_err(|e| format!("Failed to close WebSocket: {}", e))?;
+        if !wait_for_server {
+            let _ = ws.flush();
+            return Ok(());
+        }
+        
+        let deadline = Instant::now() + CLOSE_TIMEOUT;
+        while Instant::now() < deadline {
+            match ws.read() {
+                Ok(Message::Close(frame)) => {
+                    let reason = frame.map(|f| f.reason.to_string()).unwrap_or_default();
+                    debug!("[LichessWS] Server closed game {} ({})", self.game_id, reason);
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(ref e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    // Keep the close frame going out while the server has nothing to say
+                    let _ = ws.flush();
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                // Closed on both sides, or the peer is already gone
+                Err(_) => break,
+            }
+        }
         
-        Ok(messages)
+        Ok(())
+    }
+    
+    /// The pending move, if it has waited longer than `timeout` for its ack
+    /// (since it was sent or last resent)
+    pub fn check_pending_timeout(&self, timeout: Duration) -> Option<String> {
+        let pending = self.pending_move.lock().unwrap().clone()?;
+        let sent = self.last_sent_move.lock().unwrap();
+        sent.as_ref()
+            .filter(|sent| sent.uci == pending && sent.sent_at.elapsed() >= timeout)
+            .map(|sent| sent.uci.clone())
+    }
+    
+    /// Send the pending move again with the same ack number, so the server can tell it's a
+    /// duplicate. After MAX_MOVE_RESENDS the move is dropped, unblocking further moves,
+    /// and the error says so.
+    pub fn resend_pending(&self) -> Result<(), String> {
+        let Some(uci) = self.pending_move.lock().unwrap().clone() else {
+            return Err("No move pending".to_string());
+        };
+        let mut last_sent = self.last_sent_move.lock().unwrap();
+        let Some(sent) = last_sent.as_mut().filter(|sent| sent.uci == uci) else {
+            return Err("No move pending".to_string());
+        };
+        if sent.resends >= MAX_MOVE_RESENDS {
+            error!(
+                "[LichessWS] ❌ Move {} not acknowledged after {} resends, dropping it",
+                uci, sent.resends
+            );
+            let resends = sent.resends;
+            *last_sent = None;
+            self.pending_move.lock().unwrap().take();
+            return Err(format!(
+                "Move {} was not acknowledged after {} resends",
+                uci, resends
+            ));
+        }
+        sent.resends += 1;
+        sent.sent_at = Instant::now();
+        warn!(
+            "[LichessWS] ⏱️ No ack for {}, resending ({}/{})",
+            uci, sent.resends, MAX_MOVE_RESENDS
+        );
+        self.record_frame("⬆️", &sent.frame);
+        self.ws
+            .lock()
+            .unwrap()
+            .send(Message::Text(sent.frame.clone()))
+            .map_err(|e| format!("Failed to resend move: {}", e))
+    }
+    
+    /// Check if a sent move is still waiting for the server's ack
+    pub fn is_move_pending(&self) -> bool {
+        self.pending_move.lock().unwrap().is_some()
     }
     
     /// Check if the game has ended
@@ -272,4 +1569,23 @@ impl LichessWebSocket {
     pub fn game_id(&self) -> &str {
         &self.game_id
     }
+    
+    /// Take the game snapshot announced by the last SNAPSHOT event
+    pub fn take_snapshot(&self) -> Option<GameSnapshot> {
+        self.snapshot.lock().unwrap().take()
+    }
+    
+    /// Keep a text frame for diagnostics, dropping the oldest past RECORDED_FRAMES
+    fn record_frame(&self, direction: &str, text: &str) {
+        let mut frames = self.recent_frames.lock().unwrap();
+        if frames.len() == RECORDED_FRAMES {
+            frames.pop_front();
+        }
+        frames.push_back(format!("{} {}", direction, text));
+    }
+    
+    /// The last RECORDED_FRAMES text frames sent and received, oldest first
+    pub fn recent_frames(&self) -> Vec<String> {
+        self.recent_frames.lock().unwrap().iter().cloned().collect()
+    }
 }
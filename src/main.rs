@@ -3,8 +3,9 @@ extern crate chess_tui;
 
 use chess_tui::app::{App, AppResult};
 use chess_tui::config::Config;
-use chess_tui::constants::{config_dir, DisplayMode};
+use chess_tui::constants::{data_dir, DisplayMode};
 use chess_tui::event::{Event, EventHandler};
+use chess_tui::game_logic::bot::Bot;
 use chess_tui::game_logic::opponent::wait_for_game_start;
 use chess_tui::handler::{handle_key_events, handle_mouse_events};
 use chess_tui::logging;
@@ -33,61 +34,106 @@ struct Args {
     /// Disable sound effects
     #[arg(long)]
     no_sound: bool,
-    /// Open browser for Lichess login (session-based auth)
+    /// Log in to Lichess in the browser (OAuth) and save the session
     #[arg(long)]
     lichess_login: bool,
+    /// Delete the stored Lichess session, then exit
+    #[arg(long)]
+    lichess_logout: bool,
+    /// Lichess account profile to use, for switching between several accounts
+    #[arg(long, value_name = "NAME", default_value = "default")]
+    lichess_profile: String,
+    /// Watch the Lichess TV featured game (no token needed)
+    #[arg(long)]
+    tv: bool,
+    /// Seed for reproducible random choices (SRI, auto-move)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Check the chess engine, print its name, author and options, then exit
+    #[arg(long)]
+    engine_info: bool,
+    /// Directory for all persisted files (config, skins, session, logs)
+    #[arg(long)]
+    data_dir: Option<std::path::PathBuf>,
+    /// Write a redacted diagnostic bundle for bug reports, print its path, then exit
+    #[arg(long)]
+    diagnostics: bool,
+    /// Ack field of sent moves for this session: "sequence" or "ply" (fallback if games desync)
+    #[arg(long)]
+    ack_scheme: Option<String>,
+    /// Play a PGN file back as if it were a live game (board, clocks and move list)
+    #[arg(long, value_name = "PGN")]
+    simulate: Option<std::path::PathBuf>,
 }
 
-/// Open browser for Lichess login
-fn open_lichess_login() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔐 Opening Lichess login in your browser...");
+/// Log in to Lichess through the browser and save the session
+fn open_lichess_login(profile: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔐 Logging in to Lichess...");
     println!();
     
-    let login_url = "https://lichess.org/login";
-    
-    // Open browser (cross-platform)
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(login_url)
-            .spawn()?;
-    }
-    
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(login_url)
-            .spawn()?;
+    let session = chess_tui::lichess_auth::oauth_login(profile)?;
+    match &session.username {
+        Some(username) => println!("✅ Logged in as {}", username),
+        None => println!("✅ Logged in"),
     }
     
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(&["/C", "start", login_url])
-            .spawn()?;
-    }
-    
-    println!("📝 After logging in to Lichess:");
-    println!("   1. Open Developer Tools (F12)");
-    println!("   2. Go to Application/Storage > Cookies");
-    println!("   3. Copy the 'lila2' cookie value");
-    println!();
-    println!("⚠️  Note: Full session-based auth is not yet implemented.");
-    println!("   For now, please use: chess-tui -l YOUR_API_TOKEN");
-    println!();
-    println!("   Get your API token at: https://lichess.org/account/oauth/token");
-    println!("   (Enable 'board:play' scope)");
-    
     Ok(())
 }
 
+/// Print the configured engine's info (CLI path takes precedence over config)
+fn print_engine_info(args: &Args) -> AppResult<()> {
+    let engine_path = if !args.engine_path.is_empty() {
+        args.engine_path.clone()
+    } else {
+        let config_path = data_dir()?.join("config.toml");
+        fs::read_to_string(config_path)
+            .ok()
+            .and_then(|content| toml::from_str::<Config>(&content).ok())
+            .and_then(|config| config.engine_path)
+            .unwrap_or_default()
+    };
+
+    match Bot::engine_info(&engine_path) {
+        Ok(info) => {
+            println!("Engine: {}", engine_path);
+            println!("Name:   {}", info.name.as_deref().unwrap_or("unknown"));
+            println!("Author: {}", info.author.as_deref().unwrap_or("unknown"));
+            println!("Options ({}):", info.options.len());
+            for option in &info.options {
+                println!("  {}", option);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Engine check failed: {}", e);
+            eprintln!("Set the engine with: chess-tui -e /path/to/engine");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() -> AppResult<()> {
     // Parse the cli arguments first (this will handle --version and exit early if needed)
     let args = Args::parse();
 
+    // Route every persisted file through the data dir override
+    if let Some(dir) = &args.data_dir {
+        chess_tui::constants::set_data_dir(dir.clone())?;
+    }
+
     // Handle Lichess login request first
     if args.lichess_login {
-        return open_lichess_login();
+        return open_lichess_login(&args.lichess_profile);
+    }
+    if args.lichess_logout {
+        chess_tui::lichess_auth::LichessSession::logout_profile(&args.lichess_profile)?;
+        println!("Logged out of Lichess");
+        return Ok(());
+    }
+
+    // Print engine info and exit, before touching the terminal
+    if args.engine_info {
+        return print_engine_info(&args);
     }
 
     // Used to enable mouse capture (only after we know we're running the TUI)
@@ -96,9 +142,8 @@ fn main() -> AppResult<()> {
         ratatui::crossterm::event::EnableMouseCapture
     )?;
 
-    let config_dir = config_dir()?;
-    let folder_path = config_dir.join("chess-tui");
-    let config_path = config_dir.join("chess-tui/config.toml");
+    let folder_path = data_dir()?;
+    let config_path = folder_path.join("config.toml");
 
     // Create the configuration file
     config_create(&args, &folder_path, &config_path)?;
@@ -154,6 +199,115 @@ fn main() -> AppResult<()> {
                 app.sound_enabled = sound_enabled;
                 chess_tui::sound::set_sound_enabled(sound_enabled);
             }
+            // Add opponent-move reaction delay handling (off unless both bounds are set)
+            if let (Some(min_ms), Some(max_ms)) =
+                (config.reaction_delay_min_ms, config.reaction_delay_max_ms)
+            {
+                app.reaction_delay_ms = Some((min_ms, max_ms));
+            }
+            // Add socket strict mode handling
+            if let Some(ws_strict_mode) = config.ws_strict_mode {
+                app.ws_strict_mode = ws_strict_mode;
+            }
+            // Add ack scheme handling, keeping the sequence scheme on unknown values
+            if let Some(ws_ack_scheme) = config.ws_ack_scheme {
+                match ws_ack_scheme.parse() {
+                    Ok(ws_ack_scheme) => app.ws_ack_scheme = ws_ack_scheme,
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            // Add socket host handling, keeping the default host on malformed values
+            if let Some(ws_host) = config.ws_host {
+                match chess_tui::lichess_ws::game_socket_url(&ws_host, "game", "sri") {
+                    Ok(_) => app.ws_host = Some(ws_host),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            // Add book-only auto-move handling
+            if let Some(book_only) = config.auto_move_book_only {
+                app.auto_move_book_only = book_only;
+            }
+            app.auto_move_book_depth = config.auto_move_book_depth;
+            // Add courtesy resign handling
+            if let Some(courtesy_resign) = config.courtesy_resign {
+                app.courtesy_resign = courtesy_resign;
+            }
+            app.courtesy_resign_cp = config.courtesy_resign_cp;
+            // Add input method handling, keeping two-click on unknown values
+            if let Some(input_method) = config.input_method {
+                match input_method.parse() {
+                    Ok(input_method) => app.input_method = input_method,
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            // Add spectator orientation handling, keeping the featured side on unknown values
+            if let Some(orientation) = config.spectator_orientation {
+                match orientation.parse() {
+                    Ok(orientation) => app.spectator_orientation = orientation,
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            // Add move notation handling, keeping SAN on unknown values
+            if let Some(notation) = config.move_notation {
+                match notation.parse() {
+                    Ok(notation) => app.move_notation = notation,
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            // Add move confirm debounce handling
+            if let Some(move_debounce_ms) = config.move_debounce_ms {
+                app.move_debounce_ms = move_debounce_ms;
+            }
+            // Add game record handling
+            if let Some(auto_save_pgn) = config.auto_save_pgn {
+                app.auto_save_pgn = auto_save_pgn;
+            }
+            app.pgn_dir = config.pgn_dir.map(std::path::PathBuf::from);
+            // Add game resume handling
+            if let Some(auto_resume_game) = config.auto_resume_game {
+                app.auto_resume_game = auto_resume_game;
+            }
+            // Add move ack flash handling
+            if let Some(ack_flash) = config.ack_flash {
+                app.ack_flash = ack_flash;
+            }
+            // Add coordinate labels handling
+            if let Some(show_coordinates) = config.show_coordinates {
+                app.show_coordinates = show_coordinates;
+            }
+            // Add eval bar handling
+            if let Some(show_eval_bar) = config.show_eval_bar {
+                app.show_eval_bar = show_eval_bar;
+            }
+            // Add auth backend handling, keeping the file backend on unknown values
+            if let Some(auth_backend) = config.auth_backend {
+                match auth_backend.parse() {
+                    Ok(auth_backend) => {
+                        app.auth_backend = auth_backend;
+                        chess_tui::lichess_auth::set_auth_backend(auth_backend);
+                    }
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            // Add history cap handling
+            match config.max_history_plies {
+                Some(0) => eprintln!("max_history_plies must be at least 1"),
+                Some(max_history_plies) => app.max_history_plies = max_history_plies,
+                None => {}
+            }
+            // Add auto-move source chain handling, skipping unknown entries
+            if let Some(sources) = config.auto_move_sources {
+                app.auto_move_sources = sources
+                    .iter()
+                    .filter_map(|s| match s.parse() {
+                        Ok(source) => Some(source),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            None
+                        }
+                    })
+                    .collect();
+            }
         }
     } else {
         println!("Error reading the file or the file does not exist");
@@ -164,7 +318,7 @@ fn main() -> AppResult<()> {
     app.available_skins.push(Skin::ascii_display_mode());
 
     // Load all available skins from skins.json
-    let skins_path = config_dir.join("chess-tui/skins.json");
+    let skins_path = folder_path.join("skins.json");
 
     // Create skins.json if it doesn't exist
     if !skins_path.exists() {
@@ -232,6 +386,17 @@ fn main() -> AppResult<()> {
         app.lichess_token = Some(token.clone());
     }
 
+    // Command line seed makes random choices reproducible
+    app.rng_seed = args.seed;
+
+    // Command line ack scheme takes precedence over configuration file
+    if let Some(ack_scheme) = &args.ack_scheme {
+        match ack_scheme.parse() {
+            Ok(ack_scheme) => app.ws_ack_scheme = ack_scheme,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
     // Command line no-sound flag takes precedence over configuration file
     if args.no_sound {
         app.sound_enabled = false;
@@ -244,7 +409,7 @@ fn main() -> AppResult<()> {
     }
 
     // Try to load existing Lichess session
-    match chess_tui::lichess_auth::LichessSession::load_or_create() {
+    match chess_tui::lichess_auth::LichessSession::load_or_create_profile(&args.lichess_profile) {
         Ok(session) => {
             log::info!("Loaded Lichess session for: {:?}", session.username);
             app.lichess_session = Some(session);
@@ -255,6 +420,38 @@ fn main() -> AppResult<()> {
         }
     }
 
+    // Write the diagnostic bundle and exit, before the terminal is taken over
+    if args.diagnostics {
+        ratatui::crossterm::execute!(
+            std::io::stdout(),
+            ratatui::crossterm::event::DisableMouseCapture
+        )?;
+        let path = chess_tui::diagnostics::write_report(&app)?;
+        println!("Diagnostics written to {}", path.display());
+        return Ok(());
+    }
+
+    // Check the Lichess token up front, so an expired one asks for a new login
+    app.validate_lichess_token_at_startup();
+
+    // Offer to rejoin the game the last session was playing
+    if !args.tv && args.simulate.is_none() {
+        app.check_resumable_game_at_startup();
+    }
+
+    // Jump straight into Lichess TV for a zero-config demo
+    if args.tv {
+        app.start_lichess_tv();
+    }
+
+    // Replay a PGN as a live game, failing before the terminal is taken over
+    if let Some(pgn_path) = &args.simulate {
+        let pgn = std::fs::read_to_string(pgn_path)
+            .map_err(|e| format!("Failed to read {}: {}", pgn_path.display(), e))?;
+        app.start_simulation(&pgn, std::time::Instant::now())
+            .map_err(|e| format!("Cannot simulate {}: {}", pgn_path.display(), e))?;
+    }
+
     // Initialize the terminal user interface.
     let terminal = ratatui::try_init()?;
     let events = EventHandler::new(250);
@@ -305,6 +502,15 @@ fn main() -> AppResult<()> {
         // Check if Lichess seek is done
         app.check_lichess_seek();
 
+        // Apply Lichess TV frames
+        app.check_lichess_tv();
+
+        // Play the simulated PGN's due moves
+        app.check_simulation();
+
+        // Collect or cancel the on-demand analysis
+        app.check_deep_analysis();
+
         // Check if game ended
         app.check_game_end_status();
 
@@ -464,6 +670,7 @@ fn create_default_skins_file(skins_path: &Path) -> AppResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chess_tui::constants::config_dir;
     use std::fs;
 
     #[test]
@@ -474,6 +681,15 @@ mod tests {
             lichess_token: None,
             no_sound: false,
             lichess_login: false,
+            lichess_logout: false,
+            lichess_profile: "default".to_string(),
+            tv: false,
+            seed: None,
+            engine_info: false,
+            data_dir: None,
+            diagnostics: false,
+            ack_scheme: None,
+            simulate: None,
         };
 
         let config_dir = config_dir().unwrap();
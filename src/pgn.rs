@@ -0,0 +1,355 @@
+use shakmaty::fen::Fen;
+use shakmaty::san::SanPlus;
+use shakmaty::{CastlingMode, Chess, Color, Move, Position};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Delay between simulated moves when the PGN has no clock annotations
+pub const SIMULATION_MOVE_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest think a simulated move waits for, so long thinks don't stall playback
+pub const SIMULATION_MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// A mainline move, with the mover's remaining time from a `[%clk]` annotation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    pub san: String,
+    pub clock: Option<Duration>,
+}
+
+/// A game parsed from PGN: tag pairs, mainline moves and result
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnGame {
+    pub headers: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    /// Result token ("1-0", "0-1", "1/2-1/2" or "*")
+    pub result: Option<String>,
+}
+
+impl PgnGame {
+    /// Value of a tag pair, e.g. "White"
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Starting position: the FEN tag for games set up from a position, else the standard one
+    pub fn start_position(&self) -> Result<Chess, String> {
+        match self.header("FEN") {
+            Some(fen) => Fen::from_ascii(fen.as_bytes())
+                .map_err(|e| format!("Invalid FEN tag {}: {}", fen, e))?
+                .into_position(CastlingMode::Standard)
+                .map_err(|e| format!("Invalid FEN tag {}: {}", fen, e)),
+            None => Ok(Chess::default()),
+        }
+    }
+
+    /// Play the mainline from the start position, checking every move is legal
+    pub fn mainline(&self) -> Result<Vec<Move>, String> {
+        let mut position = self.start_position()?;
+        let mut moves = Vec::with_capacity(self.moves.len());
+        for (ply, pgn_move) in self.moves.iter().enumerate() {
+            let chess_move = SanPlus::from_ascii(pgn_move.san.as_bytes())
+                .ok()
+                .and_then(|san| san.san.to_move(&position).ok())
+                .ok_or_else(|| format!("Illegal move {} at ply {}", pgn_move.san, ply + 1))?;
+            position = position
+                .play(&chess_move)
+                .map_err(|e| format!("Illegal move {}: {}", pgn_move.san, e))?;
+            moves.push(chess_move);
+        }
+        Ok(moves)
+    }
+
+    /// Base time and increment from the TimeControl tag ("180+2"), if it has one
+    pub fn time_control(&self) -> Option<(Duration, Duration)> {
+        let time_control = self.header("TimeControl")?;
+        let (base, increment) = time_control.split_once('+').unwrap_or((time_control, "0"));
+        Some((
+            Duration::from_secs(base.parse().ok()?),
+            Duration::from_secs(increment.parse().ok()?),
+        ))
+    }
+}
+
+/// Parse a single-game PGN. Comments, variations and NAGs are skipped, except for
+/// `[%clk]` annotations, which are attached to the move they follow.
+pub fn parse_pgn(text: &str) -> Result<PgnGame, String> {
+    let mut game = PgnGame::default();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                let tag: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let (name, value) = tag
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| format!("Malformed tag: [{}]", tag))?;
+                let value = value.trim().trim_matches('"').replace("\\\"", "\"");
+                game.headers.push((name.to_string(), value));
+            }
+            '{' => {
+                let comment: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                if let (Some(clock), Some(last)) = (parse_clock(&comment), game.moves.last_mut()) {
+                    last.clock = Some(clock);
+                }
+            }
+            ';' => {
+                chars.by_ref().take_while(|&c| c != '\n').for_each(drop);
+            }
+            '(' => {
+                // Variations nest, and their comments may contain parentheses
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some('{') => chars.by_ref().take_while(|&c| c != '}').for_each(drop),
+                        Some(_) => {}
+                        None => return Err("Unterminated variation".to_string()),
+                    }
+                }
+            }
+            '$' => while chars.next_if(|c| c.is_ascii_digit()).is_some() {},
+            c if c.is_whitespace() => {}
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}()[];".contains(*c))
+                {
+                    token.push(c);
+                }
+                if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    game.result = Some(token);
+                    continue;
+                }
+                // Move numbers ("12." or "12...") may be glued to the move
+                let san = match token.rfind('.') {
+                    Some(dot) if token.starts_with(|c: char| c.is_ascii_digit()) => {
+                        &token[dot + 1..]
+                    }
+                    _ => token.as_str(),
+                };
+                let san = san.trim_end_matches(['!', '?']);
+                if !san.is_empty() {
+                    game.moves.push(PgnMove {
+                        san: san.to_string(),
+                        clock: None,
+                    });
+                }
+            }
+        }
+    }
+    Ok(game)
+}
+
+/// Remaining time from a comment's `[%clk h:mm:ss(.f)]` annotation
+pub fn parse_clock(comment: &str) -> Option<Duration> {
+    let start = comment.find("[%clk")? + "[%clk".len();
+    let end = start + comment[start..].find(']')?;
+    let mut parts = comment[start..end].trim().rsplit(':');
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let mut total_ms = (seconds * 1000.0).round() as u64;
+    for (part, unit) in parts.zip([60_000u64, 3_600_000]) {
+        total_ms += part.parse::<u64>().ok()? * unit;
+    }
+    Some(Duration::from_millis(total_ms))
+}
+
+/// Write a game as PGN: the tag pairs, then the move between each pair of consecutive
+/// positions in SAN (numbered from the first position's move number), then the result
+pub fn export_pgn(headers: &[(String, String)], positions: &[Chess], result: &str) -> String {
+    let mut pgn = String::new();
+    for (name, value) in headers {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('"', "\\\"")));
+    }
+    pgn.push('\n');
+
+    let mut tokens = Vec::new();
+    for (i, pair) in positions.windows(2).enumerate() {
+        let (position, next) = (&pair[0], &pair[1]);
+        let Some(chess_move) = position
+            .legal_moves()
+            .into_iter()
+            .find(|m| position.clone().play(m).is_ok_and(|played| played == *next))
+        else {
+            log::warn!(
+                "PGN export stopped: no legal move leads to position {}",
+                i + 1
+            );
+            break;
+        };
+        let number = position.fullmoves();
+        match position.turn() {
+            Color::White => tokens.push(format!("{}.", number)),
+            Color::Black if i == 0 => tokens.push(format!("{}...", number)),
+            Color::Black => {}
+        }
+        tokens.push(SanPlus::from_move(position.clone(), &chess_move).to_string());
+    }
+    tokens.push(result.to_string());
+
+    // Keep movetext lines under 80 columns
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > 79 {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+    pgn
+}
+
+/// File name (without extension) for a saved game: date, opponent and game id,
+/// keeping only characters that are safe in file names
+pub fn game_file_stem(date: &str, opponent: &str, game_id: &str) -> String {
+    [date, opponent, game_id]
+        .iter()
+        .map(|part| {
+            part.chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Write a PGN to `dir/<stem>.pgn`, creating the directory. An existing file is
+/// never overwritten: "-2", "-3", ... are appended to the stem instead.
+pub fn save_pgn(dir: &Path, stem: &str, pgn: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let mut path = dir.join(format!("{}.pgn", stem));
+    let mut n = 2;
+    loop {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(pgn.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                path = dir.join(format!("{}-{}.pgn", stem, n));
+                n += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A PGN played back as a live game, one move at a time on a timer
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// "White vs Black" from the tags
+    pub players: String,
+    /// Result announced once every move has been played
+    pub result: Option<String>,
+    /// Position the game starts from
+    pub start: Chess,
+    moves: Vec<Move>,
+    /// Wait before each move, measured from the previous one
+    delays: Vec<Duration>,
+    /// Remaining time (white, black) in ms after each move, when the PGN has clocks
+    clocks_ms: Vec<Option<(u64, u64)>>,
+    next_ply: usize,
+    next_move_at: Instant,
+}
+
+impl Simulation {
+    pub fn new(game: &PgnGame, now: Instant) -> Result<Self, String> {
+        let start = game.start_position()?;
+        let moves = game.mainline()?;
+        if moves.is_empty() {
+            return Err("The PGN has no moves to play".to_string());
+        }
+        let (base, increment) = game
+            .time_control()
+            .map_or((None, Duration::ZERO), |(base, inc)| (Some(base), inc));
+
+        // Time spent on a move is the mover's previous clock, plus increment, minus the new one
+        let first_mover = start.turn();
+        let mut delays = Vec::with_capacity(moves.len());
+        let mut clocks_ms = Vec::with_capacity(moves.len());
+        let (mut white, mut black) = (base, base);
+        for (ply, pgn_move) in game.moves.iter().enumerate() {
+            let white_moved = (ply % 2 == 0) == (first_mover == Color::White);
+            let mover = if white_moved { &mut white } else { &mut black };
+            // Clocks don't run before each side's first move
+            let delay = match (*mover, pgn_move.clock) {
+                (Some(previous), Some(clock)) if ply >= 2 => (previous + increment)
+                    .saturating_sub(clock)
+                    .min(SIMULATION_MAX_DELAY),
+                _ => SIMULATION_MOVE_INTERVAL,
+            };
+            delays.push(delay);
+
+            if pgn_move.clock.is_some() {
+                *mover = pgn_move.clock;
+            }
+            // Until both sides have a clock, show the known one for both
+            clocks_ms.push(
+                white
+                    .or(black)
+                    .zip(black.or(white))
+                    .map(|(white, black)| (white.as_millis() as u64, black.as_millis() as u64)),
+            );
+        }
+
+        Ok(Simulation {
+            players: format!(
+                "{} vs {}",
+                game.header("White").unwrap_or("White"),
+                game.header("Black").unwrap_or("Black")
+            ),
+            result: game.result.clone(),
+            start,
+            next_move_at: now + delays[0],
+            moves,
+            delays,
+            clocks_ms,
+            next_ply: 0,
+        })
+    }
+
+    /// The next move if its time has come, with the clocks after it. Each move is
+    /// scheduled from the previous one's due time, so playback doesn't drift.
+    pub fn next_due(&mut self, now: Instant) -> Option<(Move, Option<(u64, u64)>)> {
+        if self.is_finished() || now < self.next_move_at {
+            return None;
+        }
+        let ply = self.next_ply;
+        self.next_ply += 1;
+        if let Some(delay) = self.delays.get(self.next_ply) {
+            self.next_move_at += *delay;
+        }
+        Some((self.moves[ply].clone(), self.clocks_ms[ply]))
+    }
+
+    /// Number of moves played so far
+    pub fn played(&self) -> usize {
+        self.next_ply
+    }
+
+    /// Whether every move has been played
+    pub fn is_finished(&self) -> bool {
+        self.next_ply >= self.moves.len()
+    }
+}
@@ -13,6 +13,20 @@ pub struct Skin {
     pub cursor_color: Color,
     pub selection_color: Color,
     pub last_move_color: Color,
+    /// Marks the panel of the player whose turn it is; optional in skin files
+    #[serde(default = "default_side_to_move_color")]
+    pub side_to_move_color: Color,
+    /// File letters and rank numbers around the board; optional in skin files
+    #[serde(default = "default_coordinate_color")]
+    pub coordinate_color: Color,
+}
+
+fn default_side_to_move_color() -> Color {
+    Color::Yellow
+}
+
+fn default_coordinate_color() -> Color {
+    Color::Gray
 }
 
 impl Default for Skin {
@@ -26,6 +40,8 @@ impl Default for Skin {
             cursor_color: Color::LightBlue,
             selection_color: Color::LightGreen,
             last_move_color: Color::LightGreen,
+            side_to_move_color: default_side_to_move_color(),
+            coordinate_color: default_coordinate_color(),
         }
     }
 }
@@ -65,6 +81,8 @@ impl Skin {
             cursor_color: Color::LightBlue,
             selection_color: Color::LightGreen,
             last_move_color: Color::LightGreen,
+            side_to_move_color: default_side_to_move_color(),
+            coordinate_color: default_coordinate_color(),
         }
     }
 
@@ -79,6 +97,8 @@ impl Skin {
             cursor_color: Color::LightBlue,
             selection_color: Color::LightGreen,
             last_move_color: Color::LightGreen,
+            side_to_move_color: default_side_to_move_color(),
+            coordinate_color: default_coordinate_color(),
         }
     }
 }
@@ -0,0 +1,63 @@
+use crate::skin::Skin;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Paragraph,
+    Frame,
+};
+
+/// Below this much time the clocks show tenths of a second
+pub const CLOCK_TENTHS_BELOW_MS: u64 = 10_000;
+/// Below this much time a clock flashes red
+pub const CLOCK_PANIC_MS: u64 = 20_000;
+/// Length of each half of the panic flash
+const CLOCK_FLASH_MS: u64 = 500;
+
+/// `m:ss`, or `m:ss.t` once under CLOCK_TENTHS_BELOW_MS
+pub fn format_clock(ms: u64) -> String {
+    if ms < CLOCK_TENTHS_BELOW_MS {
+        format!("0:{:02}.{}", ms / 1000, ms / 100 % 10)
+    } else {
+        format!("{}:{:02}", ms / 60_000, ms / 1000 % 60)
+    }
+}
+
+/// The side to move's clock is bold in the side-to-move color. Under
+/// CLOCK_PANIC_MS it alternates between red text and a red background, timed by
+/// the clock itself so it keeps flashing while it runs.
+pub fn clock_style(ms: u64, to_move: bool, skin: &Skin) -> Style {
+    let style = if to_move {
+        Style::default()
+            .fg(skin.side_to_move_color)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+    if ms >= CLOCK_PANIC_MS {
+        style
+    } else if to_move && (ms / CLOCK_FLASH_MS).is_multiple_of(2) {
+        style.fg(Color::White).bg(Color::Red)
+    } else {
+        style.fg(Color::Red)
+    }
+}
+
+/// Render a clock right-aligned on the top border of a material panel
+pub fn render_clock(frame: &mut Frame, panel: Rect, ms: u64, to_move: bool, skin: &Skin) {
+    let text = format!(" {} ", format_clock(ms));
+    let width = text.chars().count() as u16;
+    // Keep clear of the panel's rounded corner, and of its title on narrow panels
+    if panel.height == 0 || panel.width < width * 2 + 1 {
+        return;
+    }
+    let row = Rect {
+        x: panel.x + panel.width - 1 - width,
+        width,
+        height: 1,
+        ..panel
+    };
+    frame.render_widget(
+        Paragraph::new(text).style(clock_style(ms, to_move, skin)),
+        row,
+    );
+}
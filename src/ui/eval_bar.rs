@@ -0,0 +1,72 @@
+use crate::auto_move::Eval;
+use crate::skin::Skin;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::Style,
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+/// Scores beyond this many centipawns (10 pawns) fill the bar to the end
+pub const EVAL_BAR_MAX_CP: i32 = 1000;
+
+/// Share of the bar that is White's, from 0.0 (Black winning) to 1.0 (White winning).
+/// A mate fills the bar for the side delivering it.
+pub fn white_share(eval: Eval) -> f64 {
+    match eval {
+        Eval::Centipawns(cp) => {
+            0.5 + cp.clamp(-EVAL_BAR_MAX_CP, EVAL_BAR_MAX_CP) as f64 / (2 * EVAL_BAR_MAX_CP) as f64
+        }
+        Eval::Mate(moves) if moves > 0 => 1.0,
+        Eval::Mate(_) => 0.0,
+    }
+}
+
+/// Score shown under the bar: pawns with one decimal, or `M3` / `-M3` for mates
+pub fn eval_label(eval: Eval) -> String {
+    match eval {
+        Eval::Centipawns(cp) => format!("{:+.1}", cp as f64 / 100.0),
+        Eval::Mate(moves) if moves < 0 => format!("-M{}", -moves),
+        Eval::Mate(moves) => format!("M{}", moves),
+    }
+}
+
+/// Render the bar in `bar_area`, White's share on White's side of the board, and
+/// the score in `label_area`. `eval` is from White's point of view.
+pub fn render_eval_bar(
+    frame: &mut Frame,
+    bar_area: Rect,
+    label_area: Rect,
+    eval: Eval,
+    skin: &Skin,
+    is_flipped: bool,
+) {
+    let white_rows = (white_share(eval) * bar_area.height as f64).round() as u16;
+    let (top_rows, top_color, bottom_color) = if is_flipped {
+        (white_rows, skin.board_white_color, skin.board_black_color)
+    } else {
+        (
+            bar_area.height - white_rows,
+            skin.board_black_color,
+            skin.board_white_color,
+        )
+    };
+    let top = Rect {
+        height: top_rows,
+        ..bar_area
+    };
+    let bottom = Rect {
+        y: bar_area.y + top_rows,
+        height: bar_area.height - top_rows,
+        ..bar_area
+    };
+    frame.render_widget(Block::default().style(Style::default().bg(top_color)), top);
+    frame.render_widget(
+        Block::default().style(Style::default().bg(bottom_color)),
+        bottom,
+    );
+    frame.render_widget(
+        Paragraph::new(eval_label(eval)).alignment(Alignment::Center),
+        label_area,
+    );
+}
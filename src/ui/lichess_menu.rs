@@ -59,6 +59,7 @@ pub fn render_lichess_menu(frame: &mut Frame, app: &App) {
         ("Puzzle", "Play a puzzle"),
         ("My Ongoing Games", "View and join your current games"),
         ("Join by Code", "Enter a game code to join"),
+        ("Lichess TV", "Watch the featured game live"),
         ("Disconnect", "Remove Lichess token and logout"),
     ];
 
@@ -66,7 +67,7 @@ pub fn render_lichess_menu(frame: &mut Frame, app: &App) {
 
     for (idx, (option, description)) in menu_items.iter().enumerate() {
         let is_selected = app.menu_cursor == idx as u8;
-        let is_disconnect = idx == 4; // Disconnect is the 5th option (index 4)
+        let is_disconnect = idx == 5; // Disconnect is the 6th option (index 5)
 
         let style = if is_selected {
             if is_disconnect {
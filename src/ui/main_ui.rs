@@ -3,7 +3,7 @@ use ratatui::{
     prelude::{Alignment, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, BorderType, Borders, Paragraph},
     Frame,
 };
 
@@ -11,13 +11,17 @@ use crate::{
     constants::Popups,
     game_logic::game::GameState,
     ui::popups::{
-        render_color_selection_popup, render_credit_popup, render_end_popup,
-        render_enter_game_code_popup, render_enter_lichess_token_popup, render_error_popup,
-        render_help_popup, render_promotion_popup, render_puzzle_end_popup,
-        render_resign_confirmation_popup, render_success_popup,
+        render_color_selection_popup, render_credit_popup, render_draw_offer_popup,
+        render_end_popup, render_enter_game_code_popup, render_enter_lichess_token_popup,
+        render_enter_move_popup, render_error_popup, render_game_aborted_popup, render_help_popup,
+        render_leave_game_confirmation_popup, render_promotion_popup, render_puzzle_end_popup,
+        render_resign_confirmation_popup, render_resume_game_popup, render_success_popup,
+        render_takeback_offer_popup,
     },
 };
 
+use super::clocks::{format_clock, render_clock};
+use super::eval_bar::render_eval_bar;
 use super::lichess_menu::render_lichess_menu;
 use super::ongoing_games::render_ongoing_games;
 use super::popups::{
@@ -29,6 +33,9 @@ use crate::{
 };
 use std::path::Path;
 
+/// Squares at least this many rows tall leave room for coordinates on all four sides
+const FULL_COORDINATES_MIN_SQUARE_HEIGHT: u16 = 3;
+
 /// Renders the user interface widgets.
 pub fn render(app: &mut App, frame: &mut Frame<'_>) {
     let main_area = frame.area();
@@ -136,6 +143,10 @@ pub fn render(app: &mut App, frame: &mut Frame<'_>) {
     else if app.current_page == Pages::OngoingGames {
         render_ongoing_games(frame, app);
     }
+    // Spectating the Lichess TV featured game
+    else if app.current_page == Pages::LichessTv {
+        render_game_ui(frame, app, main_area);
+    }
     // Render menu
     else {
         render_menu_ui(frame, app, main_area);
@@ -192,6 +203,29 @@ pub fn render(app: &mut App, frame: &mut Frame<'_>) {
         Some(Popups::ResignConfirmation) => {
             render_resign_confirmation_popup(frame, app);
         }
+        Some(Popups::LeaveGameConfirmation) => {
+            render_leave_game_confirmation_popup(frame);
+        }
+        Some(Popups::ResumeGame) => {
+            render_resume_game_popup(frame, app);
+        }
+        Some(Popups::DrawOffer) => {
+            render_draw_offer_popup(frame);
+        }
+        Some(Popups::TakebackOffer) => {
+            render_takeback_offer_popup(frame);
+        }
+        Some(Popups::EnterMove) => {
+            render_enter_move_popup(frame, &app.game.ui.prompt, app.error_message.as_deref());
+        }
+        Some(Popups::GameAborted) => {
+            render_game_aborted_popup(frame);
+        }
+        Some(Popups::Diagnostics) => {
+            if let Some(ref diagnostics_msg) = app.error_message {
+                render_success_popup(frame, diagnostics_msg);
+            }
+        }
         Some(Popups::PuzzleEndScreen) => {
             // Show puzzle completion message
             let message = if let Some(ref error_msg) = app.error_message {
@@ -463,41 +497,285 @@ pub fn render_game_ui(frame: &mut Frame<'_>, app: &mut App, main_area: Rect) {
     let board_inner = board_block.inner(board_with_labels[0]);
     ui.board_render(board_inner, frame, logic);
 
-    // Render rank labels (1-8) on the left - aligned with board's inner area
-    ui.render_rank_labels(frame, rank_label_area[0], logic.game_board.is_flipped);
+    if app.show_coordinates {
+        let is_flipped = logic.game_board.is_flipped;
+        // Render rank labels (1-8) on the left - aligned with board's inner area
+        ui.render_rank_labels(frame, rank_label_area[0], is_flipped);
+
+        // Render file labels (A-H) below the board
+        ui.render_file_labels(frame, board_with_labels[1], is_flipped);
+
+        // Large boards repeat them above and on the right, small ones keep only left and bottom
+        let top_padding = main_layout_horizontal[0];
+        if board_inner.height / 8 >= FULL_COORDINATES_MIN_SQUARE_HEIGHT && top_padding.height > 0 {
+            let above_board = Rect {
+                y: top_padding.bottom() - 1,
+                height: 1,
+                ..board_with_labels[1]
+            };
+            ui.render_file_labels(frame, above_board, is_flipped);
+
+            let right_label_area = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Ratio(8, 9), Constraint::Ratio(1, 9)].as_ref())
+                .split(main_layout_vertical[3]);
+            ui.render_rank_labels(frame, right_label_area[0], is_flipped);
+        }
+    }
+
+    // Engine evaluation left of the board, while a deep analysis has a score
+    if let Some(eval) = app.eval_bar_score() {
+        let eval_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Ratio(8, 9), Constraint::Ratio(1, 9)].as_ref())
+            .split(main_layout_vertical[0]);
+        let bar_width = eval_area[0].width.min(2);
+        let bar_area = Rect {
+            x: eval_area[0].x + (eval_area[0].width - bar_width) / 2,
+            width: bar_width,
+            ..eval_area[0]
+        };
+        render_eval_bar(
+            frame,
+            bar_area,
+            eval_area[1],
+            eval,
+            &app.game.ui.skin,
+            app.game.logic.game_board.is_flipped,
+        );
+    }
+
+    // What the engine is doing: our move, or pondering on the opponent's time
+    let engine_status = match &app.auto_move_controller {
+        Some(auto_move) if auto_move.is_engine_calculating() => Some((
+            "⚙ Engine calculating (our move)",
+            Style::default().fg(Color::Cyan),
+        )),
+        Some(auto_move) if auto_move.is_engine_pondering() => Some((
+            "💭 Engine pondering",
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )),
+        _ if app.is_bot_thinking() => {
+            Some(("⚙ Engine calculating", Style::default().fg(Color::Cyan)))
+        }
+        _ => None,
+    };
+    // A running or finished deep analysis takes the slot when the engine is idle
+    let deep_analysis = app.deep_analysis_label();
+    let engine_status = engine_status.or(deep_analysis
+        .as_deref()
+        .map(|label| (label, Style::default().fg(Color::Cyan))));
+    if let Some((label, style)) = engine_status {
+        let engine_indicator = Paragraph::new(label)
+            .alignment(Alignment::Left)
+            .style(style);
+        frame.render_widget(engine_indicator, main_layout_horizontal[0]);
+    }
 
-    // Render file labels (A-H) below the board
-    ui.render_file_labels(frame, board_with_labels[1], logic.game_board.is_flipped);
+    // Featured game players when spectating Lichess TV
+    if app.current_page == Pages::LichessTv {
+        let mut title = match (&app.simulation, &app.tv_featured_players) {
+            (Some(simulation), _) if simulation.is_finished() => format!(
+                "Simulation: {} ({})",
+                simulation.players,
+                simulation.result.as_deref().unwrap_or("*")
+            ),
+            (Some(simulation), _) => format!("Simulation: {}", simulation.players),
+            (None, Some(players)) => format!("Lichess TV: {}", players),
+            (None, None) => "Lichess TV: waiting for the featured game...".to_string(),
+        };
+        if let Some((white, black)) = app.running_clocks_ms(std::time::Instant::now()) {
+            title.push_str(&format!(
+                " - {} | {}",
+                format_clock(white),
+                format_clock(black)
+            ));
+        }
+        let tv_header = Paragraph::new(title)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(tv_header, main_layout_horizontal[0]);
+    }
+
+    // Brief confirmation once the server acknowledged our move
+    if app.is_ack_flash_active(std::time::Instant::now()) {
+        let ack_indicator = Paragraph::new("✓ Move confirmed")
+            .alignment(Alignment::Center)
+            .style(
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_widget(ack_indicator, main_layout_horizontal[0]);
+    }
+
+    // Our own connection dropped: moves can't be sent until it's back
+    if app.is_lichess_reconnecting() {
+        let reconnect_indicator = Paragraph::new("⟳ Connection lost - reconnecting…")
+            .alignment(Alignment::Center)
+            .style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_widget(reconnect_indicator, main_layout_horizontal[0]);
+    } else if app.is_lichess_disconnected() {
+        let disconnected_indicator = Paragraph::new("✗ Disconnected from Lichess")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+        frame.render_widget(disconnected_indicator, main_layout_horizontal[0]);
+    }
+
+    // Opponent disconnect countdown, then the claim-victory prompt
+    if let Some(remaining) = app.opponent_gone_countdown() {
+        let text = if remaining.is_zero() {
+            "Opponent left the game - press 'c' to claim victory".to_string()
+        } else {
+            format!(
+                "Opponent left the game - claim victory in {}s",
+                remaining.as_secs() + 1
+            )
+        };
+        let gone_indicator = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+        frame.render_widget(gone_indicator, main_layout_horizontal[0]);
+    }
+
+    // Standing draw offer, cleared once we move
+    if app.is_draw_offered() {
+        let draw_indicator = Paragraph::new("½ Draw offered")
+            .alignment(Alignment::Left)
+            .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(draw_indicator, main_layout_horizontal[0]);
+    } else if app.has_opponent_draw_offer() {
+        // Left open from its popup: offering a draw back accepts it
+        let draw_indicator = Paragraph::new("½ Draw offer from your opponent (d accepts)")
+            .alignment(Alignment::Left)
+            .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(draw_indicator, main_layout_horizontal[0]);
+    } else if app.game.logic.game_state == GameState::Playing
+        && app.game.logic.game_board.repetition_count() == 2
+    {
+        // One more repetition of this position is a draw
+        let repetition_warning = Paragraph::new("⚠ Position repeated - once more is a draw")
+            .alignment(Alignment::Left)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(repetition_warning, main_layout_horizontal[0]);
+    }
+
+    // Lock indicator so it's obvious engine assistance is off
+    if app.fair_play_lock {
+        let lock_label = if app.current_game_rated {
+            "🔒 Fair-play lock (rated)"
+        } else {
+            "🔒 Fair-play lock"
+        };
+        let lock_indicator = Paragraph::new(lock_label)
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(lock_indicator, main_layout_horizontal[0]);
+    }
 
     //top box for white material
-    let black_taken = app.game.logic.game_board.black_taken_pieces();
-    app.game
-        .ui
-        .black_material_render(board_block.inner(right_box_layout[0]), frame, &black_taken);
+    let side_to_move = app.side_to_move();
+    // Taken pieces and the material lead of the shown position
+    let black_taken = app
+        .game
+        .logic
+        .game_board
+        .captured_pieces(shakmaty::Color::Black);
+    let white_lead = app.game.logic.game_board.material_difference();
+    app.game.ui.black_material_render(
+        board_block.inner(right_box_layout[0]),
+        frame,
+        &black_taken,
+        -white_lead,
+        side_to_move == Some(shakmaty::Color::Black),
+    );
+
+    // Game info panel above the move list, for connected Lichess games
+    let history_area = match &app.game_meta {
+        Some(meta) => {
+            let mut lines = meta.lines();
+            lines.extend(app.presence_line());
+            let sidebar = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(lines.len() as u16 + 2),
+                    Constraint::Min(0),
+                ])
+                .split(right_box_layout[1]);
+            let info = Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+                .block(
+                    Block::default()
+                        .title("Game")
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .style(Style::default().fg(Color::Gray));
+            frame.render_widget(info, sidebar[0]);
+            sidebar[1]
+        }
+        None => right_box_layout[1],
+    };
 
     // We make the inside of the board
-    app.game
-        .ui
-        .history_render(board_block.inner(right_box_layout[1]), frame, &app.game);
+    app.game.ui.history_render(
+        board_block.inner(history_area),
+        frame,
+        &app.game,
+        app.move_notation,
+    );
 
     //bottom box for black matetrial
-    let white_taken = app.game.logic.game_board.white_taken_pieces();
+    let white_taken = app
+        .game
+        .logic
+        .game_board
+        .captured_pieces(shakmaty::Color::White);
     let is_puzzle_mode = app.puzzle_game.is_some();
     if is_puzzle_mode {
         app.game.ui.white_material_render_with_puzzle_hint(
             board_block.inner(right_box_layout[2]),
             frame,
             &white_taken,
+            white_lead,
             true,
+            side_to_move == Some(shakmaty::Color::White),
         );
     } else {
         app.game.ui.white_material_render(
             board_block.inner(right_box_layout[2]),
             frame,
             &white_taken,
+            white_lead,
+            side_to_move == Some(shakmaty::Color::White),
         );
     }
 
+    // Both players' clocks, running down between server updates
+    if app.current_page != Pages::LichessTv {
+        if let Some((white, black)) = app.running_clocks_ms(std::time::Instant::now()) {
+            render_clock(
+                frame,
+                right_box_layout[0],
+                black,
+                side_to_move == Some(shakmaty::Color::Black),
+                &app.game.ui.skin,
+            );
+            render_clock(
+                frame,
+                right_box_layout[2],
+                white,
+                side_to_move == Some(shakmaty::Color::White),
+                &app.game.ui.skin,
+            );
+        }
+    }
+
     if app.game.logic.game_state == GameState::Promotion {
         render_promotion_popup(frame, app);
     }
@@ -522,7 +800,13 @@ pub fn render_game_ui(frame: &mut Frame<'_>, app: &mut App, main_area: Rect) {
                 .as_ref()
                 .map(|opp| opp.is_lichess())
                 .unwrap_or(false);
-            render_end_popup(frame, &format!("{string_color} Won !!!"), is_lichess);
+            render_end_popup(
+                frame,
+                &format!("{string_color} Won !!!"),
+                is_lichess,
+                app.game.logic.bot.is_some(),
+                app.game_record_saved,
+            );
         }
     }
 
@@ -536,6 +820,12 @@ pub fn render_game_ui(frame: &mut Frame<'_>, app: &mut App, main_area: Rect) {
             .as_ref()
             .map(|opp| opp.is_lichess())
             .unwrap_or(false);
-        render_end_popup(frame, "That's a draw", is_lichess);
+        render_end_popup(
+            frame,
+            "That's a draw",
+            is_lichess,
+            app.game.logic.bot.is_some(),
+            app.game_record_saved,
+        );
     }
 }
@@ -1,3 +1,5 @@
+pub mod clocks;
+pub mod eval_bar;
 pub mod lichess_menu;
 pub mod main_ui;
 pub mod ongoing_games;
@@ -2,7 +2,7 @@ use std::net::IpAddr;
 
 use crate::{
     app::App,
-    constants::{NETWORK_PORT, WHITE},
+    constants::{DisplayMode, NETWORK_PORT, WHITE},
     pieces::{bishop::Bishop, knight::Knight, pawn::Pawn, queen::Queen, rook::Rook, PieceSize},
     ui::main_ui::{centered_rect, render_cell},
 };
@@ -27,7 +27,14 @@ pub fn render_resign_confirmation_popup(frame: &mut Frame, app: &App) {
         .border_style(Style::default().fg(WHITE));
     let area = centered_rect(50, 30, frame.area());
 
-    let opponent_name = if let Some(game) = app.ongoing_games.get(app.menu_cursor as usize) {
+    let live_opponent = app
+        .game_meta
+        .as_ref()
+        .filter(|_| app.lichess_ws.is_some() && app.has_live_game())
+        .and_then(|meta| meta.opponent_name.as_deref());
+    let opponent_name = if let Some(opponent) = live_opponent {
+        format!("vs {}", opponent)
+    } else if let Some(game) = app.ongoing_games.get(app.menu_cursor as usize) {
         format!("vs {}", game.opponent.username)
     } else {
         "this game".to_string()
@@ -71,6 +78,241 @@ pub fn render_resign_confirmation_popup(frame: &mut Frame, app: &App) {
     frame.render_widget(paragraph, area);
 }
 
+// This renders a confirmation popup for leaving a game that is still being played
+pub fn render_leave_game_confirmation_popup(frame: &mut Frame) {
+    let block = Block::default()
+        .title("Leave Game")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(50, 30, frame.area());
+
+    let text = vec![
+        Line::from(""),
+        Line::from("A game is still in progress.").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from("Leaving closes the connection to your opponent.").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "Y",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("es / "),
+            Span::styled(
+                "N",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("o"),
+        ])
+        .alignment(Alignment::Center),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
+// This renders the startup prompt to rejoin the game the last session was playing
+pub fn render_resume_game_popup(frame: &mut Frame, app: &App) {
+    let block = Block::default()
+        .title("Resume Game")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(50, 30, frame.area());
+
+    let game = app.ongoing_games.get(app.menu_cursor as usize);
+    let opponent_name = game.map_or("your opponent".to_string(), |game| {
+        game.opponent.username.clone()
+    });
+    let turn = if game.is_some_and(|game| game.is_my_turn) {
+        "It's your move."
+    } else {
+        "Waiting for your opponent's move."
+    };
+
+    let text = vec![
+        Line::from(""),
+        Line::from(format!(
+            "Your last game vs {} is still going on.",
+            opponent_name
+        ))
+        .alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(turn).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from("Resume it?").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "Y",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("es / "),
+            Span::styled(
+                "N",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("o"),
+        ])
+        .alignment(Alignment::Center),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
+// This renders the popup for the opponent's draw offer
+pub fn render_draw_offer_popup(frame: &mut Frame) {
+    let block = Block::default()
+        .title("Draw Offer")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(50, 30, frame.area());
+
+    let text = vec![
+        Line::from(""),
+        Line::from("Your opponent offers a draw.").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from("Accept it?").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "Y",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("es / "),
+            Span::styled(
+                "N",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("o"),
+        ])
+        .alignment(Alignment::Center),
+        Line::from(""),
+        Line::from("Esc: decide later").alignment(Alignment::Center),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
+// This renders the popup for the opponent's takeback proposal
+pub fn render_takeback_offer_popup(frame: &mut Frame) {
+    let block = Block::default()
+        .title("Takeback")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(50, 30, frame.area());
+
+    let text = vec![
+        Line::from(""),
+        Line::from("Your opponent asks to take back their last move.").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from("Allow it?").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "Y",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("es / "),
+            Span::styled(
+                "N",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("o"),
+        ])
+        .alignment(Alignment::Center),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
+// This renders the popup for a game the server aborted
+pub fn render_game_aborted_popup(frame: &mut Frame) {
+    let block = Block::default()
+        .title("Game Aborted")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(50, 30, frame.area());
+
+    let text = vec![
+        Line::from(""),
+        Line::from("Game aborted — no result").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from("It doesn't count for either player.").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "N",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("ew game / "),
+            Span::styled(
+                "B",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("ack to the menu"),
+        ])
+        .alignment(Alignment::Center),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
 // This renders a generic error popup with a custom message
 pub fn render_error_popup(frame: &mut Frame, error_message: &str) {
     let block = Block::default()
@@ -144,7 +386,13 @@ pub fn render_success_popup(frame: &mut Frame, success_message: &str) {
 }
 
 // This renders a popup for a promotion
-pub fn render_end_popup(frame: &mut Frame, sentence: &str, is_lichess: bool) {
+pub fn render_end_popup(
+    frame: &mut Frame,
+    sentence: &str,
+    is_lichess: bool,
+    is_bot: bool,
+    is_saved: bool,
+) {
     let block = Block::default()
         .title("Game Over")
         .title_alignment(Alignment::Center)
@@ -184,6 +432,31 @@ pub fn render_end_popup(frame: &mut Frame, sentence: &str, is_lichess: bool) {
         );
     }
 
+    // Against the bot, the same game can be replayed from the other side
+    if is_bot {
+        text.push(Line::from(""));
+        text.push(
+            Line::from("Press `X` to play again with colors swapped")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::LightGreen)),
+        );
+    }
+
+    text.push(Line::from(""));
+    if is_saved {
+        text.push(
+            Line::from("Game saved as PGN")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Gray)),
+        );
+    } else {
+        text.push(
+            Line::from("Press `P` to save the game as PGN")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::LightGreen)),
+        );
+    }
+
     text.push(Line::from(""));
     text.push(
         Line::from("Press `B` to go back to the menu")
@@ -299,7 +572,9 @@ pub fn render_promotion_popup(frame: &mut Frame, app: &mut App) {
     let text = vec![
         Line::from(""),
         Line::from("-- Choose your pawn promotion --").alignment(Alignment::Center),
-        Line::from(""),
+        Line::from("Q/R/B/N to pick, Esc to take the move back")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray)),
     ];
 
     let paragraph = Paragraph::new(text)
@@ -344,12 +619,16 @@ pub fn render_promotion_popup(frame: &mut Frame, app: &mut App) {
     let display_mode = &app.game.ui.display_mode;
     let piece_size = PieceSize::from_dimensions(inner_popup_layout_horizontal[0].height);
     let piece_color = Some(app.game.logic.player_turn);
+    let highlight = match display_mode {
+        DisplayMode::CUSTOM => app.game.ui.skin.cursor_color,
+        _ => Color::LightBlue,
+    };
 
     let queen_p = Paragraph::new(Queen::to_string(display_mode, piece_size, piece_color))
         .block(Block::default())
         .alignment(Alignment::Center)
         .style(Style::default().bg(if app.game.ui.promotion_cursor == 0 {
-            Color::LightBlue
+            highlight
         } else {
             Color::Reset // Set to the default background color when the condition is false
         }));
@@ -358,7 +637,7 @@ pub fn render_promotion_popup(frame: &mut Frame, app: &mut App) {
         .block(Block::default())
         .alignment(Alignment::Center)
         .style(Style::default().bg(if app.game.ui.promotion_cursor == 1 {
-            Color::LightBlue
+            highlight
         } else {
             Color::Reset // Set to the default background color when the condition is false
         }));
@@ -367,7 +646,7 @@ pub fn render_promotion_popup(frame: &mut Frame, app: &mut App) {
         .block(Block::default())
         .alignment(Alignment::Center)
         .style(Style::default().bg(if app.game.ui.promotion_cursor == 2 {
-            Color::LightBlue
+            highlight
         } else {
             Color::Reset // Set to the default background color when the condition is false
         }));
@@ -376,7 +655,7 @@ pub fn render_promotion_popup(frame: &mut Frame, app: &mut App) {
         .block(Block::default())
         .alignment(Alignment::Center)
         .style(Style::default().bg(if app.game.ui.promotion_cursor == 3 {
-            Color::LightBlue
+            highlight
         } else {
             Color::Reset // Set to the default background color when the condition is false
         }));
@@ -475,14 +754,51 @@ pub fn render_help_popup(frame: &mut Frame, app: &crate::app::App) {
         Line::from(""),
         Line::from("s: Cycle through available skins"),
         Line::from(""),
+        Line::from("f: Toggle the fair-play lock (always on in rated games)"),
+        Line::from(""),
+        Line::from("a: Show the squares attacked by the opponent"),
+        Line::from(""),
+        Line::from("e: Analyze the current position with the engine (depth 25)"),
+        Line::from(""),
+        Line::from("o: Flip the board (it starts from your side when you play Black)"),
+        Line::from(""),
+        Line::from("g: Show or hide the coordinates around the board"),
+        Line::from(""),
+        Line::from("v: Show or hide the evaluation bar of the engine analysis"),
+        Line::from(""),
+        Line::from("`Ctrl` d: Write a diagnostic bundle for bug reports"),
+        Line::from(""),
+        Line::from("d: Offer a draw in a Lichess game, or accept your opponent's (your next move withdraws it)"),
+        Line::from(""),
+        Line::from("r: Resign a Lichess game (asks first)"),
+        Line::from(""),
+        Line::from("u: Ask your Lichess opponent to take back your last move"),
+        Line::from(""),
+        Line::from("x: Replay a bot game from the start with colors swapped"),
+        Line::from(""),
+        Line::from("With the `type` input method, `Space` opens the move prompt"),
+        Line::from(""),
+        Line::from("When promoting, q/r/b/n pick the piece and `Esc` takes the move back"),
+        Line::from(""),
     ]);
 
-    // Only show history navigation controls in solo mode (not against bot or puzzle)
+    // History navigation controls only work in solo mode (not against bot or puzzle)
     if is_solo_mode && !is_puzzle_mode {
         text.push(Line::from("P: Navigate to previous position in history"));
         text.push(Line::from(""));
         text.push(Line::from("N: Navigate to next position in history"));
         text.push(Line::from(""));
+        text.push(Line::from(
+            "Home / End: Jump to the start / back to the live position",
+        ));
+        text.push(Line::from(""));
+        text.push(Line::from("PgUp / PgDn: Jump five moves back / forward"));
+        text.push(Line::from(""));
+    } else if !is_puzzle_mode {
+        text.push(Line::from(
+            "P / N, Home / End and PgUp / PgDn browse the move list in solo games only",
+        ));
+        text.push(Line::from(""));
     }
 
     text.extend(vec![
@@ -898,6 +1214,50 @@ pub fn render_enter_game_code_popup(frame: &mut Frame, prompt: &Prompt) {
     frame.render_widget(paragraph, area);
 }
 
+// This renders a popup allowing us to type a move (type input method)
+pub fn render_enter_move_popup(frame: &mut Frame, prompt: &Prompt, error: Option<&str>) {
+    let block = Block::default()
+        .title("Enter Move")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::horizontal(1))
+        .border_style(Style::default().fg(WHITE));
+    let area = centered_rect(40, 30, frame.area());
+
+    let current_input = prompt.input.as_str();
+
+    let mut text = vec![
+        Line::from("Type a move (e.g. e2e4 or Nf3):").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(current_input),
+        Line::from(""),
+    ];
+    if let Some(error) = error {
+        text.push(Line::from(Span::styled(
+            error,
+            Style::default().fg(Color::Red),
+        )));
+    }
+    text.push(Line::from(""));
+    text.push(Line::from("Press `Esc` to cancel.").alignment(Alignment::Center));
+
+    let paragraph = Paragraph::new(text)
+        .block(block.clone())
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    frame.set_cursor_position(Position::new(
+        // Draw the cursor at the current position in the input field.
+        area.x + prompt.character_index as u16 + 2,
+        // Move one line down, from the border to the input line
+        area.y + 3,
+    ));
+
+    frame.render_widget(Clear, area); //this clears out the background
+    frame.render_widget(block, area);
+    frame.render_widget(paragraph, area);
+}
+
 // This renders a popup allowing us to enter a Lichess API token
 pub fn render_enter_lichess_token_popup(frame: &mut Frame, prompt: &Prompt) {
     let block = Block::default()
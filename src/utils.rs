@@ -1,6 +1,84 @@
 use crate::game_logic::coord::Coord;
 use ratatui::style::Color;
-use shakmaty::Square;
+use shakmaty::{Role, Square};
+use std::fmt;
+
+/// A UCI move string that has been bounds checked (e.g. "e2e4" or "e7e8q")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UciMove {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Role>,
+}
+
+impl fmt::Display for UciMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+        if let Some(role) = self.promotion {
+            write!(f, "{}", role.char())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reasons a UCI move string can be rejected by `parse_uci`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciError {
+    /// The string is not 4 or 5 characters long
+    InvalidLength(usize),
+    /// One of the two squares has an out of range file or rank
+    InvalidSquare(String),
+    /// The 5th character is not one of q, r, b, n
+    InvalidPromotion(char),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciError::InvalidLength(len) => {
+                write!(f, "UCI move must be 4 or 5 characters, got {}", len)
+            }
+            UciError::InvalidSquare(square) => write!(f, "Invalid square in UCI move: {}", square),
+            UciError::InvalidPromotion(c) => write!(f, "Invalid promotion piece: {}", c),
+        }
+    }
+}
+
+impl std::error::Error for UciError {}
+
+/// Parse a UCI move string, rejecting anything that isn't 4-5 chars with valid files/ranks
+pub fn parse_uci(uci: &str) -> Result<UciMove, UciError> {
+    let chars: Vec<char> = uci.chars().collect();
+    if chars.len() != 4 && chars.len() != 5 {
+        return Err(UciError::InvalidLength(chars.len()));
+    }
+
+    let parse_square = |file: char, rank: char| -> Result<Square, UciError> {
+        if !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+            return Err(UciError::InvalidSquare(format!("{}{}", file, rank)));
+        }
+        Square::from_ascii(format!("{}{}", file, rank).as_bytes())
+            .map_err(|_| UciError::InvalidSquare(format!("{}{}", file, rank)))
+    };
+
+    let from = parse_square(chars[0], chars[1])?;
+    let to = parse_square(chars[2], chars[3])?;
+
+    let promotion = match chars.get(4) {
+        None => None,
+        Some('q') => Some(Role::Queen),
+        Some('r') => Some(Role::Rook),
+        Some('b') => Some(Role::Bishop),
+        Some('n') => Some(Role::Knight),
+        Some(c) => return Err(UciError::InvalidPromotion(*c)),
+    };
+
+    Ok(UciMove {
+        from,
+        to,
+        promotion,
+    })
+}
 
 pub fn color_to_ratatui_enum(piece_color: Option<shakmaty::Color>) -> Color {
     match piece_color {
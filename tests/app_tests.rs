@@ -0,0 +1,621 @@
+use chess_tui::app::App;
+
+#[test]
+fn test_opponent_gone_countdown() {
+    let mut app = App::default();
+    assert!(app.opponent_gone_countdown().is_none());
+    assert!(!app.can_claim_victory());
+
+    app.handle_opponent_gone(30);
+    assert!(app.opponent_gone_countdown().is_some());
+    assert!(!app.can_claim_victory());
+
+    // Countdown already expired: claiming is allowed
+    app.handle_opponent_gone(0);
+    assert!(app.can_claim_victory());
+
+    app.handle_opponent_back();
+    assert!(app.opponent_gone_countdown().is_none());
+    assert!(!app.can_claim_victory());
+}
+
+#[test]
+fn test_leaving_live_game_asks_and_tears_down() {
+    use chess_tui::constants::{Pages, Popups};
+    use chess_tui::game_logic::opponent::Opponent;
+    use shakmaty::Color;
+    use std::sync::mpsc::{channel, TryRecvError};
+
+    let mut app = App::default();
+    assert!(!app.has_live_game());
+
+    let (_to_app_tx, to_app_rx) = channel::<String>();
+    let (to_lichess_tx, to_lichess_rx) = channel::<String>();
+    let (player_move_tx, player_move_rx) = channel::<()>();
+    app.game.logic.opponent = Some(Opponent::new_lichess(
+        "abcd1234".to_string(),
+        Color::Black,
+        to_app_rx,
+        to_lichess_tx,
+        0,
+        Some(player_move_tx),
+    ));
+    app.current_page = Pages::Lichess;
+    app.current_game_rated = true;
+    assert!(app.has_live_game());
+
+    app.request_leave_game();
+    assert_eq!(app.current_popup, Some(Popups::LeaveGameConfirmation));
+    assert_eq!(app.current_page, Pages::Lichess);
+
+    // Confirming drops the opponent, which disconnects the background threads
+    app.reset_home();
+    assert!(app.game.logic.opponent.is_none());
+    assert!(!app.current_game_rated);
+    assert_eq!(app.current_page, Pages::Home);
+    assert_eq!(player_move_rx.try_recv(), Err(TryRecvError::Disconnected));
+    assert!(to_lichess_rx.recv().is_err());
+}
+
+#[test]
+fn test_threats_overlay_respects_fair_play_lock() {
+    let mut app = App::default();
+    app.toggle_threats_overlay();
+    assert!(app.game.ui.show_threats);
+
+    // Engaging the lock hides the overlay and keeps it off
+    app.set_fair_play_lock(true);
+    assert!(!app.game.ui.show_threats);
+    app.toggle_threats_overlay();
+    assert!(!app.game.ui.show_threats);
+}
+
+#[test]
+fn test_end_data_contradicting_board_needs_resync() {
+    use shakmaty::Square;
+
+    let mut app = App::default();
+    // A resignation or timeout never shows on the board
+    assert!(!app.end_data_contradicts_board("resign"));
+    assert!(!app.end_data_contradicts_board("outoftime"));
+    // A checkmate the local board doesn't know about is a desync
+    assert!(app.end_data_contradicts_board("mate"));
+
+    // Fool's mate: now the board agrees with the server
+    let board = &mut app.game.logic.game_board;
+    board.execute_shakmaty_move(Square::F2, Square::F3);
+    board.execute_shakmaty_move(Square::E7, Square::E5);
+    board.execute_shakmaty_move(Square::G2, Square::G4);
+    board.execute_shakmaty_move(Square::D8, Square::H4);
+    assert!(!app.end_data_contradicts_board("mate"));
+}
+
+#[test]
+fn test_typed_moves() {
+    use chess_tui::constants::InputMethod;
+    use shakmaty::Color;
+
+    assert_eq!("type".parse::<InputMethod>(), Ok(InputMethod::Type));
+    assert_eq!(" Drag ".parse::<InputMethod>(), Ok(InputMethod::Drag));
+    assert!("mouse".parse::<InputMethod>().is_err());
+    assert_eq!(InputMethod::default().to_string(), "two_click");
+
+    let mut app = App::default();
+
+    // Both SAN and UCI are accepted
+    assert!(app.submit_typed_move("e4").is_ok());
+    assert!(app.submit_typed_move("e7e5").is_ok());
+    assert!(app.submit_typed_move("Nf3").is_ok());
+    assert_eq!(app.game.logic.game_board.move_history.len(), 3);
+    assert_eq!(app.game.logic.player_turn, Color::Black);
+
+    assert!(app.submit_typed_move("Ke7e9").is_err());
+    assert!(app.submit_typed_move("Qh5").is_err());
+    assert_eq!(app.game.logic.game_board.move_history.len(), 3);
+}
+
+#[test]
+fn test_swap_sides_restarts_bot_game() {
+    use chess_tui::game_logic::bot::Bot;
+    use shakmaty::{Color, Square};
+    use std::sync::mpsc::channel;
+
+    // Playing Black: the bot opened, and is thinking about its next move
+    let (_bot_tx, bot_rx) = channel();
+    let mut app = App {
+        selected_color: Some(Color::Black),
+        bot_move_receiver: Some(bot_rx),
+        ..App::default()
+    };
+    app.game.logic.bot = Some(Bot::new("", true, 10));
+    app.game.logic.game_board.flip_the_board();
+    let board = &mut app.game.logic.game_board;
+    for (from, to) in [(Square::E2, Square::E4), (Square::C7, Square::C5)] {
+        let executed = board.execute_move(from, to, None).unwrap();
+        board.move_history.push(executed);
+    }
+
+    // Now White: back to the start, our move, and the stale engine search is dropped
+    app.swap_sides_and_restart();
+    assert_eq!(app.selected_color, Some(Color::White));
+    assert!(app
+        .game
+        .logic
+        .bot
+        .as_ref()
+        .is_some_and(|bot| !bot.is_bot_starting));
+    assert!(app.bot_move_receiver.is_none());
+    assert!(app.game.logic.game_board.move_history.is_empty());
+    assert!(!app.game.logic.game_board.is_flipped);
+    assert_eq!(app.game.logic.player_turn, Color::White);
+}
+
+#[test]
+fn test_move_confirm_debounce() {
+    use chess_tui::game_logic::coord::Coord;
+    use chess_tui::utils::flip_square_if_needed;
+    use shakmaty::Square;
+    use std::time::{Duration, Instant};
+
+    fn put_cursor(app: &mut App, square: Square) {
+        let is_flipped = app.game.logic.game_board.is_flipped;
+        app.game.ui.cursor_coordinates =
+            Coord::from_square(flip_square_if_needed(square, is_flipped));
+    }
+
+    let mut app = App::default();
+    let start = Instant::now();
+    put_cursor(&mut app, Square::E2);
+    app.confirm_cell(start);
+    put_cursor(&mut app, Square::E4);
+    // Selecting a piece doesn't arm the debounce, only a played move does
+    app.confirm_cell(start + Duration::from_millis(10));
+    assert_eq!(app.game.logic.game_board.move_history.len(), 1);
+
+    // A repeat within the window is dropped, even on a square that would select a piece
+    put_cursor(&mut app, Square::E7);
+    app.confirm_cell(start + Duration::from_millis(100));
+    assert!(app.game.ui.selected_square.is_none());
+
+    // Once the window is over, input works again
+    app.confirm_cell(start + Duration::from_millis(200));
+    assert!(app.game.ui.selected_square.is_some());
+
+    // A zero window disables the debounce
+    app.move_debounce_ms = 0;
+    assert!(!app.is_move_confirm_debounced(start + Duration::from_millis(10)));
+}
+
+/// Write a fake UCI engine script that runs `on_go` for `go` and `on_stop` for `stop`
+fn fake_engine(dir: &std::path::Path, on_go: &str, on_stop: &str) -> String {
+    let script = dir.join("engine.sh");
+    std::fs::write(
+        &script,
+        format!(
+            "while read line; do case \"$line\" in go*) {} ;; stop) {} ;; esac; done\n",
+            on_go, on_stop
+        ),
+    )
+    .unwrap();
+    format!("sh {}", script.display())
+}
+
+/// Tick the deep analysis until `done` holds, failing the test after a few seconds
+fn wait_for(app: &mut App, done: impl Fn(&App) -> bool) {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !done(app) {
+        assert!(std::time::Instant::now() < deadline, "timed out");
+        app.check_deep_analysis();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_deep_analysis_shows_until_position_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let engine = fake_engine(
+        dir.path(),
+        "echo 'info depth 25 score cp -40 pv g8f6'; echo 'bestmove g8f6'",
+        ":",
+    );
+    let mut app = App {
+        chess_engine_path: Some(engine),
+        ..App::default()
+    };
+    app.submit_typed_move("e4").unwrap();
+
+    app.start_deep_analysis();
+    assert_eq!(
+        app.deep_analysis_label().as_deref(),
+        Some("🔍 Analyzing… (depth 25)")
+    );
+    wait_for(&mut app, |app| app.deep_analysis.is_none());
+    // Black's -0.40 is shown from White's side, with the move in SAN
+    assert_eq!(
+        app.deep_analysis_label().as_deref(),
+        Some("🔍 Depth 25: +0.40 Nf6")
+    );
+
+    app.submit_typed_move("Nf6").unwrap();
+    app.check_deep_analysis();
+    assert_eq!(app.deep_analysis_label(), None);
+}
+
+#[test]
+fn test_deep_analysis_stopped_when_position_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("stopped");
+    let engine = fake_engine(
+        dir.path(),
+        ":",
+        &format!("touch {}; echo 'bestmove e7e5'", marker.display()),
+    );
+    let mut app = App {
+        chess_engine_path: Some(engine),
+        ..App::default()
+    };
+    app.start_deep_analysis();
+    assert!(app.deep_analysis.is_some());
+
+    // Moving mid-search sends `stop` and drops the stale search
+    app.submit_typed_move("e4").unwrap();
+    app.check_deep_analysis();
+    assert!(app.deep_analysis.is_none());
+    assert_eq!(app.deep_analysis_label(), None);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !marker.exists() {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "engine never got stop"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_resume_prompt_skips_ended_saved_game() {
+    use chess_tui::constants::Popups;
+    use chess_tui::lichess::{OngoingGame, SavedGame};
+    use shakmaty::EnPassantMode;
+
+    let ongoing = |game_id: &str| -> OngoingGame {
+        serde_json::from_value(serde_json::json!({
+            "gameId": game_id,
+            "fullId": format!("{}full", game_id),
+            "color": "black",
+            "fen": "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+            "opponent": { "id": "bob", "username": "bob", "rating": 1500 },
+            "isMyTurn": true
+        }))
+        .unwrap()
+    };
+    let saved = SavedGame {
+        game_id: "second".to_string(),
+        color: "black".to_string(),
+    };
+
+    let mut app = App::default();
+    assert!(app.offer_resume(&saved, vec![ongoing("first"), ongoing("second")]));
+    assert_eq!(app.current_popup, Some(Popups::ResumeGame));
+    assert_eq!(
+        app.ongoing_games[app.menu_cursor as usize].game_id,
+        "second"
+    );
+
+    // The saved game is over: no prompt, the lobby stays as it was
+    let mut app = App::default();
+    assert!(!app.offer_resume(&saved, vec![ongoing("first")]));
+    assert_eq!(app.current_popup, None);
+    assert!(app.ongoing_games.is_empty());
+
+    // Automatic resume joins straight away, from the ongoing game's position
+    let mut app = App {
+        auto_resume_game: true,
+        ..App::default()
+    };
+    assert!(app.offer_resume(&saved, vec![ongoing("second")]));
+    assert_eq!(app.current_popup, None);
+    assert_eq!(
+        shakmaty::fen::Fen::from_position(
+            app.game.logic.game_board.position_ref().clone(),
+            EnPassantMode::Legal
+        )
+        .to_string(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"
+    );
+}
+
+#[test]
+fn test_side_to_move_follows_live_position() {
+    use shakmaty::Color;
+
+    let mut app = App::default();
+    assert_eq!(app.side_to_move(), Some(Color::White));
+    app.submit_typed_move("f3").unwrap();
+    assert_eq!(app.side_to_move(), Some(Color::Black));
+
+    // Browsing the history doesn't move the marker
+    app.game.logic.game_board.history_position_index = Some(0);
+    assert_eq!(app.side_to_move(), Some(Color::Black));
+    app.game.logic.game_board.history_position_index = None;
+
+    for san in ["e5", "g4", "Qh4#"] {
+        app.submit_typed_move(san).unwrap();
+    }
+    assert_eq!(app.side_to_move(), None);
+}
+
+#[test]
+fn test_promotion_dialog_picks_by_letter_or_takes_the_move_back() {
+    use chess_tui::constants::Pages;
+    use chess_tui::game_logic::coord::Coord;
+    use chess_tui::game_logic::game::GameState;
+    use chess_tui::game_logic::game_board::GameBoard;
+    use chess_tui::handler::handle_key_events;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use shakmaty::{Color, Role, Square};
+
+    let press = |app: &mut App, code: KeyCode| {
+        handle_key_events(KeyEvent::new(code, KeyModifiers::NONE), app).unwrap();
+    };
+    let push_pawn = |app: &mut App| {
+        app.game.ui.cursor_coordinates = Coord::new(1, 4);
+        press(app, KeyCode::Char(' '));
+        app.game.ui.cursor_coordinates = Coord::new(0, 4);
+        press(app, KeyCode::Char(' '));
+        assert_eq!(app.game.logic.game_state, GameState::Promotion);
+    };
+
+    let mut app = App {
+        current_page: Pages::Solo,
+        move_debounce_ms: 0,
+        ..App::default()
+    };
+    app.game.logic.game_board = GameBoard::from_fen("k7/4P3/8/8/8/8/p7/4K3 w - - 0 1").unwrap();
+
+    // Esc puts the pawn back, with White still to move
+    push_pawn(&mut app);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.game.logic.game_state, GameState::Playing);
+    assert_eq!(
+        app.game.logic.game_board.get_role_at_square(&Square::E7),
+        Some(Role::Pawn)
+    );
+    assert!(app.game.logic.game_board.move_history.is_empty());
+    assert_eq!(app.game.logic.player_turn, Color::White);
+
+    // 'n' underpromotes right away instead of quitting
+    push_pawn(&mut app);
+    press(&mut app, KeyCode::Char('n'));
+    assert!(app.running);
+    assert_eq!(app.game.logic.game_state, GameState::Playing);
+    assert_eq!(
+        app.game.logic.game_board.get_role_at_square(&Square::E8),
+        Some(Role::Knight)
+    );
+    assert_eq!(
+        app.game.logic.game_board.move_history[0].promotion(),
+        Some(Role::Knight)
+    );
+}
+
+#[test]
+fn test_manual_flip_keeps_cursor_and_selection_on_their_squares() {
+    use chess_tui::constants::Pages;
+    use chess_tui::game_logic::coord::Coord;
+    use chess_tui::handler::handle_key_events;
+    use chess_tui::utils::flip_square_if_needed;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use shakmaty::Square;
+
+    let mut app = App {
+        current_page: Pages::Solo,
+        ..App::default()
+    };
+    app.game.ui.cursor_coordinates = Coord::from_square(Square::G1);
+    app.game.ui.selected_square = Some(Square::E2);
+
+    let press = |app: &mut App| {
+        handle_key_events(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE), app).unwrap();
+    };
+    press(&mut app);
+    let board_is_flipped = app.game.logic.game_board.is_flipped;
+    assert!(board_is_flipped);
+    // Still on g1 and e2, now drawn from Black's side
+    let cursor = app.game.ui.cursor_coordinates;
+    assert_eq!(cursor, Coord::from_square(Square::G1).reverse());
+    let selected = app.game.ui.selected_square.unwrap();
+    assert_eq!(
+        flip_square_if_needed(selected, board_is_flipped),
+        Square::E2
+    );
+
+    press(&mut app);
+    assert!(!app.game.logic.game_board.is_flipped);
+    assert_eq!(
+        app.game.ui.cursor_coordinates,
+        Coord::from_square(Square::G1)
+    );
+    assert_eq!(app.game.ui.selected_square, Some(Square::E2));
+}
+
+#[test]
+fn test_move_list_jumps_and_returns_to_live() {
+    use chess_tui::constants::Pages;
+    use chess_tui::handler::handle_key_events;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use shakmaty::Color;
+
+    let mut app = App {
+        current_page: Pages::Solo,
+        ..App::default()
+    };
+    for san in "e4 e5 Nf3 Nc6 Bc4 Bc5 c3 Nf6 d4 exd4 cxd4 Bb4+ Nc3 Nxe4".split(' ') {
+        app.submit_typed_move(san).unwrap();
+    }
+    let press = |app: &mut App, code: KeyCode| {
+        handle_key_events(KeyEvent::new(code, KeyModifiers::NONE), app).unwrap();
+    };
+    let shown = |app: &App| app.game.logic.game_board.history_position_index;
+
+    press(&mut app, KeyCode::PageUp);
+    assert_eq!(shown(&app), Some(4));
+    press(&mut app, KeyCode::PageUp);
+    assert_eq!(shown(&app), Some(0));
+    press(&mut app, KeyCode::PageDown);
+    assert_eq!(shown(&app), Some(10));
+    assert_eq!(app.game.logic.player_turn, Color::White);
+    // Browsing is read-only: the game itself is untouched
+    assert_eq!(app.game.logic.game_board.move_history.len(), 14);
+
+    press(&mut app, KeyCode::End);
+    assert_eq!(shown(&app), None);
+    assert_eq!(app.game.logic.game_board.shown_index(), 14);
+    press(&mut app, KeyCode::Home);
+    assert_eq!(shown(&app), Some(0));
+    // Paging past the last move is back to live too
+    press(&mut app, KeyCode::PageDown);
+    press(&mut app, KeyCode::PageDown);
+    assert_eq!(shown(&app), None);
+    assert_eq!(app.game.logic.player_turn, Color::White);
+}
+
+#[test]
+fn test_eval_bar_follows_the_deepening_analysis() {
+    use chess_tui::auto_move::Eval;
+    use chess_tui::ui::eval_bar::{eval_label, white_share};
+
+    let dir = tempfile::tempdir().unwrap();
+    let engine = fake_engine(
+        dir.path(),
+        "echo 'info depth 10 score cp 120'; sleep 1; \
+         echo 'info depth 25 score mate 2'; echo 'bestmove g8f6'",
+        ":",
+    );
+    let mut app = App {
+        chess_engine_path: Some(engine),
+        ..App::default()
+    };
+    app.submit_typed_move("e4").unwrap();
+    assert_eq!(app.eval_bar_score(), None);
+
+    // Scores are Black's, as Black is to move, and the bar shows them for White
+    app.start_deep_analysis();
+    wait_for(&mut app, |app| app.eval_bar_score().is_some());
+    assert_eq!(app.eval_bar_score(), Some(Eval::Centipawns(-120)));
+    assert!(app.deep_analysis.is_some());
+    wait_for(&mut app, |app| app.deep_analysis.is_none());
+    let eval = app.eval_bar_score().unwrap();
+    assert_eq!(eval, Eval::Mate(-2));
+    assert_eq!(eval_label(eval), "-M2");
+    assert_eq!(white_share(eval), 0.0);
+
+    app.toggle_eval_bar();
+    assert_eq!(app.eval_bar_score(), None);
+    app.toggle_eval_bar();
+    assert!(app.deep_analysis_label().is_some());
+    // Locking takes the finished analysis and its best move off the screen
+    app.set_fair_play_lock(true);
+    assert_eq!(app.eval_bar_score(), None);
+    assert_eq!(app.deep_analysis_label(), None);
+    assert!(app.deep_analysis_result.is_none());
+
+    // Clamped to 10 pawns either way
+    assert_eq!(white_share(Eval::Centipawns(0)), 0.5);
+    assert_eq!(white_share(Eval::Centipawns(500)), 0.75);
+    assert_eq!(white_share(Eval::Centipawns(-4000)), 0.0);
+    assert_eq!(eval_label(Eval::Centipawns(35)), "+0.3");
+    assert_eq!(eval_label(Eval::Mate(3)), "M3");
+}
+
+#[test]
+fn test_clocks_run_down_for_the_side_to_move_until_the_game_ends() {
+    use chess_tui::skin::Skin;
+    use chess_tui::ui::clocks::{clock_style, format_clock};
+    use ratatui::style::Color;
+    use std::time::{Duration, Instant};
+
+    let synced = Instant::now();
+    let later = synced + Duration::from_millis(1_500);
+    let mut app = App {
+        lichess_clocks_ms: Some((180_000, 180_000)),
+        lichess_clocks_synced_at: Some(synced),
+        ..App::default()
+    };
+    // Clocks only start once both sides have moved
+    assert_eq!(app.running_clocks_ms(later), Some((180_000, 180_000)));
+    for san in ["f3", "e5"] {
+        app.submit_typed_move(san).unwrap();
+    }
+    assert_eq!(app.running_clocks_ms(later), Some((178_500, 180_000)));
+    app.submit_typed_move("g4").unwrap();
+    assert_eq!(app.running_clocks_ms(later), Some((180_000, 178_500)));
+    // A flagged clock stays at zero
+    assert_eq!(
+        app.running_clocks_ms(synced + Duration::from_secs(600)),
+        Some((180_000, 0))
+    );
+
+    // The game ending stops both clocks
+    app.submit_typed_move("Qh4#").unwrap();
+    assert_eq!(app.running_clocks_ms(later), Some((180_000, 180_000)));
+    app.lichess_clocks_synced_at = None;
+    app.lichess_clocks_ms = Some((61_000, 9_349));
+    assert_eq!(app.running_clocks_ms(later), Some((61_000, 9_349)));
+
+    assert_eq!(format_clock(61_000), "1:01");
+    assert_eq!(format_clock(10_000), "0:10");
+    assert_eq!(format_clock(9_349), "0:09.3");
+    let skin = Skin::default();
+    assert_eq!(clock_style(61_000, false, &skin).fg, Some(Color::Gray));
+    assert_eq!(clock_style(19_999, false, &skin).fg, Some(Color::Red));
+    // Under the panic threshold the side to move's clock flashes
+    assert_eq!(clock_style(19_999, true, &skin).bg, None);
+    assert_eq!(clock_style(19_499, true, &skin).bg, Some(Color::Red));
+}
+
+#[test]
+fn test_fair_play_lock_stops_a_running_analysis() {
+    let dir = tempfile::tempdir().unwrap();
+    let engine = fake_engine(dir.path(), ":", "echo 'bestmove e7e5'");
+    let mut app = App {
+        chess_engine_path: Some(engine),
+        ..App::default()
+    };
+    app.start_deep_analysis();
+    assert!(app.deep_analysis_label().is_some());
+
+    app.set_fair_play_lock(true);
+    assert!(app.deep_analysis.is_none());
+    assert_eq!(app.deep_analysis_label(), None);
+}
+
+#[test]
+fn test_end_sync_rebuilds_the_board_from_the_server_moves() {
+    use chess_tui::game_logic::game::GameState;
+    use std::sync::mpsc::channel;
+
+    // The last two moves never made it to the board
+    let mut app = App::default();
+    for san in ["f3", "e5"] {
+        app.submit_typed_move(san).unwrap();
+    }
+    let (tx, rx) = channel();
+    tx.send(Ok(
+        "[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n".to_string()
+    ))
+    .unwrap();
+    app.end_sync_receiver = Some(rx);
+    app.tick();
+
+    assert!(app.end_sync_receiver.is_none());
+    let board = &app.game.logic.game_board;
+    let sans: Vec<String> = board
+        .history()
+        .into_iter()
+        .map(|record| record.san)
+        .collect();
+    assert_eq!(sans, ["f3", "e5", "g4", "Qh4#"]);
+    assert_eq!(board.position_history.len(), board.move_history.len() + 1);
+    assert_eq!(app.game.logic.game_state, GameState::Checkmate);
+}
@@ -0,0 +1,320 @@
+use chess_tui::app::App;
+use chess_tui::auto_move::{AutoMoveController, PANIC_HYSTERESIS_SECS, PANIC_LAG_MS};
+
+#[test]
+fn test_fair_play_lock_disables_auto_move() {
+    let mut controller = AutoMoveController::new();
+    controller.set_enabled(true);
+    assert!(controller.should_auto_move(true));
+
+    controller.set_fair_play_lock(true);
+    assert!(!controller.is_enabled());
+    assert!(!controller.should_auto_move(true));
+
+    // Enabling is refused while locked
+    controller.set_enabled(true);
+    assert!(!controller.is_enabled());
+
+    controller.set_fair_play_lock(false);
+    controller.set_enabled(true);
+    assert!(controller.should_auto_move(true));
+}
+
+#[test]
+fn test_fair_play_lock_cannot_be_released_in_rated_game() {
+    let mut app = App::default();
+    app.toggle_fair_play_lock();
+    assert!(app.fair_play_lock);
+    assert!(!app.is_engine_assistance_allowed());
+
+    app.current_game_rated = true;
+    app.toggle_fair_play_lock();
+    assert!(app.fair_play_lock);
+
+    app.current_game_rated = false;
+    app.toggle_fair_play_lock();
+    assert!(!app.fair_play_lock);
+}
+
+#[test]
+fn test_reaction_delay_holds_auto_move() {
+    let mut controller = AutoMoveController::new();
+    controller.set_enabled(true);
+
+    // Off by default: the opponent's move doesn't delay anything
+    controller.on_opponent_move();
+    assert!(controller.should_auto_move(true));
+
+    controller.set_reaction_delay(60_000, 60_000);
+    controller.on_opponent_move();
+    assert!(controller.is_reacting());
+    assert!(!controller.should_auto_move(true));
+
+    controller.set_reaction_delay(0, 0);
+    assert_eq!(controller.reaction_delay(), None);
+}
+
+#[test]
+fn test_move_source_chain_falls_back() {
+    use chess_tui::auto_move::MoveSource;
+    use shakmaty::Chess;
+
+    let position = Chess::default();
+    let mut controller = AutoMoveController::new();
+
+    // Empty book and a hung engine: the random fallback still plays
+    let (uci, source) = controller.pick_move(&position, |_| None).unwrap();
+    assert_eq!(source, MoveSource::Random);
+    assert!(chess_tui::utils::parse_uci(&uci).is_ok());
+
+    // Illegal engine suggestions are skipped
+    controller.set_move_sources(vec![MoveSource::Engine]);
+    assert!(controller
+        .pick_move(&position, |_| Some("e2e5".to_string()))
+        .is_none());
+    assert_eq!(
+        controller.pick_move(&position, |_| Some("g1f3".to_string())),
+        Some(("g1f3".to_string(), MoveSource::Engine))
+    );
+
+    // The book wins when it knows the position
+    controller.set_move_sources(MoveSource::default_chain());
+    controller.add_book_move(&position, "e2e4");
+    assert_eq!(
+        controller.pick_move(&position, |_| Some("g1f3".to_string())),
+        Some(("e2e4".to_string(), MoveSource::Book))
+    );
+}
+
+#[test]
+fn test_move_source_from_str() {
+    use chess_tui::auto_move::MoveSource;
+
+    assert_eq!("Book".parse::<MoveSource>(), Ok(MoveSource::Book));
+    assert_eq!(" engine ".parse::<MoveSource>(), Ok(MoveSource::Engine));
+    assert!("oracle".parse::<MoveSource>().is_err());
+}
+
+#[test]
+fn test_seeded_rng_is_reproducible() {
+    use chess_tui::lichess_ws::LichessWebSocket;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use shakmaty::Chess;
+
+    let sri_a = LichessWebSocket::generate_sri_with(&mut StdRng::seed_from_u64(42));
+    let sri_b = LichessWebSocket::generate_sri_with(&mut StdRng::seed_from_u64(42));
+    assert_eq!(sri_a, sri_b);
+    assert_eq!(sri_a.len(), 12);
+
+    let position = Chess::default();
+    let mut a = AutoMoveController::with_seed(7);
+    let mut b = AutoMoveController::with_seed(7);
+    for _ in 0..5 {
+        assert_eq!(
+            a.pick_move(&position, |_| None),
+            b.pick_move(&position, |_| None)
+        );
+    }
+}
+
+#[test]
+fn test_ponder_hit_and_miss() {
+    let mut controller = AutoMoveController::new();
+    controller.set_engine_pondering(true);
+    assert!(controller.is_engine_pondering());
+    assert!(!controller.is_engine_calculating());
+
+    // Ponderhit turns the ponder search into our move's search
+    controller.ponder_hit();
+    assert!(!controller.is_engine_pondering());
+    assert!(controller.is_engine_calculating());
+
+    controller.set_engine_calculating(false);
+    controller.set_engine_pondering(true);
+    controller.ponder_miss();
+    assert!(!controller.is_engine_pondering());
+    assert!(!controller.is_engine_calculating());
+}
+
+#[test]
+fn test_book_only_hands_back_control() {
+    use chess_tui::auto_move::MoveSource;
+    use shakmaty::{Chess, Position, Square};
+
+    let start = Chess::default();
+    let mut controller = AutoMoveController::new();
+    controller.set_enabled(true);
+    controller.set_book_only(true, Some(1));
+    controller.add_book_move(&start, "e2e4");
+
+    // In book: the engine is never consulted
+    assert_eq!(
+        controller.pick_move(&start, |_| Some("g1f3".to_string())),
+        Some(("e2e4".to_string(), MoveSource::Book))
+    );
+    assert!(controller.is_enabled());
+    assert!(controller.take_notice().is_none());
+
+    // Past the book depth cutoff: auto-move disables itself and notifies
+    let mut later = start.clone();
+    for (from, to) in [(Square::G1, Square::F3), (Square::G8, Square::F6)] {
+        let m = later
+            .legal_moves()
+            .into_iter()
+            .find(|m| m.from() == Some(from) && m.to() == to)
+            .unwrap();
+        later.play_unchecked(&m);
+    }
+    controller.add_book_move(&later, "b1c3");
+    assert!(controller.pick_move(&later, |_| None).is_none());
+    assert!(!controller.is_enabled());
+    assert!(controller.take_notice().is_some());
+    assert!(controller.take_notice().is_none());
+}
+
+#[test]
+fn test_courtesy_resign_only_in_hopeless_positions() {
+    use chess_tui::auto_move::Eval;
+    use shakmaty::fen::Fen;
+    use shakmaty::{CastlingMode, Chess};
+
+    assert_eq!(
+        Eval::from_uci_info("info depth 12 score cp -35 nodes 1000 pv e2e4"),
+        Some(Eval::Centipawns(-35))
+    );
+    assert_eq!(
+        Eval::from_uci_info("info depth 20 score mate -3 pv a1a2"),
+        Some(Eval::Mate(-3))
+    );
+    assert_eq!(Eval::from_uci_info("info depth 1 nodes 20"), None);
+
+    let position = Chess::default();
+    let mut controller = AutoMoveController::new();
+
+    // Off by default
+    assert!(!controller.should_courtesy_resign(&position, Eval::Mate(-1)));
+
+    controller.set_courtesy_resign(Some(800));
+    assert!(controller.should_courtesy_resign(&position, Eval::Mate(-3)));
+    assert!(controller.should_courtesy_resign(&position, Eval::Centipawns(-900)));
+    assert!(!controller.should_courtesy_resign(&position, Eval::Mate(-8)));
+    assert!(!controller.should_courtesy_resign(&position, Eval::Mate(2)));
+    assert!(!controller.should_courtesy_resign(&position, Eval::Centipawns(-100)));
+
+    // King and bishop can't mate: drawish, so never resign
+    let drawish: Chess = "4k3/8/8/8/8/8/8/2B1K3 b - - 0 1"
+        .parse::<Fen>()
+        .unwrap()
+        .into_position(CastlingMode::Standard)
+        .unwrap();
+    assert!(!controller.should_courtesy_resign(&drawish, Eval::Centipawns(-900)));
+}
+
+#[test]
+fn test_engine_trait_backs_the_engine_source() {
+    use chess_tui::auto_move::{Eval, MoveSource};
+    use chess_tui::engine::{Analysis, Engine};
+    use shakmaty::Chess;
+
+    /// Plays canned moves in order and remembers the options it was given
+    struct CannedEngine {
+        moves: Vec<&'static str>,
+        options: Vec<(String, Option<String>)>,
+    }
+
+    impl Engine for CannedEngine {
+        fn analyze(&mut self, _fen: &str) -> Result<Analysis, String> {
+            if self.moves.is_empty() {
+                return Err("out of canned moves".to_string());
+            }
+            Ok(Analysis {
+                best_move: self.moves.remove(0).to_string(),
+                eval: Some(Eval::Centipawns(20)),
+            })
+        }
+
+        fn set_option(&mut self, name: &str, value: Option<&str>) -> Result<(), String> {
+            self.options
+                .push((name.to_string(), value.map(str::to_string)));
+            Ok(())
+        }
+    }
+
+    let mut engine = CannedEngine {
+        moves: vec!["g1f3", "e2e5"],
+        options: Vec::new(),
+    };
+    engine.set_option("Hash", Some("64")).unwrap();
+    assert_eq!(
+        engine.options,
+        vec![("Hash".to_string(), Some("64".to_string()))]
+    );
+    assert_eq!(engine.best_move("startpos").unwrap(), "g1f3");
+    engine.moves = vec!["d2d4", "e2e5"];
+
+    let position = Chess::default();
+    let mut controller = AutoMoveController::new();
+    assert!(!controller.has_engine());
+    controller.set_move_sources(vec![MoveSource::Engine]);
+    controller.set_engine(Box::new(engine));
+    assert_eq!(
+        controller.pick_engine_move(&position),
+        Some(("d2d4".to_string(), MoveSource::Engine))
+    );
+    // Illegal suggestions are still rejected, and a failing engine yields nothing
+    assert_eq!(controller.pick_engine_move(&position), None);
+    assert_eq!(controller.pick_engine_move(&position), None);
+}
+
+#[test]
+fn test_lag_profile_and_adaptive_margin() {
+    let mut controller = AutoMoveController::new();
+
+    // Defaults: the measured lag as is, else 20ms, or 50ms in panic mode
+    assert_eq!(controller.lag_ms(Some(80)), 80);
+    assert_eq!(controller.lag_ms(None), 20);
+    controller.set_panic_mode(true);
+    assert_eq!(controller.lag_ms(None), PANIC_LAG_MS);
+
+    controller.set_lag_profile(90, 120);
+    controller.set_adaptive_lag(Some(15));
+    assert_eq!(controller.lag_ms(Some(80)), 95);
+    assert_eq!(controller.lag_ms(None), 120);
+    controller.set_panic_mode(false);
+    assert_eq!(controller.lag_ms(None), 90);
+
+    // Without adaptive lag the measurement is ignored
+    controller.set_adaptive_lag(None);
+    assert_eq!(controller.lag_ms(Some(80)), 90);
+}
+
+#[test]
+fn test_low_clock_enters_and_leaves_panic_mode() {
+    let mut controller = AutoMoveController::new();
+    // No threshold: the clock alone never panics
+    controller.update_clock(1.0);
+    assert!(!controller.is_panic_mode());
+
+    controller.set_panic_threshold(10.0);
+    controller.update_clock(12.0);
+    assert!(!controller.is_panic_mode());
+    controller.update_clock(9.5);
+    assert!(controller.is_panic_mode());
+    assert_eq!(controller.lag_ms(None), PANIC_LAG_MS);
+    // An increment back just over the threshold isn't enough to leave
+    controller.update_clock(10.0 + PANIC_HYSTERESIS_SECS);
+    assert!(controller.is_panic_mode());
+    controller.update_clock(10.5 + PANIC_HYSTERESIS_SECS);
+    assert!(!controller.is_panic_mode());
+
+    // Manual panic mode overrides a healthy clock
+    controller.set_panic_mode(true);
+    controller.update_clock(60.0);
+    assert!(controller.is_panic_mode());
+    controller.set_panic_mode(false);
+    controller.update_clock(5.0);
+    assert!(controller.is_panic_mode());
+    controller.set_panic_threshold(0.0);
+    assert!(!controller.is_panic_mode());
+}
@@ -0,0 +1,10 @@
+use chess_tui::game_logic::bot::Bot;
+
+#[test]
+fn test_engine_info_reports_missing_binary() {
+    let err = Bot::engine_info("/nonexistent/chess-engine").unwrap_err();
+    assert!(err.contains("Failed to start engine"));
+
+    let err = Bot::engine_info("  ").unwrap_err();
+    assert_eq!(err, "No engine path configured");
+}
@@ -0,0 +1,60 @@
+use chess_tui::constants::{data_dir, set_data_dir};
+use chess_tui::lichess_auth::LichessSession;
+use std::path::PathBuf;
+
+#[test]
+fn test_data_dir_override_routes_all_paths() {
+    // Kept alive until the end, so every check below runs on a fresh directory
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path().to_path_buf();
+    set_data_dir(dir.clone()).unwrap();
+
+    assert_eq!(data_dir().unwrap(), dir);
+    assert_eq!(
+        LichessSession::session_path().unwrap(),
+        dir.join("lichess_session.json")
+    );
+
+    // The override can only be set once
+    assert!(set_data_dir(PathBuf::from("/elsewhere")).is_err());
+
+    // Logging out removes the stored session, and succeeds when nothing is stored
+    let session_file = dir.join("lichess_session.json");
+    assert!(!session_file.exists());
+    LichessSession::new("cookie".to_string(), None, Some("bob".to_string()))
+        .save()
+        .unwrap();
+    assert!(LichessSession::load_or_create().is_ok());
+    LichessSession::logout().unwrap();
+    assert!(!session_file.exists());
+    assert!(LichessSession::load_or_create().is_err());
+    LichessSession::logout().unwrap();
+
+    // Named profiles sit next to the default session, which keeps its old file
+    assert_eq!(
+        LichessSession::session_path_for("alt").unwrap(),
+        dir.join("lichess_session_alt.json")
+    );
+    assert!(LichessSession::session_path_for("../x").is_err());
+    // The fresh directory has no profile until one is saved
+    assert!(LichessSession::list_profiles().is_empty());
+    LichessSession::new("main".to_string(), None, None)
+        .save()
+        .unwrap();
+    let mut alt = LichessSession::from_token("lip_alt".to_string());
+    alt.profile = Some("alt".to_string());
+    alt.save().unwrap();
+    assert_eq!(LichessSession::list_profiles(), ["alt", "default"]);
+    let loaded = LichessSession::load_or_create_profile("alt").unwrap();
+    assert_eq!(loaded.profile(), "alt");
+    assert_eq!(loaded.token.as_deref(), Some("lip_alt"));
+    assert_eq!(
+        LichessSession::load_or_create_profile("default")
+            .unwrap()
+            .session_id,
+        "main"
+    );
+    LichessSession::logout_profile("alt").unwrap();
+    LichessSession::logout().unwrap();
+    assert!(LichessSession::list_profiles().is_empty());
+}
@@ -0,0 +1,35 @@
+use chess_tui::app::App;
+use chess_tui::diagnostics::{build_report, redact, REDACTED};
+use chess_tui::lichess_auth::LichessSession;
+
+#[test]
+fn test_diagnostics_report_redacts_secrets() {
+    let app = App {
+        lichess_token: Some("lip_secret_token".to_string()),
+        lichess_session: Some(LichessSession::new(
+            "secret_session_id".to_string(),
+            Some("secret_csrf".to_string()),
+            Some("alice".to_string()),
+        )),
+        ..App::default()
+    };
+
+    let report = build_report(&app);
+    assert!(report.starts_with(&format!("chess-tui {}", env!("CARGO_PKG_VERSION"))));
+    assert!(report.contains("kind = token + session"));
+    assert!(report.contains(&app.game.logic.game_board.fen_position()));
+    assert!(report.contains("websocket = not connected"));
+    for secret in ["lip_secret_token", "secret_session_id", "secret_csrf"] {
+        assert!(!report.contains(secret), "{} leaked", secret);
+    }
+    assert!(report.contains(REDACTED));
+}
+
+#[test]
+fn test_redact_replaces_every_occurrence() {
+    let text = r#"{"sid":"abc","again":"abc"}"#;
+    assert_eq!(
+        redact(text, &["abc".to_string(), String::new()]),
+        r#"{"sid":"<redacted>","again":"<redacted>"}"#
+    );
+}
@@ -0,0 +1,24 @@
+use chess_tui::engine::{movetime, LAG_BUFFER, MAX_CLOCK_FRACTION, MIN_MOVETIME};
+use std::time::Duration;
+
+#[test]
+fn test_movetime_bounded_by_low_clock() {
+    // Plenty of time: a share of the clock plus most of the increment
+    assert_eq!(
+        movetime(Duration::from_secs(60), Duration::from_secs(2)),
+        Duration::from_millis(3_500)
+    );
+
+    // 2s left with a 5s increment: the budget would flag us, so it's capped
+    let remaining = Duration::from_secs(2);
+    let capped = movetime(remaining, Duration::from_secs(5));
+    assert_eq!(capped, remaining.mul_f64(MAX_CLOCK_FRACTION) - LAG_BUFFER);
+    assert!(capped < remaining);
+
+    // Nearly out of time: never below the floor, even past the lag buffer
+    assert_eq!(
+        movetime(Duration::from_millis(400), Duration::ZERO),
+        MIN_MOVETIME
+    );
+    assert_eq!(movetime(Duration::ZERO, Duration::ZERO), MIN_MOVETIME);
+}
@@ -0,0 +1,44 @@
+use chess_tui::auto_move::{load_eval_log, AutoMoveController, Eval, EvalRecord};
+use chess_tui::constants::set_data_dir;
+use shakmaty::{Chess, Position, Square};
+
+#[test]
+fn test_engine_evals_are_logged_per_game() {
+    let dir = tempfile::tempdir().unwrap();
+    set_data_dir(dir.path().to_path_buf()).unwrap();
+
+    let mut controller = AutoMoveController::new();
+    let start = Chess::default();
+
+    // Nothing is written until a game is attached
+    controller.record_eval(&start, "e2e4", Eval::Centipawns(30));
+    assert!(load_eval_log("evalgame").is_err());
+
+    controller.set_eval_log(Some("evalgame".to_string()));
+    controller.record_eval(&start, "e2e4", Eval::Centipawns(30));
+    let mut after_e4 = start.clone();
+    let e4 = after_e4
+        .legal_moves()
+        .into_iter()
+        .find(|m| m.from() == Some(Square::E2) && m.to() == Square::E4)
+        .unwrap();
+    after_e4.play_unchecked(&e4);
+    controller.record_eval(&after_e4, "e7e5", Eval::Mate(-4));
+
+    assert_eq!(
+        load_eval_log("evalgame").unwrap(),
+        vec![
+            EvalRecord {
+                ply: 0,
+                best_move: "e2e4".to_string(),
+                eval: Eval::Centipawns(30),
+            },
+            EvalRecord {
+                ply: 1,
+                best_move: "e7e5".to_string(),
+                eval: Eval::Mate(-4),
+            },
+        ]
+    );
+    assert!(dir.path().join("games/evalgame.evals.jsonl").exists());
+}
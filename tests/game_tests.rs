@@ -140,4 +140,40 @@ mod tests {
         assert_eq!(game.logic.game_board.position_history.len(), 2);
         assert_eq!(game.logic.game_board.history_position_index, None);
     }
+
+    #[test]
+    fn test_invalid_selection_is_rejected() {
+        use chess_tui::game_logic::coord::Coord;
+
+        let mut game = Game::default();
+
+        // Empty square (e4): nothing gets selected, the cell flashes instead
+        game.ui.cursor_coordinates = Coord::new(4, 4);
+        game.select_cell();
+        assert!(!game.ui.is_cell_selected());
+        assert_eq!(
+            game.ui.selection_error.map(|(c, _)| c),
+            Some(Coord::new(4, 4))
+        );
+
+        // Opponent piece (e7) on white's turn
+        game.ui.cursor_coordinates = Coord::new(1, 4);
+        game.select_cell();
+        assert!(!game.ui.is_cell_selected());
+
+        // Own piece without a legal move (e1 king)
+        assert!(!game.can_select_square(Square::E1, None));
+        // Own piece that can move (e2 pawn)
+        assert!(game.can_select_square(Square::E2, None));
+
+        // The flash fades after a few ticks
+        for _ in 0..5 {
+            game.ui.update_cursor_blink();
+        }
+        assert!(game.ui.selection_error.is_none());
+
+        // Edit/analysis modes may pick any square
+        game.ui.free_selection = true;
+        assert!(game.can_select_square(Square::E4, None));
+    }
 }
@@ -0,0 +1,735 @@
+use chess_tui::app::App;
+use chess_tui::constants::Popups;
+use chess_tui::lichess::{
+    answer_challenge_at, create_seek_at, export_pgn_at, list_challenges_at, ongoing_games_at,
+    stream_events_at, tv_feed_position, validate_with_retry, GameMeta, LichessEvent,
+    PgnExportOptions, SeekParams, TvFeedFrame, UserProfile, ValidationError,
+};
+use chess_tui::lichess_auth::{
+    oauth_login_with, pkce_challenge, AuthBackend, LichessSession, OAuthEndpoints,
+};
+use chess_tui::pgn::parse_pgn;
+use shakmaty::{Color, Position};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_parse_tv_featured_frame() {
+    let line = r#"{"t":"featured","d":{"id":"abcd1234","orientation":"black","players":[{"color":"white","user":{"name":"Alice","title":"GM"},"rating":2800,"seconds":60},{"color":"black","user":{"name":"Bob"},"rating":2750,"seconds":58}],"fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"}}"#;
+    let frame: TvFeedFrame = serde_json::from_str(line).unwrap();
+    match frame {
+        TvFeedFrame::Featured(featured) => {
+            assert_eq!(featured.id, "abcd1234");
+            assert_eq!(featured.orientation, "black");
+            assert_eq!(featured.players_label(), "GM Alice (2800) vs Bob (2750)");
+        }
+        _ => panic!("Expected a featured frame"),
+    }
+}
+
+#[test]
+fn test_parse_tv_fen_frame() {
+    let line = r#"{"t":"fen","d":{"fen":"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR","lm":"e2e4","wc":60,"bc":60}}"#;
+    let frame: TvFeedFrame = serde_json::from_str(line).unwrap();
+    match frame {
+        TvFeedFrame::Fen(fen) => {
+            assert_eq!(fen.lm.as_deref(), Some("e2e4"));
+            // The side to move is deduced from the piece that just moved
+            let position = tv_feed_position(&fen.fen, fen.lm.as_deref()).unwrap();
+            assert_eq!(position.turn(), Color::Black);
+        }
+        _ => panic!("Expected a fen frame"),
+    }
+}
+
+#[test]
+fn test_tv_feed_position_rejects_garbage() {
+    assert!(tv_feed_position("not a fen", None).is_none());
+    assert!(tv_feed_position("8/8/8/8", None).is_none());
+}
+
+#[test]
+fn test_spectator_orientation_modes() {
+    use chess_tui::app::App;
+    use chess_tui::constants::SpectatorOrientation;
+    use std::sync::mpsc::channel;
+
+    let featured = r#"{"t":"featured","d":{"id":"abcd1234","orientation":"black","fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"}}"#;
+    let white_moved =
+        r#"{"t":"fen","d":{"fen":"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR","lm":"e2e4"}}"#;
+    let black_moved =
+        r#"{"t":"fen","d":{"fen":"rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR","lm":"e7e5"}}"#;
+
+    // Feed frames through the TV receiver and report the orientation after each batch
+    let orientations = |mode: SpectatorOrientation| {
+        let (tx, rx) = channel();
+        let mut app = App {
+            spectator_orientation: mode,
+            tv_feed_receiver: Some(rx),
+            ..App::default()
+        };
+        [featured, white_moved, black_moved]
+            .iter()
+            .map(|line| {
+                tx.send(serde_json::from_str::<TvFeedFrame>(line).unwrap())
+                    .unwrap();
+                app.check_lichess_tv();
+                app.game.logic.game_board.is_flipped
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        orientations(SpectatorOrientation::Featured),
+        vec![true, true, true]
+    );
+    assert_eq!(
+        orientations(SpectatorOrientation::FixedWhite),
+        vec![false, false, false]
+    );
+    assert_eq!(
+        orientations(SpectatorOrientation::FixedBlack),
+        vec![true, true, true]
+    );
+    assert_eq!(
+        orientations(SpectatorOrientation::SideToMove),
+        vec![false, true, false]
+    );
+    assert_eq!(
+        "side_to_move".parse::<SpectatorOrientation>(),
+        Ok(SpectatorOrientation::SideToMove)
+    );
+}
+
+#[test]
+fn test_game_meta_panel_lines() {
+    // Only the id known: every other field gets a fallback
+    let mut meta = GameMeta::for_game("abcd1234");
+    assert_eq!(
+        meta.lines(),
+        ["vs Anonymous", "? • ?", "Standard", "#abcd1234"]
+    );
+
+    // A socket snapshot fills in what it knows, keeping the id
+    let d = serde_json::json!({
+        "game": {"rated": true, "variant": {"name": "Chess960"}, "speed": "blitz"},
+        "clock": {"initial": 180, "increment": 2, "white": 170.5, "black": 180},
+        "opponent": {"user": {"username": "bob"}, "rating": 1720}
+    });
+    meta.merge(GameMeta::from_socket(&d));
+    assert_eq!(
+        meta.lines(),
+        ["vs bob (1720)", "3+2 • Rated", "Chess960", "#abcd1234"]
+    );
+
+    // An anonymous opponent in a casual half-minute game
+    let d = serde_json::json!({
+        "game": {"id": "efgh5678", "rated": false},
+        "clock": {"initial": 30, "increment": 0}
+    });
+    let anonymous = GameMeta::from_socket(&d);
+    assert_eq!(
+        anonymous.lines(),
+        ["vs Anonymous", "½+0 • Casual", "Standard", "#efgh5678"]
+    );
+
+    let correspondence = GameMeta {
+        speed: Some("correspondence".to_string()),
+        ..GameMeta::default()
+    };
+    assert_eq!(correspondence.time_control_label(), "Correspondence");
+}
+
+#[test]
+fn test_token_validation_retries_only_transient_errors() {
+    // Network blips are retried until one attempt gets through
+    let mut calls = 0;
+    let result = validate_with_retry(3, Duration::ZERO, || {
+        calls += 1;
+        if calls < 3 {
+            Err(ValidationError::Transient("connection reset".to_string()))
+        } else {
+            Ok(calls)
+        }
+    });
+    assert_eq!(result, Ok(3));
+
+    // ...and given up on after the last attempt
+    let mut calls = 0;
+    let result: Result<(), _> = validate_with_retry(3, Duration::ZERO, || {
+        calls += 1;
+        Err(ValidationError::Transient("timed out".to_string()))
+    });
+    assert_eq!(calls, 3);
+    assert!(matches!(result, Err(ValidationError::Transient(_))));
+
+    // A 401 won't get better by retrying
+    let mut calls = 0;
+    let result: Result<(), _> = validate_with_retry(3, Duration::ZERO, || {
+        calls += 1;
+        Err(ValidationError::Unauthorized)
+    });
+    assert_eq!((calls, result), (1, Err(ValidationError::Unauthorized)));
+}
+
+#[test]
+fn test_startup_token_validation_outcomes() {
+    let token = Some("lip_token".to_string());
+
+    let mut app = App {
+        lichess_token: token.clone(),
+        ..App::default()
+    };
+    let profile: UserProfile =
+        serde_json::from_str(r#"{"id":"alice","username":"Alice"}"#).unwrap();
+    app.apply_token_validation(Ok(profile));
+    assert_eq!(app.lichess_user_profile.unwrap().username, "Alice");
+    assert_eq!(app.current_popup, None);
+
+    // Offline: keep the token, just warn
+    let mut app = App {
+        lichess_token: token.clone(),
+        ..App::default()
+    };
+    app.apply_token_validation(Err(ValidationError::Transient("timed out".to_string())));
+    assert_eq!(app.lichess_token, token);
+    assert_eq!(app.current_popup, Some(Popups::Error));
+
+    // Rejected: drop it so the Lichess menu asks for a new one
+    let mut app = App {
+        lichess_token: token.clone(),
+        ..App::default()
+    };
+    app.apply_token_validation(Err(ValidationError::Unauthorized));
+    assert_eq!(app.lichess_token, None);
+    assert_eq!(app.current_popup, Some(Popups::Error));
+
+    // Rate limited: nothing wrong with the token, so it's kept
+    let mut app = App {
+        lichess_token: token,
+        ..App::default()
+    };
+    app.apply_token_validation(Err(ValidationError::RateLimited));
+    assert_eq!(app.lichess_token.as_deref(), Some("lip_token"));
+    assert!(app.error_message.unwrap().contains("try again later"));
+}
+
+#[test]
+fn test_auth_failures_map_to_distinct_errors() {
+    use reqwest::StatusCode;
+
+    let expired = ValidationError::from_status(StatusCode::UNAUTHORIZED);
+    let closed = ValidationError::from_status(StatusCode::FORBIDDEN);
+    let limited = ValidationError::from_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(expired, ValidationError::Unauthorized);
+    assert_eq!(closed, ValidationError::AccountClosed);
+    assert_eq!(limited, ValidationError::RateLimited);
+    assert!(matches!(
+        ValidationError::from_status(StatusCode::BAD_GATEWAY),
+        ValidationError::Transient(_)
+    ));
+
+    // Only an expired token is fixed by logging in again
+    assert!(expired.needs_relogin());
+    assert!(!closed.needs_relogin());
+    assert!(expired.to_string().contains("re-login"));
+    assert!(closed.to_string().contains("closed"));
+    assert!(limited.to_string().contains("rate limit"));
+
+    // None of them are retried
+    let mut calls = 0;
+    let result: Result<(), _> = validate_with_retry(3, Duration::ZERO, || {
+        calls += 1;
+        Err(ValidationError::RateLimited)
+    });
+    assert_eq!((calls, result), (1, Err(ValidationError::RateLimited)));
+}
+
+/// Answer one HTTP request with `status` and `body`, returning the request's headers
+fn spawn_http_responder(
+    status: &'static str,
+    body: &'static str,
+) -> (u16, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                break;
+            }
+            request.push_str(&line);
+        }
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+        request.to_lowercase()
+    });
+    (port, handle)
+}
+
+#[test]
+fn test_session_validation_against_account_endpoint() {
+    let (port, server) = spawn_http_responder("200 OK", r#"{"id":"bob","username":"Bob"}"#);
+    let mut session = LichessSession::new("cookie123".to_string(), Some("csrf".to_string()), None);
+    let url = format!("http://127.0.0.1:{}/api/account", port);
+    assert!(session.validate_against(&url).unwrap());
+    assert_eq!(session.username.as_deref(), Some("Bob"));
+    let request = server.join().unwrap();
+    assert!(request.contains("cookie: lila2=cookie123"), "{}", request);
+    assert!(request.contains("x-csrf-token: csrf"), "{}", request);
+
+    // A rejected cookie asks for a new login, a server error isn't taken as valid
+    let (port, server) = spawn_http_responder("401 Unauthorized", "{}");
+    let url = format!("http://127.0.0.1:{}/api/account", port);
+    assert!(!session.validate_against(&url).unwrap());
+    server.join().unwrap();
+    let (port, server) = spawn_http_responder("500 Internal Server Error", "{}");
+    let url = format!("http://127.0.0.1:{}/api/account", port);
+    assert!(session.validate_against(&url).is_err());
+    server.join().unwrap();
+
+    // Nothing listening is an error too
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let url = format!("http://127.0.0.1:{}/api/account", port);
+    assert!(session.validate_against(&url).is_err());
+}
+
+#[test]
+fn test_token_session_authenticates_with_bearer() {
+    let mut session = LichessSession::from_token("lip_abc".to_string());
+    assert_eq!(
+        session.auth_header(),
+        ("Authorization", "Bearer lip_abc".to_string())
+    );
+    let (port, server) = spawn_http_responder("200 OK", r#"{"id":"bob","username":"Bob"}"#);
+    let url = format!("http://127.0.0.1:{}/api/account", port);
+    assert!(session.validate_against(&url).unwrap());
+    let request = server.join().unwrap();
+    assert!(
+        request.contains("authorization: bearer lip_abc"),
+        "{}",
+        request
+    );
+    assert!(!request.contains("cookie:"), "{}", request);
+
+    // Stored with the token, and sessions saved before tokens still load
+    let json = serde_json::to_string(&session).unwrap();
+    let loaded: LichessSession = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.token.as_deref(), Some("lip_abc"));
+    let old: LichessSession =
+        serde_json::from_str(r#"{"session_id":"abc","csrf_token":null,"username":"Bob"}"#).unwrap();
+    assert_eq!(old.token, None);
+    assert_eq!(old.auth_header(), ("Cookie", "lila2=abc".to_string()));
+}
+
+#[test]
+fn test_auth_backend_parses_and_reaches_the_config() {
+    assert_eq!("Keyring ".parse(), Ok(AuthBackend::Keyring));
+    assert_eq!("file".parse(), Ok(AuthBackend::File));
+    assert!("vault".parse::<AuthBackend>().is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let app = App {
+        auth_backend: AuthBackend::Keyring,
+        ..App::default()
+    };
+    let config = app.effective_config(&dir.path().join("config.toml"));
+    assert_eq!(config.auth_backend.as_deref(), Some("keyring"));
+}
+
+#[test]
+fn test_pkce_challenge_matches_rfc_example() {
+    // RFC 7636, appendix B
+    assert_eq!(
+        pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+}
+
+/// Play the browser: follow the authorize URL's redirect with `code`, or with
+/// "access_denied" if None
+fn redirect_back(authorize_url: &str, code: Option<&str>) -> thread::JoinHandle<()> {
+    let url = reqwest::Url::parse(authorize_url).unwrap();
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+            .unwrap()
+    };
+    assert_eq!(param("code_challenge_method"), "S256");
+    let mut redirect = reqwest::Url::parse(&param("redirect_uri")).unwrap();
+    match code {
+        Some(code) => redirect.query_pairs_mut().append_pair("code", code),
+        None => redirect
+            .query_pairs_mut()
+            .append_pair("error", "access_denied"),
+    };
+    redirect
+        .query_pairs_mut()
+        .append_pair("state", &param("state"));
+    thread::spawn(move || {
+        let body = reqwest::blocking::get(redirect).unwrap().text().unwrap();
+        assert!(body.contains("close this tab"), "{}", body);
+    })
+}
+
+#[test]
+fn test_oauth_login_trades_the_code_for_a_token() {
+    let (port, token_server) = spawn_http_responder(
+        "200 OK",
+        r#"{"token_type":"Bearer","access_token":"lio_new","expires_in":31536000}"#,
+    );
+    let endpoints = OAuthEndpoints {
+        authorize_url: "https://lichess.example/oauth".to_string(),
+        token_url: format!("http://127.0.0.1:{}/api/token", port),
+        client_id: "chess-tui".to_string(),
+    };
+    let mut browser = None;
+    let session = oauth_login_with(&endpoints, Duration::from_secs(10), |url| {
+        browser = Some(redirect_back(url, Some("abc")));
+    })
+    .unwrap();
+    browser.unwrap().join().unwrap();
+    assert_eq!(session.token.as_deref(), Some("lio_new"));
+    assert!(session.expires_at.is_some());
+    assert!(!session.is_expired());
+    assert!(token_server.join().unwrap().starts_with("post /api/token"));
+
+    // Denying access in the browser, or never coming back, fails the login
+    let mut browser = None;
+    assert!(
+        oauth_login_with(&endpoints, Duration::from_secs(10), |url| {
+            browser = Some(redirect_back(url, None));
+        })
+        .is_err()
+    );
+    browser.unwrap().join().unwrap();
+    assert!(oauth_login_with(&endpoints, Duration::from_millis(200), |_| {}).is_err());
+}
+
+#[test]
+fn test_expired_session_is_rejected_without_asking() {
+    let mut session = LichessSession::from_token("lip_old".to_string());
+    assert!(!session.is_expired());
+    session.expires_at = Some(1);
+    assert!(session.is_expired());
+    // Nothing listens there, so any request would fail
+    assert!(!session
+        .validate_against("http://127.0.0.1:9/api/account")
+        .unwrap());
+
+    // Session files from before expiry tracking never expire
+    let old: LichessSession =
+        serde_json::from_str(r#"{"session_id":"abc","csrf_token":null,"username":"Bob"}"#).unwrap();
+    assert_eq!(old.expires_at, None);
+    assert!(!old.is_expired());
+}
+
+#[test]
+fn test_ongoing_games_for_a_session() {
+    let session = LichessSession::from_token("lip_abc".to_string());
+    let (port, server) = spawn_http_responder(
+        "200 OK",
+        r#"{"nowPlaying":[{"gameId":"abcd1234","fullId":"abcd1234wxyz","color":"black",
+        "fen":"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+        "opponent":{"id":"alice","username":"Alice","rating":1500},"isMyTurn":true}]}"#,
+    );
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    let games = ongoing_games_at(&api_url, &session).unwrap();
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].game_id, "abcd1234");
+    assert_eq!(games[0].color, "black");
+    assert_eq!(games[0].opponent.username, "Alice");
+    assert!(games[0].is_my_turn);
+    let request = server.join().unwrap();
+    assert!(
+        request.starts_with("get /api/account/playing"),
+        "{}",
+        request
+    );
+    assert!(
+        request.contains("authorization: bearer lip_abc"),
+        "{}",
+        request
+    );
+
+    // No games is an empty list, a rejected token asks for a new login
+    let (port, server) = spawn_http_responder("200 OK", r#"{"nowPlaying":[]}"#);
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    assert!(ongoing_games_at(&api_url, &session).unwrap().is_empty());
+    server.join().unwrap();
+    let (port, server) = spawn_http_responder("401 Unauthorized", "{}");
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    let error = ongoing_games_at(&api_url, &session).unwrap_err();
+    assert!(error.needs_relogin());
+    server.join().unwrap();
+}
+
+/// Lichess stand-in for seeks: holds `/api/board/seek` open with keep-alives and
+/// lists a new game once the seek is matched, which happens if `matched`.
+/// Returns whether the client dropped the seek connection itself.
+fn spawn_seek_server(matched: bool) -> (u16, thread::JoinHandle<bool>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let seeking = Arc::new(AtomicBool::new(false));
+        let mut seek_thread: Option<thread::JoinHandle<bool>> = None;
+        listener.set_nonblocking(true).unwrap();
+        loop {
+            // Unmatched seeks end when the client lets go
+            if !matched && seek_thread.as_ref().is_some_and(|seek| seek.is_finished()) {
+                return seek_thread.unwrap().join().unwrap();
+            }
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(_) => {
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+            };
+            stream.set_nonblocking(false).unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let mut stream = reader.into_inner();
+
+            if request_line.starts_with("POST /api/board/seek") {
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                assert_eq!(body["time"], 3);
+                assert_eq!(body["color"], "white");
+                seeking.store(true, Ordering::Relaxed);
+                seek_thread = Some(thread::spawn(move || {
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n")
+                        .unwrap();
+                    for _ in 0..100 {
+                        if stream.write_all(b"1\r\n\n\r\n").is_err() {
+                            return true;
+                        }
+                        thread::sleep(Duration::from_millis(50));
+                        if matched {
+                            // Matched: Lichess ends the seek stream
+                            let _ = stream.write_all(b"0\r\n\r\n");
+                            return false;
+                        }
+                    }
+                    false
+                }));
+                continue;
+            }
+
+            let game_listed = matched && seeking.load(Ordering::Relaxed);
+            let body = if game_listed {
+                r#"{"nowPlaying":[{"gameId":"seek1234","fullId":"seek1234abcd","color":"white",
+                "fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "opponent":{"username":"Alice"},"isMyTurn":true}]}"#
+            } else {
+                r#"{"nowPlaying":[]}"#
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            if game_listed {
+                return seek_thread.unwrap().join().unwrap();
+            }
+        }
+    });
+    (port, handle)
+}
+
+#[test]
+fn test_seek_returns_the_matched_game() {
+    let session = LichessSession::from_token("lip_abc".to_string());
+    let params = SeekParams {
+        color: Some(Color::White),
+        ..SeekParams::new(3, 2)
+    };
+
+    let (port, server) = spawn_seek_server(true);
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    let cancel = Arc::new(AtomicBool::new(false));
+    assert_eq!(
+        create_seek_at(&api_url, &session, &params, cancel).unwrap(),
+        "seek1234"
+    );
+    assert!(!server.join().unwrap());
+
+    // Cancelling drops the seek connection
+    let (port, server) = spawn_seek_server(false);
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    let cancel = Arc::new(AtomicBool::new(false));
+    let canceller = {
+        let cancel = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            cancel.store(true, Ordering::Relaxed);
+        })
+    };
+    assert!(create_seek_at(&api_url, &session, &params, cancel).is_err());
+    canceller.join().unwrap();
+    assert!(server.join().unwrap());
+}
+
+#[test]
+fn test_incoming_challenges_and_answers() {
+    let session = LichessSession::from_token("lip_abc".to_string());
+    let (port, server) = spawn_http_responder(
+        "200 OK",
+        r#"{"in":[{"id":"chal1234","status":"created","rated":true,"speed":"blitz",
+        "challenger":{"id":"alice","name":"Alice","rating":1820,"title":"FM"},
+        "timeControl":{"type":"clock","limit":180,"increment":2,"show":"3+2"},
+        "variant":{"key":"standard","name":"Standard"},"color":"random"}],"out":[]}"#,
+    );
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    let challenges = list_challenges_at(&api_url, &session).unwrap();
+    assert!(server.join().unwrap().starts_with("get /api/challenge "));
+    assert_eq!(challenges.len(), 1);
+    let meta = challenges[0].meta();
+    assert_eq!(meta.time_control_label(), "3+2");
+    assert_eq!(meta.opponent_label(), "FM Alice (1820)");
+
+    let (port, server) = spawn_http_responder("200 OK", r#"{"ok":true}"#);
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    answer_challenge_at(&api_url, &session, "chal1234", "accept").unwrap();
+    assert!(server
+        .join()
+        .unwrap()
+        .starts_with("post /api/challenge/chal1234/accept"));
+
+    // Withdrawn before we got to it
+    let (port, server) = spawn_http_responder("404 Not Found", "{}");
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    assert_eq!(
+        answer_challenge_at(&api_url, &session, "chal1234", "decline"),
+        Err(ValidationError::NotFound)
+    );
+    server.join().unwrap();
+}
+
+#[test]
+fn test_event_stream_parses_known_events_and_hangs_up() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                break;
+            }
+        }
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\n\r\n")
+            .unwrap();
+        let game = r#"{"type":"gameStart","game":{"gameId":"abcd1234","fullId":"abcd1234wxyz","color":"white","fen":"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1","opponent":{"id":"bob","username":"Bob","rating":1500},"isMyTurn":true}}"#;
+        let lines = [
+            game.to_string(),
+            String::new(),
+            r#"{"type":"somethingNew"}"#.to_string(),
+            r#"{"type":"challengeCanceled","challenge":{"id":"chal1234","rated":false,"speed":"blitz","challenger":{"name":"Alice"},"timeControl":{"type":"clock","limit":180,"increment":0},"variant":{"key":"standard","name":"Standard"}}}"#.to_string(),
+        ];
+        let mut write_line = |line: &str| {
+            let chunk = format!("{}\n", line);
+            write!(stream, "{:x}\r\n{}\r\n", chunk.len(), chunk)?;
+            stream.flush()
+        };
+        for line in &lines {
+            write_line(line).unwrap();
+        }
+        // Keeps sending games until the client hangs up
+        for _ in 0..500 {
+            if write_line(game).is_err() {
+                return (request_line, true);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        (request_line, false)
+    });
+
+    let session = LichessSession::from_token("lip_abc".to_string());
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    let events = stream_events_at(&api_url, &session).unwrap();
+    match events.recv_timeout(Duration::from_secs(5)).unwrap() {
+        LichessEvent::GameStart { game } => assert_eq!(game.game_id, "abcd1234"),
+        other => panic!("Expected a game start, got {:?}", other),
+    }
+    // The unknown event is skipped
+    match events.recv_timeout(Duration::from_secs(5)).unwrap() {
+        LichessEvent::ChallengeCanceled { challenge } => assert_eq!(challenge.id, "chal1234"),
+        other => panic!("Expected a canceled challenge, got {:?}", other),
+    }
+    drop(events);
+
+    let (request_line, hung_up) = server.join().unwrap();
+    assert!(request_line.starts_with("GET /api/stream/event "));
+    assert!(hung_up);
+}
+
+#[test]
+fn test_export_game_pgn() {
+    let session = LichessSession::from_token("lip_abc".to_string());
+    let (port, server) = spawn_http_responder(
+        "200 OK",
+        "[Event \"Rated blitz game\"]\n[Result \"*\"]\n\n1. e4 { [%clk 0:03:00] } *\n",
+    );
+    let site_url = format!("http://127.0.0.1:{}", port);
+    let pgn = export_pgn_at(&site_url, &session, "abcd1234", PgnExportOptions::default()).unwrap();
+    let request = server.join().unwrap();
+    assert!(
+        request
+            .starts_with("get /game/export/abcd1234.pgn?pgninjson=false&clocks=true&evals=false "),
+        "{}",
+        request
+    );
+    assert!(
+        request.contains("authorization: bearer lip_abc"),
+        "{}",
+        request
+    );
+    // Still in progress: the moves so far, with no result yet
+    assert_eq!(parse_pgn(&pgn).unwrap().result.as_deref(), Some("*"));
+
+    let (port, server) = spawn_http_responder("404 Not Found", "");
+    let site_url = format!("http://127.0.0.1:{}", port);
+    assert_eq!(
+        export_pgn_at(&site_url, &session, "missing1", PgnExportOptions::default()),
+        Err(ValidationError::NotFound)
+    );
+    server.join().unwrap();
+}
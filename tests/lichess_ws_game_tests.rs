@@ -0,0 +1,1310 @@
+use chess_tui::app::App;
+use chess_tui::auto_move::{AutoMoveController, AutoMoveStats, MoveSource};
+use chess_tui::constants::Popups;
+use chess_tui::game_logic::game::GameState;
+use chess_tui::lichess_ws::{
+    game_socket_url, jittered, reconnect_delay, sanitize_chat, takeback_plies, AckScheme,
+    CrowdData, EndData, GameEvent, LichessMessage, LichessWebSocket, ReconnectError,
+    RECONNECT_BACKOFF_MAX,
+};
+use shakmaty::fen::Fen;
+use shakmaty::{CastlingMode, Chess, Color};
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::Message;
+
+/// One step of the scripted server side of a game
+enum Step {
+    /// Wait for the client to send this move
+    ExpectMove(&'static str),
+    /// Wait for the client to send a frame of this type
+    Expect(&'static str),
+    /// Send a raw frame to the client
+    Send(String),
+}
+
+fn move_frame(uci: &str, ply: u32) -> String {
+    format!(r#"{{"t":"move","d":{{"uci":"{}","ply":{}}}}}"#, uci, ply)
+}
+
+/// Scripted frames for a Scholar's mate with the client playing White
+fn scholars_mate_script() -> Vec<Step> {
+    let ours = ["e2e4", "f1c4", "d1h5", "h5f7"];
+    let theirs = ["e7e5", "b8c6", "g8f6"];
+    let mut script = Vec::new();
+    let mut ply = 0;
+    for (i, uci) in ours.iter().enumerate() {
+        script.push(Step::ExpectMove(uci));
+        script.push(Step::Send(r#"{"t":"ack"}"#.to_string()));
+        ply += 1;
+        script.push(Step::Send(move_frame(uci, ply)));
+        if let Some(reply) = theirs.get(i) {
+            ply += 1;
+            script.push(Step::Send(move_frame(reply, ply)));
+        }
+    }
+    script.push(Step::Send(
+        r#"{"t":"endData","d":{"winner":"white","status":{"name":"mate"}}}"#.to_string(),
+    ));
+    script
+}
+
+/// Fool's mate with us as White: the opponent's mating move and the endData come together
+fn fools_mate_script() -> Vec<Step> {
+    let mut script = Vec::new();
+    for (ply, (ours, theirs)) in [("f2f3", "e7e5"), ("g2g4", "d8h4")].iter().enumerate() {
+        script.push(Step::ExpectMove(ours));
+        script.push(Step::Send(r#"{"t":"ack"}"#.to_string()));
+        script.push(Step::Send(move_frame(ours, 2 * ply as u32 + 1)));
+        script.push(Step::Send(move_frame(theirs, 2 * ply as u32 + 2)));
+    }
+    script.push(Step::Send(
+        r#"{"t":"endData","d":{"winner":"black","status":{"name":"mate"}}}"#.to_string(),
+    ));
+    script
+}
+
+/// Serve one game socket on localhost, returning its port and the server thread.
+/// The thread yields the ack numbers of the moves it received.
+fn spawn_mock_server(script: Vec<Step>) -> (u16, thread::JoinHandle<Result<Vec<u64>, String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().map_err(|e| e.to_string())?;
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .map_err(|e| e.to_string())?;
+        let mut ws = tungstenite::accept(stream).map_err(|e| e.to_string())?;
+        let mut acks = Vec::new();
+        for step in script {
+            match step {
+                Step::Send(frame) => ws.send(Message::Text(frame)).map_err(|e| e.to_string())?,
+                Step::Expect(expected) => loop {
+                    let msg = ws.read().map_err(|e| e.to_string())?;
+                    let Message::Text(text) = msg else { continue };
+                    let json: serde_json::Value =
+                        serde_json::from_str(&text).map_err(|e| e.to_string())?;
+                    if json["t"] == expected {
+                        break;
+                    }
+                    if json["t"] == "move" {
+                        return Err(format!("expected {}, got a move", expected));
+                    }
+                },
+                Step::ExpectMove(expected) => loop {
+                    let msg = ws.read().map_err(|e| e.to_string())?;
+                    let Message::Text(text) = msg else { continue };
+                    let json: serde_json::Value =
+                        serde_json::from_str(&text).map_err(|e| e.to_string())?;
+                    // Skip pings and anything else that isn't a move
+                    if json["t"] != "move" {
+                        continue;
+                    }
+                    let uci = json["d"]["u"].as_str().unwrap_or_default();
+                    if uci != expected {
+                        return Err(format!("expected {}, got {}", expected, uci));
+                    }
+                    match json["d"]["a"].as_u64() {
+                        Some(ack) => acks.push(ack),
+                        None => return Err(format!("move {} sent without an ack number", uci)),
+                    }
+                    break;
+                },
+            }
+        }
+        Ok(acks)
+    });
+    (port, handle)
+}
+
+/// Tick the app until `done` holds, failing the test after a few seconds
+fn tick_until(app: &mut App, what: &str, done: impl Fn(&App) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done(app) {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        app.tick();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn is_move_pending(app: &App) -> bool {
+    app.lichess_ws
+        .as_ref()
+        .is_some_and(|ws| ws.lock().unwrap().is_move_pending())
+}
+
+/// Play a Scholar's mate against the mock server, returning the acks it received
+fn play_scholars_mate(app: &mut App) -> Vec<u64> {
+    let (port, server) = spawn_mock_server(scholars_mate_script());
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+    assert_eq!(app.game.logic.game_state, GameState::Playing);
+
+    // Our side of the game comes from the opening book
+    let mut book = AutoMoveController::new();
+    let ours = ["e2e4", "f1c4", "d1h5", "h5f7"];
+    for (i, uci) in ours.iter().enumerate() {
+        let position = app.game.logic.game_board.position_ref().clone();
+        book.add_book_move(&position, uci);
+        let (picked, source) = book.pick_move(&position, |_| None).unwrap();
+        assert_eq!((picked.as_str(), source), (*uci, MoveSource::Book));
+
+        app.submit_typed_move(&picked).unwrap();
+        assert!(is_move_pending(app));
+        tick_until(app, "ack", |app| !is_move_pending(app));
+
+        // The echo of our own move must not be played a second time
+        let expected_plies = 2 * (i + 1);
+        if i + 1 < ours.len() {
+            tick_until(app, "opponent move", |app| {
+                app.game.logic.game_board.move_history.len() == expected_plies
+            });
+            assert_eq!(app.game.logic.player_turn, Color::White);
+        }
+    }
+
+    tick_until(app, "endData", |app| app.lichess_end_data.is_some());
+    assert_eq!(app.game.logic.game_board.move_history.len(), 7);
+    assert_eq!(app.game.logic.game_state, GameState::Checkmate);
+    assert_eq!(
+        app.lichess_end_data,
+        Some(("mate".to_string(), "white".to_string()))
+    );
+
+    server.join().unwrap().unwrap()
+}
+
+#[test]
+fn test_scripted_game_plays_to_checkmate() {
+    let mut app = App::default();
+    let acks = play_scholars_mate(&mut app);
+    // Sequence scheme by default: one number per move sent
+    assert_eq!(acks, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn test_opponent_mate_ends_the_game_on_the_board() {
+    let (port, server) = spawn_mock_server(fools_mate_script());
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    app.submit_typed_move("f2f3").unwrap();
+    tick_until(&mut app, "opponent move", |app| {
+        app.game.logic.game_board.move_history.len() == 2
+    });
+    app.submit_typed_move("g2g4").unwrap();
+
+    // The mate is on the board as soon as the result is, so there's nothing to resync
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert_eq!(app.game.logic.game_board.move_history.len(), 4);
+    assert!(app.game.logic.game_board.is_checkmate());
+    assert!(!app.end_data_contradicts_board("mate"));
+    assert!(app.end_sync_receiver.is_none());
+    assert_eq!(server.join().unwrap(), Ok(vec![1, 2]));
+}
+
+#[test]
+fn test_threefold_repetition_claims_the_draw() {
+    // Both knights shuffle out and back twice, so the start position comes up a third time
+    let moves = [
+        ("g1f3", "g8f6"),
+        ("f3g1", "f6g8"),
+        ("g1f3", "g8f6"),
+        ("f3g1", "f6g8"),
+    ];
+    let mut script = Vec::new();
+    for (ply, (ours, theirs)) in moves.iter().enumerate() {
+        script.push(Step::ExpectMove(ours));
+        script.push(Step::Send(r#"{"t":"ack"}"#.to_string()));
+        script.push(Step::Send(move_frame(ours, 2 * ply as u32 + 1)));
+        script.push(Step::Send(move_frame(theirs, 2 * ply as u32 + 2)));
+    }
+    script.push(Step::Expect("draw-yes"));
+    script.push(Step::Send(
+        r#"{"t":"endData","d":{"status":{"name":"draw"}}}"#.to_string(),
+    ));
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    for (i, (ours, _)) in moves.iter().enumerate() {
+        app.submit_typed_move(ours).unwrap();
+        tick_until(&mut app, "opponent move", |app| {
+            app.game.logic.game_board.move_history.len() == 2 * (i + 1)
+        });
+        if i == 1 {
+            assert_eq!(app.game.logic.game_board.repetition_count(), 2);
+            assert!(!app.threefold_draw_claimed);
+        }
+    }
+
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert!(app.game.logic.game_board.is_threefold_repetition());
+    assert!(app.threefold_draw_claimed);
+    assert_eq!(app.game.logic.game_state, GameState::Draw);
+    assert_eq!(server.join().unwrap(), Ok(vec![1, 2, 3, 4]));
+}
+
+#[test]
+fn test_ply_ack_scheme_fallback() {
+    assert_eq!("Ply".parse::<AckScheme>(), Ok(AckScheme::Ply));
+    assert!("turn".parse::<AckScheme>().is_err());
+
+    let mut app = App {
+        ws_ack_scheme: AckScheme::Ply,
+        ..App::default()
+    };
+    let acks = play_scholars_mate(&mut app);
+    // Legacy scheme: the last ply the server reported before each move
+    assert_eq!(acks, vec![0, 2, 4, 6]);
+}
+
+#[test]
+fn test_move_withdraws_draw_offer() {
+    let script = vec![
+        Step::Expect("draw-yes"),
+        Step::ExpectMove("e2e4"),
+        Step::Send(r#"{"t":"ack"}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    app.offer_draw().unwrap();
+    assert!(app.is_draw_offered());
+    assert!(app.offer_draw().is_err());
+
+    // Moving withdraws the offer, as Lichess does
+    app.submit_typed_move("e4").unwrap();
+    assert!(!app.is_draw_offered());
+
+    // No offer while the move waits for its ack
+    assert!(is_move_pending(&app));
+    assert!(app.offer_draw().is_err());
+    assert!(!app.is_draw_offered());
+    tick_until(&mut app, "ack", |app| !is_move_pending(app));
+
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+}
+
+#[test]
+fn test_connect_snapshot_rebuilds_board() {
+    let script = vec![
+        Step::Send(
+            r#"{"t":"full","d":{"moves":"e2e4 e7e5 g1f3","fen":"rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2","clock":{"white":170.5,"black":180}}}"#
+                .to_string(),
+        ),
+        Step::ExpectMove("b8c6"),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::Black);
+
+    tick_until(&mut app, "snapshot", |app| app.lichess_clocks_ms.is_some());
+    assert_eq!(app.lichess_clocks_ms, Some((170_500, 180_000)));
+    let meta = app.game_meta.as_ref().unwrap();
+    assert_eq!(meta.game_id.as_deref(), Some("mockgame"));
+    assert_eq!(app.game.logic.game_board.move_history.len(), 3);
+    assert_eq!(app.game.logic.player_turn, Color::Black);
+
+    // Play carries on from the snapshot position
+    app.submit_typed_move("Nc6").unwrap();
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+}
+
+#[test]
+fn test_aborted_game_gets_its_own_ending() {
+    let script = vec![Step::Send(
+        r#"{"t":"endData","d":{"status":{"name":"aborted"}}}"#.to_string(),
+    )];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+    assert!(app.has_live_game());
+
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert!(app.is_game_aborted());
+    assert_eq!(app.current_popup, Some(Popups::GameAborted));
+    // No result to show, and leaving needs no confirmation
+    assert_eq!(app.game.logic.game_state, GameState::Playing);
+    assert!(!app.has_live_game());
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+}
+
+#[test]
+fn test_joining_a_rated_game_engages_the_fair_play_lock() {
+    use chess_tui::lichess::OngoingGame;
+
+    let script = vec![Step::Send(
+        r#"{"t":"endData","d":{"status":{"name":"aborted"}}}"#.to_string(),
+    )];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let rated: OngoingGame = serde_json::from_value(serde_json::json!({
+        "gameId": "mockgame",
+        "fullId": "mockgamefull",
+        "color": "white",
+        "fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "opponent": { "id": "bob", "username": "bob", "rating": 1500 },
+        "isMyTurn": true,
+        "rated": true
+    }))
+    .unwrap();
+    let mut app = App {
+        ongoing_games: vec![rated],
+        ..App::default()
+    };
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+    assert!(app.current_game_rated);
+    assert!(app.fair_play_lock);
+    app.toggle_fair_play_lock();
+    assert!(app.fair_play_lock);
+
+    // The game's end releases the lock it engaged, so later bot games get help again
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert!(!app.current_game_rated);
+    assert!(!app.fair_play_lock);
+    assert!(app.is_engine_assistance_allowed());
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+}
+
+#[test]
+fn test_ack_flashes_move_confirmation() {
+    let script = vec![
+        Step::ExpectMove("e2e4"),
+        Step::Send(r#"{"t":"ack"}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App {
+        ack_flash: true,
+        ..App::default()
+    };
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    app.submit_typed_move("e4").unwrap();
+    assert!(!app.is_ack_flash_active(Instant::now()));
+    tick_until(&mut app, "ack", |app| !is_move_pending(app));
+    assert!(app.is_ack_flash_active(Instant::now()));
+    assert!(!app.is_ack_flash_active(Instant::now() + Duration::from_secs(1)));
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+
+    // Panic mode skips the flash
+    let mut auto_move = AutoMoveController::new();
+    auto_move.set_panic_mode(true);
+    let mut app = App {
+        ack_flash: true,
+        auto_move_controller: Some(auto_move),
+        ..App::default()
+    };
+    app.on_move_acked(Instant::now());
+    assert!(!app.is_ack_flash_active(Instant::now()));
+}
+
+/// Accept one socket, recording the path it was opened with
+// The handshake callback's error type is tungstenite's
+#[allow(clippy::result_large_err)]
+fn accept_recording_path(
+    listener: &TcpListener,
+) -> Result<(tungstenite::WebSocket<std::net::TcpStream>, String), String> {
+    let (stream, _) = listener.accept().map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .map_err(|e| e.to_string())?;
+    let mut path = String::new();
+    let ws = tungstenite::accept_hdr(
+        stream,
+        |request: &tungstenite::handshake::server::Request,
+         response: tungstenite::handshake::server::Response| {
+            path = request.uri().to_string();
+            Ok(response)
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    Ok((ws, path))
+}
+
+/// Read frames until a move arrives, returning its ack number
+fn read_move_ack(ws: &mut tungstenite::WebSocket<std::net::TcpStream>) -> Result<u64, String> {
+    loop {
+        let Message::Text(text) = ws.read().map_err(|e| e.to_string())? else {
+            continue;
+        };
+        let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        if json["t"] == "move" {
+            return json["d"]["a"]
+                .as_u64()
+                .ok_or("move without an ack".to_string());
+        }
+    }
+}
+
+#[test]
+fn test_reconnects_after_dropped_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || -> Result<(Vec<String>, Vec<u64>), String> {
+        // First connection dies with our move still unacked
+        let (mut first, first_path) = accept_recording_path(&listener)?;
+        let first_ack = read_move_ack(&mut first)?;
+        drop(first);
+
+        let (mut second, second_path) = accept_recording_path(&listener)?;
+        let second_ack = read_move_ack(&mut second)?;
+        second
+            .send(Message::Text(r#"{"t":"ack"}"#.to_string()))
+            .map_err(|e| e.to_string())?;
+        second.flush().map_err(|e| e.to_string())?;
+        // Hold the socket until the client has seen the ack
+        let _ = second.read();
+        Ok((vec![first_path, second_path], vec![first_ack, second_ack]))
+    });
+
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=keepme", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    ws.send_move("e2e4", 0, false).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut messages = Vec::new();
+    while !messages.iter().any(|m| m == "RECONNECTED") {
+        assert!(Instant::now() < deadline, "timed out waiting for reconnect");
+        messages.extend(ws.process_messages().unwrap());
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(messages.iter().any(|m| m == "DISCONNECTED"));
+    assert!(ws.is_connected());
+    // The unacked move is dropped; the server may have moved on
+    assert!(!ws.is_move_pending());
+
+    // Acks keep counting across the reconnect
+    ws.send_move("d2d4", 0, false).unwrap();
+    while ws.is_move_pending() {
+        assert!(Instant::now() < deadline, "timed out waiting for ack");
+        ws.process_messages().unwrap();
+        thread::sleep(Duration::from_millis(5));
+    }
+    ws.close().unwrap();
+
+    let (paths, acks) = server.join().unwrap().unwrap();
+    assert_eq!(paths, ["/play/mockgame/v6?sri=keepme"; 2]);
+    assert_eq!(acks, [1, 2]);
+}
+
+#[test]
+fn test_reconnect_backoff_doubles_up_to_cap() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    assert_eq!(reconnect_delay(1), Duration::from_millis(500));
+    assert_eq!(reconnect_delay(2), Duration::from_secs(1));
+    assert_eq!(reconnect_delay(6), Duration::from_secs(16));
+    assert_eq!(reconnect_delay(7), RECONNECT_BACKOFF_MAX);
+    assert_eq!(reconnect_delay(u32::MAX), RECONNECT_BACKOFF_MAX);
+
+    // Jitter stays within ±20% and actually varies
+    let mut rng = StdRng::seed_from_u64(7);
+    let delays: Vec<Duration> = (0..100)
+        .map(|_| jittered(Duration::from_secs(10), &mut rng))
+        .collect();
+    assert!(delays
+        .iter()
+        .all(|d| (Duration::from_secs(8)..=Duration::from_secs(12)).contains(d)));
+    assert!(delays.iter().any(|d| *d != delays[0]));
+}
+
+#[test]
+fn test_reconnect_with_backoff_gives_up() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // Serve one connection, then the server goes away for good
+    let server = thread::spawn(move || accept_recording_path(&listener).map(|_| ()));
+
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=keepme", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    server.join().unwrap().unwrap();
+
+    let started = Instant::now();
+    let result = ws.reconnect_with_backoff(2);
+    assert!(
+        matches!(
+            result,
+            Err(ReconnectError::RetriesExhausted { attempts: 2, .. })
+        ),
+        "{:?}",
+        result
+    );
+    // 500ms then 1s, each within the jitter
+    assert!(started.elapsed() >= Duration::from_millis(1200));
+    assert!(!ws.is_connected());
+    assert!(ws.has_reconnect_failed());
+    assert!(!ws.is_reconnecting());
+    assert!(ws.send_move("e2e4", 0, false).is_err());
+    // The polling loop stops retrying too
+    assert_eq!(ws.process_messages().unwrap(), Vec::<String>::new());
+}
+
+#[test]
+fn test_socket_host_accepts_bare_or_schemed_hosts() {
+    assert_eq!(
+        game_socket_url("socket1.lichess.org", "abcd1234", "sri")
+            .unwrap()
+            .as_str(),
+        "wss://socket1.lichess.org/play/abcd1234/v6?sri=sri"
+    );
+    assert_eq!(
+        game_socket_url("ws://localhost:9000/", "abcd1234", "sri")
+            .unwrap()
+            .as_str(),
+        "ws://localhost:9000/play/abcd1234/v6?sri=sri"
+    );
+    for malformed in ["", "ws://", "exa mple.org", "https://lichess.org"] {
+        let err = game_socket_url(malformed, "abcd1234", "sri").unwrap_err();
+        assert!(err.starts_with("Invalid WebSocket host"), "{}", err);
+    }
+
+    // A local server stands in for the Lichess host
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || accept_recording_path(&listener).map(|(_, path)| path));
+    let host = format!("ws://127.0.0.1:{}", port);
+    let ws = LichessWebSocket::new_with_host("mockgame", "keepme", &host).unwrap();
+    assert_eq!(
+        server.join().unwrap().unwrap(),
+        "/play/mockgame/v6?sri=keepme"
+    );
+    assert_eq!(ws.game_id(), "mockgame");
+    assert!(LichessWebSocket::new_with_host("mockgame", "keepme", "ws://").is_err());
+}
+
+#[test]
+fn test_keepalive_ping_leaves_pending_move() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || -> Result<Vec<String>, String> {
+        let (mut ws, _) = accept_recording_path(&listener)?;
+        let mut frames = Vec::new();
+        while frames.len() < 2 {
+            if let Message::Text(text) = ws.read().map_err(|e| e.to_string())? {
+                frames.push(text);
+            }
+        }
+        // Pongs are bare zeros, and never reach the caller
+        ws.send(Message::Text("0".to_string()))
+            .map_err(|e| e.to_string())?;
+        ws.flush().map_err(|e| e.to_string())?;
+        let _ = ws.read();
+        Ok(frames)
+    });
+
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=keepme", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    ws.send_move("e2e4", 0, false).unwrap();
+    assert!(ws.tick_keepalive(Duration::from_secs(60)).unwrap());
+    // Only one ping per interval
+    assert!(!ws.tick_keepalive(Duration::from_secs(60)).unwrap());
+    assert!(ws.is_move_pending());
+
+    let until = Instant::now() + Duration::from_millis(200);
+    while Instant::now() < until {
+        assert!(ws.process_messages().unwrap().is_empty());
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(ws.is_move_pending());
+    ws.close().unwrap();
+    let frames = server.join().unwrap().unwrap();
+    let ping: serde_json::Value = serde_json::from_str(&frames[1]).unwrap();
+    assert_eq!(ping, serde_json::json!({"t": "p", "v": 0}));
+}
+
+#[test]
+fn test_unacked_move_is_resent_with_same_ack() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // The server never acks, as if every frame were lost
+    let server = thread::spawn(move || -> Result<Vec<u64>, String> {
+        let (mut ws, _) = accept_recording_path(&listener)?;
+        (0..4).map(|_| read_move_ack(&mut ws)).collect()
+    });
+
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=keepme", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    assert_eq!(ws.check_pending_timeout(Duration::ZERO), None);
+    ws.send_move("e2e4", 0, false).unwrap();
+    assert_eq!(ws.check_pending_timeout(Duration::from_secs(60)), None);
+    assert_eq!(
+        ws.check_pending_timeout(Duration::ZERO).as_deref(),
+        Some("e2e4")
+    );
+
+    for _ in 0..3 {
+        ws.resend_pending().unwrap();
+    }
+    assert!(ws.is_move_pending());
+    // Out of resends: the move is dropped so the game can go on
+    let err = ws.resend_pending().unwrap_err();
+    assert!(err.contains("not acknowledged after 3 resends"), "{}", err);
+    assert!(!ws.is_move_pending());
+    assert_eq!(ws.check_pending_timeout(Duration::ZERO), None);
+
+    assert_eq!(server.join().unwrap().unwrap(), [1, 1, 1, 1]);
+}
+
+#[test]
+fn test_move_clocks_reach_the_app() {
+    let script = vec![
+        Step::Send(
+            r#"{"t":"move","d":{"uci":"e2e4","ply":1,"clock":{"white":179.7,"black":180}}}"#
+                .to_string(),
+        ),
+        // Correspondence games send no clock, which leaves the last one shown
+        Step::Send(move_frame("e7e5", 2)),
+        Step::Send(
+            r#"{"t":"endData","d":{"winner":"white","status":{"name":"resign"}}}"#.to_string(),
+        ),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::Black);
+
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert_eq!(app.lichess_clocks_ms, Some((179_700, 180_000)));
+    // The resignation stopped the clocks where the server last left them
+    assert!(app.lichess_clocks_synced_at.is_none());
+    let later = std::time::Instant::now() + std::time::Duration::from_secs(60);
+    assert_eq!(app.running_clocks_ms(later), Some((179_700, 180_000)));
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+}
+
+#[test]
+fn test_engine_search_time_follows_the_running_clock() {
+    use chess_tui::engine::{movetime, Analysis, Engine};
+    use chess_tui::lichess::GameMeta;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the clocks it's given
+    struct ClockEngine(Arc<Mutex<Vec<(Duration, Duration)>>>);
+
+    impl Engine for ClockEngine {
+        fn analyze(&mut self, _fen: &str) -> Result<Analysis, String> {
+            Err("not searching".to_string())
+        }
+
+        fn set_option(&mut self, _name: &str, _value: Option<&str>) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn set_clock(&mut self, remaining: Duration, increment: Duration) {
+            self.0.lock().unwrap().push((remaining, increment));
+        }
+    }
+
+    let script = vec![
+        Step::Send(
+            r#"{"t":"move","d":{"uci":"e2e4","ply":1,"clock":{"white":179,"black":180}}}"#
+                .to_string(),
+        ),
+        Step::Send(
+            r#"{"t":"move","d":{"uci":"e7e5","ply":2,"clock":{"white":179,"black":4.5}}}"#
+                .to_string(),
+        ),
+        Step::Send(
+            r#"{"t":"endData","d":{"winner":"white","status":{"name":"resign"}}}"#.to_string(),
+        ),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::Black);
+    app.game_meta = Some(GameMeta {
+        clock: Some((180, 2)),
+        ..GameMeta::for_game("mockgame")
+    });
+    let clocks = Arc::new(Mutex::new(Vec::new()));
+    app.auto_move_controller
+        .as_mut()
+        .unwrap()
+        .set_engine(Box::new(ClockEngine(Arc::clone(&clocks))));
+
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    // Every clock update reaches the engine with our time and the game's increment
+    let clocks = clocks.lock().unwrap().clone();
+    let increment = Duration::from_secs(2);
+    assert_eq!(
+        clocks,
+        [
+            (Duration::from_secs(180), increment),
+            (Duration::from_millis(4_500), increment)
+        ]
+    );
+    let searches: Vec<Duration> = clocks
+        .iter()
+        .map(|(remaining, increment)| movetime(*remaining, *increment))
+        .collect();
+    assert!(searches[1] < searches[0]);
+    assert!(searches[1] < Duration::from_millis(4_500) * 2 / 5);
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+}
+
+#[test]
+fn test_opponent_draw_offer_declined_then_accepted() {
+    let offer = || Step::Send(r#"{"t":"drawOffer","d":"black"}"#.to_string());
+    let script = vec![
+        offer(),
+        Step::Expect("draw-no"),
+        offer(),
+        Step::Expect("draw-yes"),
+        Step::Send(r#"{"t":"endData","d":{"status":{"name":"draw"}}}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    tick_until(&mut app, "draw offer", |app| {
+        app.current_popup == Some(Popups::DrawOffer)
+    });
+    assert!(app.has_opponent_draw_offer());
+    app.answer_draw_offer(false);
+    assert!(!app.has_opponent_draw_offer());
+    assert_eq!(app.current_popup, None);
+
+    tick_until(&mut app, "second draw offer", |app| {
+        app.current_popup == Some(Popups::DrawOffer)
+    });
+    app.answer_draw_offer(true);
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+
+    // Typed consumers see the offering side
+    let msg: LichessMessage = serde_json::from_str(r#"{"t":"drawOffer","d":null}"#).unwrap();
+    assert!(matches!(msg, LichessMessage::DrawOffer { d: None }));
+}
+
+#[test]
+fn test_resign_is_sent_once() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // Answers the first resign with endData, then records frames other than pings until closed
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let mut ws = tungstenite::accept(stream).unwrap();
+        let mut received = Vec::new();
+        while let Ok(msg) = ws.read() {
+            let Message::Text(text) = msg else { continue };
+            let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+            let kind = json["t"].as_str().unwrap_or_default().to_string();
+            if kind == "p" {
+                continue;
+            }
+            if kind == "resign" && !received.contains(&kind) {
+                let end = r#"{"t":"endData","d":{"winner":"black","status":{"name":"resign"}}}"#;
+                ws.send(Message::Text(end.to_string())).unwrap();
+            }
+            received.push(kind);
+        }
+        received
+    });
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    app.request_resign();
+    assert_eq!(app.current_popup, Some(Popups::ResignConfirmation));
+    app.confirm_resign_game();
+    // A second resign before the server answered is a no-op
+    app.lichess_ws
+        .as_ref()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .resign()
+        .unwrap();
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+
+    let ws_arc = app.lichess_ws.clone().unwrap();
+    let ws = ws_arc.lock().unwrap();
+    assert!(ws.is_game_ended());
+    assert_eq!(ws.resign(), Ok(()));
+    ws.close().unwrap();
+    drop(ws);
+    assert_eq!(server.join().unwrap(), ["resign"]);
+}
+
+#[test]
+fn test_takeback_plies_depend_on_side_to_move() {
+    // White proposes right after moving: just that move
+    assert_eq!(takeback_plies(1, Some("white")), 1);
+    // Black already replied: both moves go
+    assert_eq!(takeback_plies(2, Some("white")), 2);
+    assert_eq!(takeback_plies(2, Some("black")), 1);
+    assert_eq!(takeback_plies(3, Some("black")), 2);
+    // Never past the start, and one ply when the proposer is unknown
+    assert_eq!(takeback_plies(1, Some("black")), 1);
+    assert_eq!(takeback_plies(0, Some("white")), 0);
+    assert_eq!(takeback_plies(4, None), 1);
+}
+
+#[test]
+fn test_takebacks_roll_the_board_back() {
+    let script = vec![
+        Step::ExpectMove("e2e4"),
+        Step::Send(r#"{"t":"ack"}"#.to_string()),
+        Step::Send(move_frame("e2e4", 1)),
+        Step::Send(move_frame("e7e5", 2)),
+        // The opponent takes back their reply
+        Step::Send(r#"{"t":"takebackOffers","d":{"black":true}}"#.to_string()),
+        Step::Expect("takeback-yes"),
+        // Then we take back our first move, which the opponent accepts
+        Step::Expect("takeback-yes"),
+        Step::Send(r#"{"t":"takebackOffers","d":{"white":true}}"#.to_string()),
+        Step::Send(r#"{"t":"takebackOffers"}"#.to_string()),
+        Step::Send(r#"{"t":"reload"}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    app.submit_typed_move("e4").unwrap();
+    // The opponent's reply reaches the board a tick after its frame
+    tick_until(&mut app, "reply and takeback proposal", |app| {
+        app.current_popup == Some(Popups::TakebackOffer)
+            && app.game.logic.game_board.move_history.len() == 2
+    });
+    app.answer_takeback_offer(true);
+    assert_eq!(app.current_popup, None);
+    assert_eq!(app.game.logic.game_board.move_history.len(), 1);
+    assert_eq!(app.game.logic.player_turn, Color::Black);
+
+    app.propose_takeback().unwrap();
+    assert!(app.propose_takeback().is_err());
+    tick_until(&mut app, "accepted takeback", |app| {
+        app.game.logic.game_board.move_history.is_empty()
+    });
+    assert_eq!(app.game.logic.player_turn, Color::White);
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+
+    let msg: LichessMessage =
+        serde_json::from_str(r#"{"t":"takebackOffers","d":{"white":true}}"#).unwrap();
+    assert!(
+        matches!(msg, LichessMessage::Takeback { d: Some(offers) } if offers.proposer() == Some("white"))
+    );
+}
+
+#[test]
+fn test_chat_is_sanitized_and_received() {
+    assert_eq!(sanitize_chat("  good\tgame\u{7}  "), "goodgame");
+    assert_eq!(sanitize_chat(&"é".repeat(200)).chars().count(), 140);
+
+    let script = vec![
+        Step::Expect("talk"),
+        Step::Send(r#"{"t":"message","d":{"u":"bob","t":"gg: well played"}}"#.to_string()),
+        Step::Send(r#"{"t":"endData","d":{"status":{"name":"draw"}}}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+
+    assert!(app.send_chat(" \n ").is_err());
+    app.send_chat("hi\n").unwrap();
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+
+    // Colons in the text survive the CHAT:<username>:<text> framing
+    assert_eq!(app.lichess_chat.len(), 1);
+    assert_eq!(app.lichess_chat[0].u, "bob");
+    assert_eq!(app.lichess_chat[0].t, "gg: well played");
+
+    let msg: LichessMessage =
+        serde_json::from_str(r#"{"t":"message","d":{"u":"lichess","t":"Takeback sent"}}"#).unwrap();
+    assert!(matches!(msg, LichessMessage::Chat { d } if d.u == "lichess"));
+}
+
+/// Process frames until `expected` comes in, failing after a few seconds
+fn process_until(ws: &LichessWebSocket, expected: &str) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for {}",
+            expected
+        );
+        if ws.process_messages().unwrap().iter().any(|m| m == expected) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_claim_win_waits_for_gone_timer() {
+    let script = vec![
+        Step::Send(r#"{"t":"goneIn","d":30}"#.to_string()),
+        // A chat line from the client lets the script go on
+        Step::Expect("talk"),
+        Step::Send(r#"{"t":"gone","d":true}"#.to_string()),
+        Step::Expect("resign-force"),
+        Step::Send(r#"{"t":"gone","d":false}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    assert!(!ws.opponent_gone());
+    assert!(ws.claim_win().is_err());
+
+    process_until(&ws, "OPPONENT_GONE:30");
+    assert!(ws.opponent_gone());
+    assert!(ws.claim_win_in().unwrap() > Duration::from_secs(25));
+    assert!(ws.claim_win().is_err());
+
+    ws.send_chat("still there?").unwrap();
+    process_until(&ws, "OPPONENT_GONE:0");
+    assert_eq!(ws.claim_win_in(), Some(Duration::ZERO));
+    ws.claim_win().unwrap();
+
+    process_until(&ws, "OPPONENT_BACK");
+    assert!(!ws.opponent_gone());
+    assert_eq!(ws.claim_win_in(), None);
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+}
+
+#[test]
+fn test_events_are_typed_and_match_legacy_messages() {
+    let script = vec![
+        Step::Send(
+            r#"{"t":"move","d":{"uci":"e2e4","ply":1,"fen":"8/8/8/8/8/8/8/8","clock":{"white":61.5,"black":60}}}"#
+                .to_string(),
+        ),
+        Step::Send(r#"{"t":"resync"}"#.to_string()),
+        Step::Send(r#"{"t":"endData","d":{"status":{"name":"outoftime"}}}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut events = Vec::new();
+    while !events.iter().any(|e| matches!(e, GameEvent::GameEnd(_))) {
+        assert!(Instant::now() < deadline, "timed out waiting for endData");
+        events.extend(ws.process_events().unwrap());
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+    assert_eq!(
+        events,
+        [
+            GameEvent::Move("e2e4".to_string()),
+            GameEvent::Fen("8/8/8/8/8/8/8/8".to_string()),
+            GameEvent::Clock {
+                white: 61.5,
+                black: 60.0
+            },
+            GameEvent::Resync,
+            GameEvent::GameEnd(Some(EndData {
+                status: Some("outoftime".to_string()),
+                winner: None,
+            })),
+        ]
+    );
+
+    // process_messages still speaks the old strings, and never reported resyncs
+    let messages: Vec<String> = events.iter().filter_map(GameEvent::to_message).collect();
+    assert_eq!(
+        messages,
+        [
+            "MOVE:e2e4",
+            "FEN:8/8/8/8/8/8/8/8",
+            "CLOCK:61500,60000",
+            "GAME_END:outoftime,none"
+        ]
+    );
+}
+
+#[test]
+fn test_premove_fires_on_opponent_move() {
+    let script = vec![
+        Step::ExpectMove("e2e4"),
+        Step::Send(r#"{"t":"ack"}"#.to_string()),
+        Step::Send(move_frame("e2e4", 1)),
+        Step::Send(move_frame("e7e5", 2)),
+        Step::ExpectMove("g1f3"),
+        Step::Send(r#"{"t":"ack"}"#.to_string()),
+        Step::Send(move_frame("g1f3", 3)),
+        Step::Send(
+            r#"{"t":"move","d":{"uci":"f8b4","ply":4,"fen":"rnbqk1nr/pppp1ppp/8/4p3/1b2P3/5N2/PPPP1PPP/RNBQKB1R"}}"#
+                .to_string(),
+        ),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    assert!(ws.set_premove("nonsense").is_err());
+
+    ws.send_move("e2e4", 0, false).unwrap();
+    ws.set_premove("g1f3").unwrap();
+    // The echo of our own move leaves the premove queued
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut events = Vec::new();
+    while !events.contains(&GameEvent::PremoveSent("g1f3".to_string())) {
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for the premove"
+        );
+        events.extend(ws.process_events().unwrap());
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(!ws.has_premove());
+    assert!(events.contains(&GameEvent::Move("e7e5".to_string())));
+
+    // A premove the validator finds illegal in the new position is dropped unsent
+    ws.set_premove_validator(Box::new(|uci, fen| {
+        !(uci == "d2d4" && fen.is_some_and(|fen| fen.contains("1b2P3")))
+    }));
+    ws.set_premove("d2d4").unwrap();
+    process_until(&ws, "MOVE:f8b4");
+    assert!(!ws.has_premove());
+    assert_eq!(server.join().unwrap(), Ok(vec![1, 2]));
+
+    ws.set_premove("c2c3").unwrap();
+    ws.clear_premove();
+    assert!(!ws.has_premove());
+}
+
+#[test]
+fn test_crowd_updates_show_presence() {
+    let parse = |json: &str| serde_json::from_str::<CrowdData>(json).unwrap();
+    assert_eq!(
+        parse(r#"{"white":true,"black":false,"watchers":{"nb":3,"users":["ann"]}}"#),
+        CrowdData {
+            white_present: true,
+            black_present: false,
+            watchers: Some(3)
+        }
+    );
+    assert_eq!(parse(r#"{"white":true,"watchers":7}"#).watchers, Some(7));
+    assert_eq!(parse(r#"{"white":true,"black":true}"#).watchers, None);
+    assert_eq!(
+        GameEvent::Presence(parse(r#"{"white":true,"watchers":7}"#)).to_message(),
+        Some("PRESENCE:1,0,7".to_string())
+    );
+    assert_eq!(
+        GameEvent::Presence(parse(r#"{"white":true,"black":true}"#)).to_message(),
+        Some("PRESENCE:1,1,".to_string())
+    );
+
+    let script = vec![
+        Step::Send(
+            r#"{"t":"crowd","d":{"white":true,"black":false,"watchers":{"nb":3}}}"#.to_string(),
+        ),
+        Step::Send(r#"{"t":"endData","d":{"status":{"name":"resign"}}}"#.to_string()),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    let mut app = App::default();
+    app.attach_lichess_websocket(ws, "mockgame", Color::White);
+    assert_eq!(app.presence_line(), None);
+
+    tick_until(&mut app, "endData", |app| app.lichess_end_data.is_some());
+    assert_eq!(
+        app.presence_line().as_deref(),
+        Some("Opponent away • 3 watching")
+    );
+    assert_eq!(server.join().unwrap(), Ok(vec![]));
+}
+
+#[test]
+fn test_lag_is_read_from_our_echoed_moves() {
+    let script = vec![
+        Step::ExpectMove("e2e4"),
+        Step::Send(r#"{"t":"ack"}"#.to_string()),
+        Step::Send(r#"{"t":"move","d":{"uci":"e2e4","ply":1,"l":87}}"#.to_string()),
+        // The opponent's lag isn't ours
+        Step::Send(r#"{"t":"move","d":{"uci":"e7e5","ply":2,"l":5}}"#.to_string()),
+        Step::ExpectMove("g1f3"),
+        Step::Send(r#"{"t":"ack"}"#.to_string()),
+        Step::Send(move_frame("g1f3", 3)),
+    ];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    assert_eq!(ws.last_lag_ms(), None);
+
+    ws.send_move("e2e4", 0, false).unwrap();
+    process_until(&ws, "MOVE:e7e5");
+    assert_eq!(ws.last_lag_ms(), Some(87));
+
+    ws.send_move("g1f3", 87, false).unwrap();
+    process_until(&ws, "MOVE:g1f3");
+    // An echo without a lag keeps the last measure
+    assert_eq!(ws.last_lag_ms(), Some(87));
+    assert_eq!(server.join().unwrap(), Ok(vec![1, 2]));
+    assert_eq!(GameEvent::Lag(87).to_message().as_deref(), Some("LAG:87"));
+}
+
+/// Serve one socket and return the reason of the close frame it gets
+fn spawn_close_recorder() -> (u16, thread::JoinHandle<Result<String, String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let (mut ws, _) = accept_recording_path(&listener)?;
+        loop {
+            match ws.read().map_err(|e| e.to_string())? {
+                Message::Close(frame) => {
+                    return Ok(frame.map(|f| f.reason.to_string()).unwrap_or_default())
+                }
+                _ => continue,
+            }
+        }
+    });
+    (port, handle)
+}
+
+#[test]
+fn test_close_sends_close_frame_and_blocks_moves() {
+    let (port, server) = spawn_close_recorder();
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+    ws.close().unwrap();
+    assert!(ws.is_closed());
+    assert_eq!(
+        server.join().unwrap(),
+        Ok("closed by the client".to_string())
+    );
+
+    // Closing again, and dropping it later, do nothing
+    ws.close().unwrap();
+    assert_eq!(
+        ws.send_move("e2e4", 0, false),
+        Err("Connection closed".to_string())
+    );
+
+    // Dropping an open socket closes it too
+    let (port, server) = spawn_close_recorder();
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    drop(LichessWebSocket::connect(&url, "mockgame").unwrap());
+    assert_eq!(server.join().unwrap(), Ok("client dropped".to_string()));
+}
+
+#[test]
+fn test_min_interval_spaces_auto_moves() {
+    let script = vec![Step::ExpectMove("e2e4")];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+
+    let mut controller = AutoMoveController::new();
+    controller.set_enabled(true);
+    controller.set_min_interval(Duration::from_secs(60));
+    assert!(controller.should_auto_move(true));
+    assert!(controller.execute_auto_move("e2e4", &ws));
+    // A second answer can't follow straight away, even a different move
+    assert!(!controller.should_auto_move(true));
+
+    controller.set_min_interval(Duration::ZERO);
+    assert!(controller.should_auto_move(true));
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+}
+
+#[test]
+fn test_auto_move_stats_count_each_outcome() {
+    let script = vec![Step::ExpectMove("e2e4")];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+
+    let mut controller = AutoMoveController::new();
+    controller.set_enabled(true);
+    assert!(controller.execute_auto_move("e2e4", &ws));
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+    assert!(!controller.execute_auto_move("e2e4", &ws));
+    ws.close().unwrap();
+    assert!(!controller.execute_auto_move("d2d4", &ws));
+    assert_eq!(
+        controller.stats(),
+        AutoMoveStats {
+            sent: 1,
+            blocked_duplicates: 1,
+            failed: 1,
+        }
+    );
+
+    controller.reset_stats();
+    assert_eq!(controller.stats(), AutoMoveStats::default());
+}
+
+#[test]
+fn test_on_move_callback_runs_after_send() {
+    let script = vec![Step::ExpectMove("e2e4")];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+
+    let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut controller = AutoMoveController::new();
+    let record = sent.clone();
+    controller.set_on_move(Box::new(move |uci, lag_ms, berserked| {
+        record
+            .lock()
+            .unwrap()
+            .push((uci.to_string(), lag_ms, berserked));
+    }));
+    controller.set_lag_profile(35, 60);
+    controller.set_adaptive_lag(None);
+    assert!(controller.execute_auto_move("e2e4", &ws));
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+
+    // Blocked and failed sends don't call it
+    assert!(!controller.execute_auto_move("e2e4", &ws));
+    ws.close().unwrap();
+    assert!(!controller.execute_auto_move("d2d4", &ws));
+    assert_eq!(*sent.lock().unwrap(), vec![("e2e4".to_string(), 35, false)]);
+}
+
+#[test]
+fn test_checked_auto_move_rejects_illegal_moves() {
+    let script = vec![Step::ExpectMove("e7e8q")];
+    let (port, server) = spawn_mock_server(script);
+    let url = format!("ws://127.0.0.1:{}/play/mockgame/v6?sri=test", port);
+    let ws = LichessWebSocket::connect(&url, "mockgame").unwrap();
+
+    let position: Chess = "k7/4P3/8/8/8/8/8/4K3 w - - 0 1"
+        .parse::<Fen>()
+        .unwrap()
+        .into_position(CastlingMode::Standard)
+        .unwrap();
+    let mut controller = AutoMoveController::new();
+    // A promotion needs its piece, and garbage never reaches the socket
+    assert!(!controller.execute_checked_auto_move("e7e8", &position, &ws));
+    assert!(!controller.execute_checked_auto_move("e1e3", &position, &ws));
+    assert!(!controller.execute_checked_auto_move("nonsense", &position, &ws));
+    assert!(controller.execute_checked_auto_move("e7e8q", &position, &ws));
+    assert_eq!(server.join().unwrap(), Ok(vec![1]));
+    assert_eq!(controller.stats().sent, 1);
+}
@@ -0,0 +1,187 @@
+use chess_tui::app::App;
+use chess_tui::constants::Pages;
+use chess_tui::game_logic::game::GameState;
+use chess_tui::game_logic::game_board::GameBoard;
+use chess_tui::pgn::{
+    export_pgn, game_file_stem, parse_clock, parse_pgn, save_pgn, SIMULATION_MOVE_INTERVAL,
+};
+use shakmaty::{Color, Position};
+use std::time::{Duration, Instant};
+
+const SCHOLARS_MATE: &str = r#"[Event "Rated blitz game"]
+[White "alice"]
+[Black "bob"]
+[TimeControl "180+2"]
+
+1. e4 { [%clk 0:03:00] } 1... e5 { [%clk 0:03:00] } 2. Bc4 { [%clk 0:02:59] }
+2... Nc6 { [%clk 0:02:55.5] } 3. Qh5!? { [%clk 0:02:58] } (3. Nf3 { main line (usually) } Nf6)
+3... Nf6?? $4 { [%clk 0:02:50] } ; blunder
+4. Qxf7# { [%clk 0:02:57] } 1-0
+"#;
+
+#[test]
+fn test_parse_pgn_mainline_and_clocks() {
+    let game = parse_pgn(SCHOLARS_MATE).unwrap();
+    assert_eq!(game.header("White"), Some("alice"));
+    assert_eq!(
+        game.time_control(),
+        Some((Duration::from_secs(180), Duration::from_secs(2)))
+    );
+    assert_eq!(game.result.as_deref(), Some("1-0"));
+
+    let sans: Vec<&str> = game.moves.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(sans, ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]);
+    assert_eq!(game.moves[3].clock, Some(Duration::from_millis(175_500)));
+    assert_eq!(game.mainline().unwrap().len(), 7);
+
+    assert_eq!(
+        parse_clock("[%clk 1:02:03.25]"),
+        Some(Duration::from_millis(3_723_250))
+    );
+    assert_eq!(parse_clock("no clock here"), None);
+    assert!(parse_pgn("1. e4 e5 2. Ke3").unwrap().mainline().is_err());
+}
+
+#[test]
+fn test_simulation_replays_on_clock_timing() {
+    let start = Instant::now();
+    let mut app = App::default();
+    app.start_simulation(SCHOLARS_MATE, start).unwrap();
+    assert_eq!(app.current_page, Pages::LichessTv);
+
+    // Opening moves have no previous clock, so they use the fixed interval
+    app.advance_simulation(start + SIMULATION_MOVE_INTERVAL - Duration::from_millis(1));
+    assert!(app.game.logic.game_board.move_history.is_empty());
+    app.advance_simulation(start + SIMULATION_MOVE_INTERVAL);
+    assert_eq!(app.game.logic.game_board.move_history.len(), 1);
+    assert_eq!(app.lichess_clocks_ms, Some((180_000, 180_000)));
+
+    // Each later move takes the mover's previous clock plus increment minus the new one:
+    // Bc4 180 + 2 - 179 = 3s, Nc6 180 + 2 - 175.5 = 6.5s, then Qh5 179 + 2 - 178 = 3s
+    let after_nc6 = start
+        + 2 * SIMULATION_MOVE_INTERVAL
+        + Duration::from_secs(3)
+        + Duration::from_millis(6_500);
+    app.advance_simulation(after_nc6);
+    assert_eq!(app.game.logic.game_board.move_history.len(), 4);
+    assert_eq!(app.lichess_clocks_ms, Some((179_000, 175_500)));
+    app.advance_simulation(after_nc6 + Duration::from_millis(2_999));
+    assert_eq!(app.game.logic.game_board.move_history.len(), 4);
+    assert_eq!(app.game.logic.player_turn, Color::White);
+
+    // A slow tick catches up on every due move and ends on the result
+    app.advance_simulation(start + Duration::from_secs(3600));
+    assert_eq!(app.game.logic.game_board.move_history.len(), 7);
+    assert_eq!(app.game.logic.game_state, GameState::Checkmate);
+    assert!(app.simulation.as_ref().is_some_and(|s| s.is_finished()));
+    assert_eq!(app.lichess_clocks_ms, Some((177_000, 170_000)));
+}
+
+#[test]
+fn test_simulation_rejects_illegal_pgn() {
+    let mut app = App::default();
+    assert!(app
+        .start_simulation("1. e4 e5 2. Ke3 *", Instant::now())
+        .is_err());
+    assert!(app
+        .start_simulation("[Event \"Empty\"]\n\n*", Instant::now())
+        .is_err());
+    assert!(app.simulation.is_none());
+    assert_eq!(app.current_page, Pages::Home);
+}
+
+#[test]
+fn test_finished_game_record_is_saved_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut app = App {
+        auto_save_pgn: true,
+        pgn_dir: Some(dir.path().join("records")),
+        ..App::default()
+    };
+    for san in ["f3", "e5", "g4", "Qh4#"] {
+        app.submit_typed_move(san).unwrap();
+    }
+    assert_eq!(app.game_result(), Some("0-1"));
+    // Showing the end screen again doesn't write a second record
+    app.show_end_screen();
+
+    let files: Vec<_> = std::fs::read_dir(dir.path().join("records"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    let name = files[0].file_name().unwrap().to_string_lossy().to_string();
+    assert!(name.ends_with("_Local_offline.pgn"), "{}", name);
+
+    // The record reads back as the same game
+    let game = parse_pgn(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+    assert_eq!(game.result.as_deref(), Some("0-1"));
+    assert_eq!(game.header("Result"), Some("0-1"));
+    let sans: Vec<&str> = game.moves.iter().map(|m| m.san.as_str()).collect();
+    assert_eq!(sans, ["f3", "e5", "g4", "Qh4#"]);
+}
+
+#[test]
+fn test_export_and_save_pgn() {
+    // A game set up from Black to move numbers its first move "n..."
+    let game = parse_pgn(
+        "[FEN \"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1\"]\n1... e5 2. Nf3 *",
+    )
+    .unwrap();
+    let mut positions = vec![game.start_position().unwrap()];
+    for chess_move in game.mainline().unwrap() {
+        let next = positions.last().unwrap().clone().play(&chess_move).unwrap();
+        positions.push(next);
+    }
+    let headers = vec![("White".to_string(), "a \"quoted\" name".to_string())];
+    assert_eq!(
+        export_pgn(&headers, &positions, "*"),
+        "[White \"a \\\"quoted\\\" name\"]\n\n1... e5 2. Nf3 *\n"
+    );
+
+    assert_eq!(
+        game_file_stem("2024-05-01", "bob/../x", "abcd1234"),
+        "2024-05-01_bob____x_abcd1234"
+    );
+
+    // Collisions get a numbered suffix instead of overwriting
+    let dir = tempfile::tempdir().unwrap();
+    let first = save_pgn(&dir.path().join("new"), "game", "1").unwrap();
+    let second = save_pgn(&dir.path().join("new"), "game", "2").unwrap();
+    assert_eq!(first.file_name().unwrap(), "game.pgn");
+    assert_eq!(second.file_name().unwrap(), "game-2.pgn");
+    assert_eq!(std::fs::read_to_string(first).unwrap(), "1");
+}
+
+#[test]
+fn test_saved_game_has_the_seven_tag_roster_and_its_start_position() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut app = App {
+        pgn_dir: Some(dir.path().to_path_buf()),
+        ..App::default()
+    };
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 30";
+    app.game.logic.game_board = GameBoard::from_fen(fen).unwrap();
+    app.submit_typed_move("e4").unwrap();
+    app.save_game_on_request();
+    assert!(app.game_record_saved);
+
+    let file = std::fs::read_dir(dir.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let pgn = std::fs::read_to_string(file).unwrap();
+    let tags: Vec<&str> = pgn
+        .lines()
+        .take_while(|line| line.starts_with('['))
+        .map(|line| line[1..].split(' ').next().unwrap())
+        .collect();
+    assert_eq!(
+        tags,
+        ["Event", "Site", "Date", "Round", "White", "Black", "Result", "SetUp", "FEN"]
+    );
+    assert!(pgn.contains(&format!("[FEN \"{}\"]", fen)), "{}", pgn);
+    assert!(pgn.ends_with("\n\n30. e4 *\n"), "{}", pgn);
+}
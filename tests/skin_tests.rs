@@ -33,4 +33,7 @@ fn test_load_skin() {
     assert_eq!(skin.board_white_color, Color::Red);
     assert_eq!(skin.board_black_color, Color::Blue);
     assert_eq!(skin.piece_white_color, Color::Green);
+    // Skins written before the side-to-move marker still load
+    assert_eq!(skin.side_to_move_color, Color::Yellow);
+    assert_eq!(skin.coordinate_color, Color::Gray);
 }
@@ -0,0 +1,44 @@
+use chess_tui::utils::{parse_uci, UciError};
+use shakmaty::{Role, Square};
+
+#[test]
+fn test_parse_uci_simple_move() {
+    let mv = parse_uci("e2e4").unwrap();
+    assert_eq!(mv.from, Square::E2);
+    assert_eq!(mv.to, Square::E4);
+    assert_eq!(mv.promotion, None);
+    assert_eq!(mv.to_string(), "e2e4");
+}
+
+#[test]
+fn test_parse_uci_promotion() {
+    let mv = parse_uci("e7e8q").unwrap();
+    assert_eq!(mv.from, Square::E7);
+    assert_eq!(mv.to, Square::E8);
+    assert_eq!(mv.promotion, Some(Role::Queen));
+    assert_eq!(mv.to_string(), "e7e8q");
+}
+
+#[test]
+fn test_parse_uci_rejects_bad_input() {
+    assert_eq!(parse_uci(""), Err(UciError::InvalidLength(0)));
+    assert_eq!(parse_uci("e2e"), Err(UciError::InvalidLength(3)));
+    assert_eq!(parse_uci("e2e4qq"), Err(UciError::InvalidLength(6)));
+    assert_eq!(
+        parse_uci("i2e4"),
+        Err(UciError::InvalidSquare("i2".to_string()))
+    );
+    assert_eq!(
+        parse_uci("e2e9"),
+        Err(UciError::InvalidSquare("e9".to_string()))
+    );
+    assert_eq!(parse_uci("e7e8k"), Err(UciError::InvalidPromotion('k')));
+    // Multi-byte characters must not panic on slicing
+    assert!(parse_uci("é2e4").is_err());
+}
+
+#[test]
+fn test_data_dir_is_chess_tui_folder() {
+    let dir = chess_tui::constants::data_dir().unwrap();
+    assert!(dir.ends_with("chess-tui") || dir.ends_with(chess_tui::constants::FALLBACK_DATA_DIR));
+}